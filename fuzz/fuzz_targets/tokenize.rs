@@ -0,0 +1,9 @@
+#![no_main]
+
+use bash_tokenizer::tokenize;
+use libfuzzer_sys::fuzz_target;
+
+// tokenize should never panic or hang, errors are fine :
+fuzz_target!(|data: &str| {
+    let _ = tokenize(data.to_string());
+});
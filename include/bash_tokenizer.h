@@ -0,0 +1,89 @@
+#ifndef BASH_TOKENIZER_H
+#define BASH_TOKENIZER_H
+
+/* Generated by cbindgen from src/ffi.rs, don't edit by hand. */
+
+#include <stddef.h>
+
+// The tokens of a script, see [bash_tokenize]. C code only handles it through a pointer.
+typedef struct BashTokenList BashTokenList;
+
+// The location of a token as byte offsets into the script, `end` is exclusive
+typedef struct {
+  size_t start;
+  size_t end;
+} BashSpan;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// bash_tokenize
+// Tokenizes the `len` bytes of UTF-8 at `script`, which don't need a terminating NUL. `dialect` is the
+// NUL-terminated name of a preset, `"bash"`, `"posix"`, `"dash"`, `"ksh93"`, `"zsh"` or `"ash"`, or NULL for
+// bash.
+//
+// Returns the tokens, to free with [bash_token_list_free]. When the script can't be tokenized it returns
+// NULL and, if `error` isn't NULL, points `*error` to the message, to free with [bash_error_free].
+//
+// # Safety
+// `script` must point to `len` readable bytes, it can be NULL when `len` is 0. `dialect` must be NULL or a
+// NUL-terminated string and `error` NULL or writable.
+BashTokenList *bash_tokenize(const char *script,
+                             size_t len,
+                             const char *dialect,
+                             char **error);
+
+// bash_token_list_len
+// The number of tokens in `list`, 0 for NULL.
+//
+// # Safety
+// `list` must be NULL or returned by [bash_tokenize] and not freed yet.
+size_t bash_token_list_len(const BashTokenList *list);
+
+// bash_token_kind
+// The kind of the token at `index` as a NUL-terminated name, `"Word"`, `"Pipe"`, ... like the JSON of the
+// `serde` feature. NULL when `index` is out of range. The name lives as long as `list`.
+//
+// # Safety
+// `list` must be NULL or returned by [bash_tokenize] and not freed yet.
+const char *bash_token_kind(const BashTokenList *list,
+                            size_t index);
+
+// bash_token_text
+// The text of the token at `index`, NUL-terminated, with its length in bytes in `*len` unless `len` is NULL.
+// The length is the way to go for scripts with NUL bytes. NULL when `index` is out of range. The text lives
+// as long as `list`.
+//
+// # Safety
+// `list` must be NULL or returned by [bash_tokenize] and not freed yet, `len` NULL or writable.
+const char *bash_token_text(const BashTokenList *list,
+                            size_t index,
+                            size_t *len);
+
+// bash_token_span
+// The span of the token at `index`, `{0, 0}` when `index` is out of range.
+//
+// # Safety
+// `list` must be NULL or returned by [bash_tokenize] and not freed yet.
+BashSpan bash_token_span(const BashTokenList *list, size_t index);
+
+// bash_token_list_free
+// Frees the tokens returned by [bash_tokenize], doing nothing for NULL.
+//
+// # Safety
+// `list` must be NULL or returned by [bash_tokenize], and not be used or freed again afterwards.
+void bash_token_list_free(BashTokenList *list);
+
+// bash_error_free
+// Frees an error message from [bash_tokenize], doing nothing for NULL.
+//
+// # Safety
+// `error` must be NULL or a message set by [bash_tokenize], and not be used or freed again afterwards.
+void bash_error_free(char *error);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* BASH_TOKENIZER_H */
@@ -0,0 +1,343 @@
+use crate::dialect::Dialect;
+use crate::lexer::case_pattern::COMMAND_PREFIXES;
+use crate::lexer::keyword::mark_keywords;
+use crate::parser::is_assignment;
+use crate::tokens::span::Span;
+use crate::tokens::tokens::{Token, TokenKind};
+use crate::Lexer;
+use std::collections::HashMap;
+use std::error::Error;
+use std::rc::Rc;
+
+/// tokenize_with_aliases
+/// > [6.6 Aliases](https://www.gnu.org/software/bash/manual/bash.html#Aliases)
+/// > Aliases allow a string to be substituted for a word that is in a position in the input where it can be
+/// > the first word of a simple command. [...] The first word of the replacement text is tested for aliases,
+/// > but a word that is identical to an alias being expanded is not expanded a second time. [...] If the last
+/// > character of the alias value is a blank, then the next command word following the alias is also checked
+/// > for alias expansion.
+///
+/// Like [tokenize_with_dialect](crate::tokenize_with_dialect), with the unquoted command words found in
+/// `aliases` replaced by their value : `ll` becomes `ls -l` with `alias ll='ls -l'`. Like in bash, the value
+/// is read together with the rest of the input, so `alias q="echo '"` opens a quote that the input closes.
+///
+/// The tokens of a value remember the alias they come from and all have the span of the word they replaced,
+/// so [render](crate::render) gives the expanded script but spans can repeat and overlap : a token that
+/// starts in a value and ends after it spans from the replaced word to its end in the input.
+pub fn tokenize_with_aliases(
+    s: String,
+    dialect: &Dialect,
+    aliases: &HashMap<String, String>,
+) -> Result<Vec<Token>, Box<dyn Error>> {
+    let mut expander = AliasExpander {
+        aliases,
+        origins: s
+            .char_indices()
+            .map(|(offset, char)| Origin {
+                span: Span::new(offset, offset + char.len_utf8()),
+                alias: None,
+            })
+            .collect(),
+        lexer: Lexer::new(s.chars().collect(), dialect),
+        command_start: true,
+        redirection: false,
+        blank_end: None,
+        word: None,
+    };
+    let mut result = Vec::new();
+    loop {
+        if expander.expand()? {
+            continue;
+        }
+        match expander.next()? {
+            Some(token) => result.push(token),
+            None => break,
+        }
+    }
+    mark_keywords(&mut result);
+    Ok(result)
+}
+
+/// Where a character of the expanded input comes from
+#[derive(Clone)]
+struct Origin {
+    span: Span, // The character in the input, or the word an alias replaced
+    alias: Option<Rc<Expansion>>,
+}
+
+/// An alias whose value is being read, `parent` is the alias the replaced word came from
+struct Expansion {
+    name: String,
+    parent: Option<Rc<Expansion>>,
+}
+
+impl Expansion {
+    /// Whether `name` is this alias or one it was expanded from
+    fn expands(&self, name: &str) -> bool {
+        self.name == name
+            || self
+                .parent
+                .as_ref()
+                .is_some_and(|parent| parent.expands(name))
+    }
+}
+
+struct AliasExpander<'a> {
+    aliases: &'a HashMap<String, String>,
+    lexer: Lexer<'a>,
+    origins: Vec<Origin>, // One for every character of `lexer.chars`
+    command_start: bool,
+    redirection: bool,        // The next word is the target of a redirection
+    blank_end: Option<usize>, // The end of a value ending in a blank, the next word is a command word
+    word: Option<String>,     // The word being read, as long as tokens are adjacent
+}
+
+impl AliasExpander<'_> {
+    /// Replaces the alias at the pointer with its value, returns whether there was one
+    fn expand(&mut self) -> Result<bool, Box<dyn Error>> {
+        let pointer = self.lexer.pointer;
+        if self.word.is_some() || self.redirection {
+            return Ok(false);
+        }
+        if self.blank_end.is_some_and(|blank_end| pointer >= blank_end) {
+            self.blank_end = None;
+            self.command_start = true;
+        }
+        let Some(end) = self
+            .command_start
+            .then(|| self.lexer.word_end())
+            .transpose()?
+            .flatten()
+        else {
+            return Ok(false);
+        };
+        let name: String = self.lexer.chars[pointer..end].iter().collect();
+        let origin = &self.origins[pointer];
+        let value = match self.aliases.get(&name) {
+            Some(_)
+                if origin
+                    .alias
+                    .as_ref()
+                    .is_some_and(|alias| alias.expands(&name)) =>
+            {
+                return Ok(false)
+            }
+            Some(value) => value,
+            None => return Ok(false),
+        };
+
+        let value: Vec<char> = value.chars().collect();
+        let replaced = Origin {
+            span: Span::new(origin.span.start, self.origins[end - 1].span.end),
+            alias: Some(Rc::new(Expansion {
+                name,
+                parent: origin.alias.clone(),
+            })),
+        };
+        self.origins
+            .splice(pointer..end, value.iter().map(|_| replaced.clone()));
+        self.blank_end = match value.last() {
+            Some(' ' | '\t') => Some(pointer + value.len()),
+            // A value ending in a blank can't end inside the replaced word :
+            _ => self
+                .blank_end
+                .map(|blank_end| blank_end - (end - pointer) + value.len()),
+        };
+        self.lexer.splice(end, &value);
+        Ok(true)
+    }
+
+    /// Reads the next token, `None` at the end of the input
+    fn next(&mut self) -> Result<Option<Token>, Box<dyn Error>> {
+        let start = self.lexer.pointer;
+        let (kind, text) = match self.lexer.next() {
+            Ok(Some(token)) => token,
+            Ok(None) => return Ok(None),
+            Err(error) => {
+                let origin = &self.origins[start];
+                return Err(match &origin.alias {
+                    Some(alias) => format!(
+                        "Cannot tokenize alias `{}` at index {}: {}",
+                        alias.name, origin.span.start, error
+                    )
+                    .into(),
+                    None => error,
+                });
+            }
+        };
+
+        if kind.is_word() {
+            self.word.get_or_insert_with(String::new).push_str(&text);
+        } else {
+            if let Some(word) = self.word.take() {
+                self.end_word(&word, kind);
+            }
+            self.operator(kind);
+        }
+        let origin = &self.origins[start];
+        Ok(Some(Token {
+            kind,
+            text,
+            span: Span::new(
+                origin.span.start,
+                self.origins[self.lexer.pointer - 1].span.end,
+            ),
+            alias: origin.alias.as_ref().map(|alias| alias.name.clone()),
+        }))
+    }
+
+    /// Follows what comes after `word`, `kind` is the kind of the token after it
+    fn end_word(&mut self, word: &str, kind: TokenKind) {
+        let is_io_number = word.chars().all(|char| char.is_ascii_digit()) && kind.is_redirection();
+        if self.redirection {
+            self.redirection = false;
+        } else if !is_io_number {
+            // Assignments and reserved words like `then` are followed by a command word :
+            self.command_start =
+                self.command_start && (is_assignment(word) || COMMAND_PREFIXES.contains(&word));
+        }
+    }
+
+    /// Follows what comes after a token that isn't part of a word
+    fn operator(&mut self, kind: TokenKind) {
+        match kind {
+            TokenKind::WhiteSpace | TokenKind::Comment | TokenKind::HereDocument => {}
+            kind if kind.is_redirection() => self.redirection = true,
+            _ => {
+                self.command_start = true;
+                self.redirection = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alias::tokenize_with_aliases;
+    use crate::dialect::Dialect;
+    use crate::render;
+    use crate::tokens::span::Span;
+    use crate::tokens::tokens::{Token, TokenKind};
+    use std::collections::HashMap;
+
+    fn aliases(definitions: &[(&str, &str)]) -> HashMap<String, String> {
+        definitions
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn expand(script: &str, definitions: &[(&str, &str)]) -> String {
+        let tokens =
+            tokenize_with_aliases(script.to_string(), &Dialect::BASH, &aliases(definitions))
+                .unwrap();
+        render(&tokens)
+    }
+
+    #[test]
+    fn test_tokenize_with_aliases() {
+        let tokens = tokenize_with_aliases(
+            String::from("ll /tmp"),
+            &Dialect::BASH,
+            &aliases(&[("ll", "ls -l")]),
+        )
+        .unwrap();
+        let from_alias = |kind, text| Token {
+            alias: Some(String::from("ll")),
+            ..Token::new(kind, text, Span::new(0, 2))
+        };
+        assert_eq!(
+            vec![
+                from_alias(TokenKind::Word, "ls"),
+                from_alias(TokenKind::WhiteSpace, " "),
+                from_alias(TokenKind::Word, "-l"),
+                Token::new(TokenKind::WhiteSpace, " ", Span::new(2, 3)),
+                Token::new(TokenKind::Word, "/tmp", Span::new(3, 7)),
+            ],
+            tokens
+        );
+
+        let ll = [("ll", "ls -l")];
+        assert_eq!(
+            "ls -l; ls -l | ls -l && (ls -l)\nif ls -l; then ls -l; fi",
+            expand("ll; ll | ll && (ll)\nif ll; then ll; fi", &ll)
+        );
+        assert_eq!(
+            "A=1 ls -l >out ll; >out ls -l 2>err",
+            expand("A=1 ll >out ll; >out ll 2>err", &ll)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_with_aliases_not_command_words() {
+        let ll = [("ll", "ls -l")];
+        assert_eq!("echo ll", expand("echo ll", &ll));
+        assert_eq!("cat <ll", expand("cat <ll", &ll));
+        assert_eq!(
+            "\\ll; 'll'; ll\"\"; ll=1",
+            expand("\\ll; 'll'; ll\"\"; ll=1", &ll)
+        );
+        assert_eq!(
+            "for ll in ll; do :; done",
+            expand("for ll in ll; do :; done", &ll)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_with_aliases_recursion() {
+        assert_eq!("ls -F x", expand("ls x", &[("ls", "ls -F")]));
+        assert_eq!("a", expand("a", &[("a", "b"), ("b", "a")]));
+        assert_eq!(
+            "ls -l; ls -l",
+            expand("l; ll", &[("l", "ll"), ("ll", "ls -l")])
+        );
+        let tokens = tokenize_with_aliases(
+            String::from("echo; l"),
+            &Dialect::BASH,
+            &aliases(&[("l", "ll"), ("ll", "ls -l")]),
+        )
+        .unwrap();
+        assert_eq!(Some(String::from("ll")), tokens[3].alias);
+        assert_eq!(Span::new(6, 7), tokens[3].span);
+    }
+
+    #[test]
+    fn test_tokenize_with_aliases_trailing_blank() {
+        let definitions = [("sudo", "sudo "), ("ll", "ls -l"), ("up", "cd ..; ")];
+        assert_eq!("sudo  ls -l", expand("sudo ll", &definitions));
+        assert_eq!("sudo  sudo  ls -l", expand("sudo sudo ll", &definitions));
+        assert_eq!("cd ..;  ls -l", expand("up ll", &definitions));
+        assert_eq!("echo ll", expand("echo ll", &definitions));
+    }
+
+    #[test]
+    fn test_tokenize_with_aliases_read_with_input() {
+        // The value opens a quote or a comment that goes on in the input :
+        let tokens = tokenize_with_aliases(
+            String::from("q a' b"),
+            &Dialect::BASH,
+            &aliases(&[("q", "echo '")]),
+        )
+        .unwrap();
+        assert_eq!("echo ' a' b", render(&tokens));
+        let quote = Token {
+            alias: Some(String::from("q")),
+            ..Token::new(TokenKind::SingleQuote, "' a'", Span::new(0, 4))
+        };
+        assert_eq!(quote, tokens[2]);
+        assert_eq!(
+            "echo # ll\nls -l",
+            expand("c ll\nll", &[("c", "echo #"), ("ll", "ls -l")])
+        );
+        assert_eq!(
+            "Cannot tokenize alias `q` at index 0: Unterminated string literal at index 5",
+            tokenize_with_aliases(
+                String::from("q"),
+                &Dialect::BASH,
+                &aliases(&[("q", "echo '")])
+            )
+            .unwrap_err()
+            .to_string()
+        );
+    }
+}
@@ -0,0 +1,504 @@
+use crate::dialect::Dialect;
+use crate::lexer::arithmetic_expansion::tokenize_arithmetic_expansion;
+use crate::lexer::dollar_single_quotes::decode_dollar_single_quotes;
+use crate::lexer::nested::tokenize_expansion;
+use crate::parser::is_assignment;
+use crate::tokenize;
+use crate::tokens::tokens::{Token, TokenKind};
+use std::error::Error;
+use std::ffi::OsString;
+
+/// to_argv
+/// Returns the arguments `execve` would receive for the simple command `command`, the first one being the
+/// program. Quotes are removed, `$'...'` and backslash escapes are decoded and adjacent parts like `'a'"b"c`
+/// are joined into a single argument. Variable assignments before the program (`A=b cmd`, `A+=b`, `A[0]=b`)
+/// and redirections (`2>/dev/null`) aren't part of argv and are left out, whatever their value.
+///
+/// Anything that depends on the environment the command runs in can't be evaluated statically and results in
+/// an error : parameter expansions, command substitutions, arithmetic expansions, tilde prefixes, brace
+/// expansions and pathname expansions. As do operators and reserved words (`!`, `time`, `if`, ...) that
+/// make it more than a simple command.
+pub fn to_argv(command: &str) -> Result<Vec<OsString>, Box<dyn Error>> {
+    let tokens = tokenize(command.to_string())?;
+    let mut argv: Vec<Vec<u8>> = Vec::new();
+    let mut word: Option<Vec<u8>> = None;
+
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = &tokens[index];
+        let (text, span) = (token.text.as_str(), token.span);
+        index += 1;
+
+        // Tokens that end the current word :
+        if token.kind.is_redirection() {
+            if is_io_number(&tokens, index - 1) {
+                word = None; // `2>` : The number is part of the redirection
+            }
+            index = skip_redirection(&tokens, index)?;
+            continue;
+        }
+        match token.kind {
+            TokenKind::WhiteSpace
+            | TokenKind::Newline
+            | TokenKind::Comment
+            | TokenKind::HereDocument => {
+                let is_last = tokens[index..].iter().all(|token| {
+                    matches!(
+                        token.kind,
+                        TokenKind::WhiteSpace
+                            | TokenKind::Newline
+                            | TokenKind::Comment
+                            | TokenKind::HereDocument
+                    )
+                });
+                if token.kind == TokenKind::Newline && !is_last {
+                    return Err(format!(
+                        "Expected a simple command, found a newline at index {}",
+                        span.start
+                    )
+                    .into());
+                }
+                if let Some(bytes) = word.take() {
+                    argv.push(bytes);
+                }
+                continue;
+            }
+            TokenKind::Pipe
+            | TokenKind::Ampersand
+            | TokenKind::PipeAmpersand
+            | TokenKind::Semicolon
+            | TokenKind::LParenthesis
+            | TokenKind::RParenthesis
+            | TokenKind::CasePatternStart
+            | TokenKind::CasePatternSeparator
+            | TokenKind::CasePatternEnd
+            | TokenKind::AndIf
+            | TokenKind::OrIf
+            | TokenKind::DoubleSemicolon
+            | TokenKind::SemicolonAmpersand
+            | TokenKind::DoubleSemicolonAmpersand
+            | TokenKind::ArithmeticCommand => {
+                return Err(format!(
+                    "Expected a simple command, found `{}` at index {}",
+                    text, span.start
+                )
+                .into());
+            }
+            _ => {}
+        }
+
+        // Assignments in front of the program and reserved words where a command starts :
+        if word.is_none() && argv.is_empty() {
+            let word_text: String = tokens[index - 1..]
+                .iter()
+                .take_while(|token| token.kind.is_word())
+                .map(|token| token.text.as_str())
+                .collect();
+            if token.kind == TokenKind::Keyword {
+                return Err(format!(
+                    "Expected a simple command, found the reserved word `{}` at index {}",
+                    text, span.start
+                )
+                .into());
+            }
+            if matches!(token.kind, TokenKind::Word | TokenKind::Pattern)
+                && is_assignment(&word_text)
+            {
+                while tokens.get(index).is_some_and(|token| token.kind.is_word()) {
+                    index += 1;
+                }
+                continue;
+            }
+        }
+
+        // Tokens that are (part of) a word :
+        let bytes = match token.kind {
+            TokenKind::Word | TokenKind::Keyword => decode_word(text),
+            TokenKind::Number => text.as_bytes().to_vec(),
+            TokenKind::Pattern => {
+                return Err(cannot_evaluate("pathname expansion", text, span.start));
+            }
+            TokenKind::TildePrefix => {
+                return Err(cannot_evaluate("tilde prefix", text, span.start));
+            }
+            TokenKind::HistoryExpansion => {
+                return Err(cannot_evaluate("history expansion", text, span.start));
+            }
+            TokenKind::SingleQuote => text.as_bytes()[1..text.len() - 1].to_vec(),
+            TokenKind::DollarSingleQuote => decode_dollar_single_quotes(text),
+            TokenKind::DoubleQuote => decode_double_quotes(text, span.start)?,
+            // Without a message catalog the translation is the string itself :
+            TokenKind::DollarDoubleQuote => decode_double_quotes(&text[1..], span.start + 1)?,
+            _ => return Err(cannot_evaluate(expansion_name(text), text, span.start)),
+        };
+        if bytes.is_empty() && token.kind == TokenKind::Word && word.is_none() {
+            continue; // Only line continuations, which don't start a word
+        }
+        word.get_or_insert_with(Vec::new).extend(bytes);
+    }
+    argv.extend(word);
+
+    Ok(argv.into_iter().map(to_os_string).collect())
+}
+
+/// Whether the token at `index` is a number directly in front of a redirection operator, like in `2>file`.
+fn is_io_number(tokens: &[Token], index: usize) -> bool {
+    index >= 1
+        && tokens[index - 1].kind == TokenKind::Number
+        && (index == 1 || tokens[index - 2].kind == TokenKind::WhiteSpace)
+}
+
+/// Skips the target of a redirection operator, returns the index of the first token after it.
+fn skip_redirection(tokens: &[Token], mut index: usize) -> Result<usize, Box<dyn Error>> {
+    let operator_start = tokens[index - 1].span.start;
+    while tokens
+        .get(index)
+        .is_some_and(|token| token.kind == TokenKind::WhiteSpace)
+    {
+        index += 1;
+    }
+    let target_start = index;
+    while tokens.get(index).is_some_and(|token| token.kind.is_word()) {
+        index += 1;
+    }
+    if index == target_start {
+        return Err(format!("Missing redirection target at index {}", operator_start).into());
+    }
+    Ok(index)
+}
+
+/// Removes backslash escapes from an unquoted word
+fn decode_word(text: &str) -> Vec<u8> {
+    let mut decoded = String::new();
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => match chars.next() {
+                Some('\n') => {} // Line continuation
+                Some(escaped) => decoded.push(escaped),
+                None => decoded.push('\\'),
+            },
+            char => decoded.push(char),
+        }
+    }
+    decoded.into_bytes()
+}
+
+/// Removes the quotes and backslash escapes from a double-quoted string, `offset` is where the string is
+/// located in the command.
+fn decode_double_quotes(text: &str, offset: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    let inner = &text[1..text.len() - 1];
+    let inner_chars: Vec<char> = inner.chars().collect();
+    let mut decoded = String::new();
+    let mut chars = inner.char_indices().enumerate();
+    while let Some((pointer, (position, char))) = chars.next() {
+        match char {
+            // > The backslash retains its special meaning only when followed by one of the following
+            // > characters: ‘$’, ‘`’, ‘"’, ‘\’, or newline.
+            '\\' => match chars.next() {
+                Some((_, (_, '\n'))) => {}
+                Some((_, (_, escaped @ ('$' | '`' | '"' | '\\')))) => decoded.push(escaped),
+                Some((_, (_, escaped))) => {
+                    decoded.push('\\');
+                    decoded.push(escaped);
+                }
+                None => decoded.push('\\'),
+            },
+            '$' | '`' => {
+                let expansion_pointer = tokenize_expansion(&inner_chars, pointer, &Dialect::BASH)?;
+                if expansion_pointer == pointer {
+                    decoded.push(char); // A lone `$`
+                    continue;
+                }
+                let expansion: String = inner_chars[pointer..expansion_pointer].iter().collect();
+                return Err(cannot_evaluate(
+                    expansion_name(&expansion),
+                    &expansion,
+                    offset + 1 + position,
+                ));
+            }
+            char => decoded.push(char),
+        }
+    }
+    Ok(decoded.into_bytes())
+}
+
+fn expansion_name(expansion: &str) -> &'static str {
+    let chars: Vec<char> = expansion.chars().collect();
+    if matches!(tokenize_arithmetic_expansion(&chars, 0, &Dialect::BASH), Ok(pointer) if pointer != 0)
+    {
+        "arithmetic expansion"
+    } else if expansion.starts_with("$(") || expansion.starts_with('`') {
+        "command substitution"
+    } else if expansion.starts_with("<(") || expansion.starts_with(">(") {
+        "process substitution"
+    } else if expansion.starts_with('{') {
+        "brace expansion"
+    } else {
+        "parameter expansion"
+    }
+}
+
+pub(crate) fn cannot_evaluate(name: &str, text: &str, index: usize) -> Box<dyn Error> {
+    format!(
+        "Cannot statically evaluate {} `{}` at index {}",
+        name, text, index
+    )
+    .into()
+}
+
+#[cfg(unix)]
+fn to_os_string(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+#[cfg(not(unix))]
+fn to_os_string(bytes: Vec<u8>) -> OsString {
+    String::from_utf8_lossy(&bytes).into_owned().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::argv::to_argv;
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::quote::join;
+    #[cfg(not(target_arch = "wasm32"))]
+    use proptest::prelude::*;
+    #[cfg(not(target_arch = "wasm32"))]
+    use std::ffi::OsString;
+
+    fn argv(command: &str) -> Vec<String> {
+        to_argv(command)
+            .unwrap()
+            .into_iter()
+            .map(|arg| arg.into_string().unwrap())
+            .collect()
+    }
+
+    fn error(command: &str) -> String {
+        to_argv(command).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_to_argv_basic() {
+        assert_eq!(vec!["ls", "-l", "/tmp"], argv("ls -l /tmp"));
+        assert_eq!(vec!["ls"], argv("  ls  \n"));
+        assert_eq!(Vec::<String>::new(), argv(""));
+        assert_eq!(vec!["sleep", "10"], argv("sleep 10"));
+    }
+
+    #[test]
+    fn test_to_argv_quote_removal() {
+        assert_eq!(vec!["echo", "hello world"], argv("echo 'hello world'"));
+        assert_eq!(vec!["echo", "hello world"], argv("echo \"hello world\""));
+        assert_eq!(vec!["echo", "abc"], argv("echo 'a'\"b\"c"));
+        assert_eq!(vec!["echo", ""], argv("echo ''"));
+        assert_eq!(vec!["echo", "", ""], argv("echo \"\" ''"));
+        assert_eq!(vec!["echo", "a\nb"], argv("echo $'a\\nb'"));
+        assert_eq!(vec!["echo", "it's"], argv("echo it\\'s"));
+        assert_eq!(vec!["echo", "a b"], argv("echo a\\ b"));
+        assert_eq!(vec!["echo", "$HOME"], argv("echo \\$HOME"));
+        assert_eq!(vec!["echo", "$HOME"], argv("echo '$HOME'"));
+        assert_eq!(vec!["echo", "a\"$`\\\\b"], argv(r#"echo "a\"\$\`\\\b""#));
+        assert_eq!(vec!["echo", "cost: $"], argv("echo \"cost: $\""));
+    }
+
+    #[test]
+    fn test_to_argv_line_continuation() {
+        assert_eq!(vec!["ls", "-l"], argv("ls \\\n-l"));
+        assert_eq!(vec!["ls", "-l"], argv("ls -\\\nl"));
+        assert_eq!(vec!["echo", "ab"], argv("echo \"a\\\nb\""));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_to_argv_invalid_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+        assert_eq!(
+            vec![
+                OsString::from("printf"),
+                OsString::from_vec(vec![0xff, b'a'])
+            ],
+            to_argv("printf $'\\xffa'").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_to_argv_assignments_and_redirections() {
+        assert_eq!(vec!["make", "all"], argv("CC=gcc make all"));
+        assert_eq!(vec!["make", "CC=gcc"], argv("make CC=gcc"));
+        assert_eq!(vec!["ls"], argv("A=1 B='x y' ls"));
+        assert_eq!(vec!["ls"], argv("A+=1 A[0]=1 A[1]+=x ls"));
+        assert_eq!(vec!["ls"], argv("A=$HOME B=*.txt C=~ ls"));
+        assert_eq!(vec!["ls", "A=1"], argv("ls A=1"));
+        assert_eq!(vec!["A=1", "A"], argv("'A'=1 A"));
+        assert_eq!(vec!["ls", "-l"], argv("ls -l > out.txt"));
+        assert_eq!(vec!["ls", "-l"], argv("ls 2>/dev/null -l"));
+        assert_eq!(vec!["ls", "-l"], argv("ls -l 2>&1"));
+        assert_eq!(vec!["ls", "-l"], argv("ls -l >>'log file'"));
+        assert_eq!(vec!["ls", "-l"], argv("ls -l &>/dev/null"));
+        assert_eq!(vec!["echo", "a2"], argv("echo a2>out"));
+        assert_eq!(vec!["cat"], argv("cat < \"$FILE\""));
+        assert_eq!(vec!["cat"], argv("cat <<EOF\nhello\nEOF\n"));
+        assert_eq!(vec!["cat", "-n"], argv("cat <<-'EOF' -n\n\thello\n\tEOF"));
+        assert_eq!(vec!["ls"], argv("ls >|out <>rw 0<&3"));
+        assert_eq!(vec!["cat"], argv("cat <<< 'a b'"));
+        assert_eq!("Missing redirection target at index 3", error("ls >"));
+    }
+
+    #[test]
+    fn test_to_argv_not_a_simple_command() {
+        assert_eq!(
+            "Expected a simple command, found `|` at index 3",
+            error("ls | cat")
+        );
+        assert_eq!(
+            "Expected a simple command, found `;` at index 2",
+            error("ls; id")
+        );
+        assert_eq!(
+            "Expected a simple command, found `&` at index 3",
+            error("ls & id")
+        );
+        assert_eq!(
+            "Expected a simple command, found a newline at index 2",
+            error("ls\nid")
+        );
+        assert_eq!(
+            "Expected a simple command, found `&&` at index 3",
+            error("ls && id")
+        );
+        assert_eq!(
+            "Expected a simple command, found `((x++))` at index 0",
+            error("((x++))")
+        );
+    }
+
+    #[test]
+    fn test_to_argv_reserved_words() {
+        assert_eq!(
+            "Expected a simple command, found the reserved word `!` at index 0",
+            error("! ls")
+        );
+        assert_eq!(
+            "Expected a simple command, found the reserved word `time` at index 1",
+            error(" time ls")
+        );
+        assert_eq!(
+            "Expected a simple command, found the reserved word `coproc` at index 0",
+            error("coproc x")
+        );
+        assert_eq!(
+            "Expected a simple command, found the reserved word `if` at index 0",
+            error("if true")
+        );
+        assert_eq!(
+            "Expected a simple command, found the reserved word `{` at index 0",
+            error("{ ls")
+        );
+        assert_eq!(vec!["echo", "if", "!"], argv("echo if !"));
+        assert_eq!(vec!["if", "time"], argv("'if' time"));
+        assert_eq!(vec!["if"], argv("\\if"));
+        assert_eq!(vec!["iffy"], argv("iffy"));
+        assert_eq!(vec!["if"], argv("A=1 if"));
+    }
+
+    #[test]
+    fn test_to_argv_bash_syntax() {
+        assert_eq!(vec!["echo", "hello world"], argv("echo $\"hello world\""));
+        assert_eq!(
+            "Cannot statically evaluate process substitution `<(ls)` at index 5",
+            error("diff <(ls) b")
+        );
+        assert_eq!(
+            "Expected a simple command, found `|&` at index 3",
+            error("ls |& cat")
+        );
+    }
+
+    #[test]
+    fn test_to_argv_expansions() {
+        assert_eq!(
+            "Cannot statically evaluate parameter expansion `$HOME` at index 5",
+            error("echo $HOME")
+        );
+        assert_eq!(
+            "Cannot statically evaluate parameter expansion `${x:-a}` at index 6",
+            error("echo a${x:-a}")
+        );
+        assert_eq!(
+            "Cannot statically evaluate command substitution `$(id)` at index 6",
+            error("echo \"$(id)\"")
+        );
+        assert_eq!(
+            "Cannot statically evaluate command substitution ``id`` at index 5",
+            error("echo `id`")
+        );
+        assert_eq!(
+            "Cannot statically evaluate arithmetic expansion `$((1+2))` at index 8",
+            error("echo \"a $((1+2))\"")
+        );
+        assert_eq!(
+            "Cannot statically evaluate pathname expansion `*.txt` at index 3",
+            error("ls *.txt")
+        );
+        assert_eq!(
+            "Cannot statically evaluate pathname expansion `file[12]` at index 3",
+            error("ls file[12]")
+        );
+        assert_eq!(
+            "Cannot statically evaluate tilde prefix `~` at index 3",
+            error("ls ~/bin")
+        );
+        assert_eq!(
+            "Cannot statically evaluate tilde prefix `~alice` at index 7",
+            error("ls a:b ~alice")
+        );
+        assert_eq!(
+            "Cannot statically evaluate brace expansion `{a,b}` at index 3",
+            error("ls {a,b}.txt")
+        );
+        assert_eq!(
+            "Cannot statically evaluate brace expansion `{1..3}` at index 7",
+            error("touch a{1..3}")
+        );
+        assert_eq!(vec!["ls", "a~", "[", "{}", "{a}"], argv("ls a~ [ {} {a}"));
+        assert_eq!(vec!["ls", "{a,b}", "{a,b}"], argv("ls '{a,b}' \\{a,b}"));
+        assert_eq!(vec!["ls", "*.txt"], argv("ls '*.txt'"));
+        assert_eq!(
+            vec!["ls", "~root", "a:~", "~/x"],
+            argv("ls ~'root' a:~ \\~/x")
+        );
+    }
+
+    #[test]
+    fn test_to_argv_comments() {
+        assert_eq!(vec!["ls", "-l"], argv("ls -l # list files"));
+        assert_eq!(vec!["ls", "-l"], argv("ls -l # list files\n"));
+        assert_eq!(vec!["ls", "-l#x"], argv("ls -l#x"));
+    }
+
+    #[test]
+    fn test_to_argv_lexer_errors() {
+        assert_eq!("Unterminated string literal at index 5", error("echo 'abc"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_to_argv_inverts_join_assignments() {
+        for args in [["FOO+=bar", "ls"], ["a+=", "b"], ["a=b", "c"]] {
+            assert_eq!(args.to_vec(), argv(&join(&args)));
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    proptest! {
+        #[test]
+        fn test_to_argv_inverts_join(args in prop::collection::vec("[^\\x00]*", 1..5)) {
+            let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+            let expected: Vec<OsString> = args.iter().map(OsString::from).collect();
+            prop_assert_eq!(expected, to_argv(&join(&args)).unwrap());
+        }
+    }
+}
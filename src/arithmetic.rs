@@ -0,0 +1,890 @@
+use crate::tokens::span::Span;
+use std::collections::HashMap;
+use std::error::Error;
+
+/// An arithmetic expression, `span` is where it's located in the text it was parsed from.
+/// > [6.5 Shell Arithmetic](https://www.gnu.org/software/bash/manual/bash.html#Shell-Arithmetic)
+/// > Evaluation is done in fixed-width integers with no check for overflow, though division by 0 is trapped
+/// > and flagged as an error.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExpressionKind {
+    Number(i64),
+    Variable(String), // x, $x, ${x}
+    Unary(UnaryOperator, Box<Expression>),
+    Increment(IncrementOperator, String),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Conditional(Box<Expression>, Box<Expression>, Box<Expression>), // a ? b : c
+    Assignment {
+        name: String,
+        operator: Option<BinaryOperator>, // `+` for `+=`, `None` for `=`
+        value: Box<Expression>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnaryOperator {
+    Plus,       // +
+    Minus,      // -
+    Not,        // !
+    BitwiseNot, // ~
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IncrementOperator {
+    PreIncrement,  // ++x
+    PreDecrement,  // --x
+    PostIncrement, // x++
+    PostDecrement, // x--
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BinaryOperator {
+    Power,        // **
+    Multiply,     // *
+    Divide,       // /
+    Remainder,    // %
+    Add,          // +
+    Subtract,     // -
+    ShiftLeft,    // <<
+    ShiftRight,   // >>
+    Less,         // <
+    LessEqual,    // <=
+    Greater,      // >
+    GreaterEqual, // >=
+    Equal,        // ==
+    NotEqual,     // !=
+    BitwiseAnd,   // &
+    BitwiseXor,   // ^
+    BitwiseOr,    // |
+    And,          // &&
+    Or,           // ||
+    Comma,        // ,
+}
+
+/// How deep variables can refer to other variables, so `a=b b=a` ends with an error instead of a stack overflow
+const MAX_RECURSION: usize = 128;
+
+/// How deep parentheses, unary operators, assignments, `?:` and `**` can nest, past this parsing returns an
+/// error instead of running out of stack
+const MAX_DEPTH: usize = 128;
+
+/// From the lowest to the highest precedence, the operators of every level are left-associative
+const PRECEDENCE: [&[(&str, BinaryOperator)]; 10] = [
+    &[("||", BinaryOperator::Or)],
+    &[("&&", BinaryOperator::And)],
+    &[("|", BinaryOperator::BitwiseOr)],
+    &[("^", BinaryOperator::BitwiseXor)],
+    &[("&", BinaryOperator::BitwiseAnd)],
+    &[
+        ("==", BinaryOperator::Equal),
+        ("!=", BinaryOperator::NotEqual),
+    ],
+    &[
+        ("<=", BinaryOperator::LessEqual),
+        (">=", BinaryOperator::GreaterEqual),
+        ("<", BinaryOperator::Less),
+        (">", BinaryOperator::Greater),
+    ],
+    &[
+        ("<<", BinaryOperator::ShiftLeft),
+        (">>", BinaryOperator::ShiftRight),
+    ],
+    &[("+", BinaryOperator::Add), ("-", BinaryOperator::Subtract)],
+    &[
+        ("*", BinaryOperator::Multiply),
+        ("/", BinaryOperator::Divide),
+        ("%", BinaryOperator::Remainder),
+    ],
+];
+
+const ASSIGNMENTS: [(&str, Option<BinaryOperator>); 11] = [
+    ("=", None),
+    ("*=", Some(BinaryOperator::Multiply)),
+    ("/=", Some(BinaryOperator::Divide)),
+    ("%=", Some(BinaryOperator::Remainder)),
+    ("+=", Some(BinaryOperator::Add)),
+    ("-=", Some(BinaryOperator::Subtract)),
+    ("<<=", Some(BinaryOperator::ShiftLeft)),
+    (">>=", Some(BinaryOperator::ShiftRight)),
+    ("&=", Some(BinaryOperator::BitwiseAnd)),
+    ("^=", Some(BinaryOperator::BitwiseXor)),
+    ("|=", Some(BinaryOperator::BitwiseOr)),
+];
+
+/// Longest first, so `<<=` isn't read as `<` followed by `<=`
+const OPERATORS: [&str; 40] = [
+    "**=", "<<=", ">>=", "**", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "++", "--", "*=",
+    "/=", "%=", "+=", "-=", "&=", "^=", "|=", "*", "/", "%", "+", "-", "<", ">", "&", "^", "|",
+    "!", "~", "?", ":", "=", ",", "(", ")",
+];
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Symbol {
+    Number(i64),
+    Name(String),
+    Operator(&'static str),
+}
+
+impl Expression {
+    /// Parses an arithmetic expression, like the contents of `$((...))`, `((...))` or an argument of `let`.
+    /// An empty expression is 0.
+    pub fn parse(content: &str) -> Result<Expression, Box<dyn Error>> {
+        parse_at(content, 0)
+    }
+
+    /// Parses an arithmetic expansion `$((...))` or an arithmetic command `((...))`, spans are located in `text`
+    pub fn parse_construct(text: &str) -> Result<Expression, Box<dyn Error>> {
+        let prefix = match text {
+            text if text.starts_with("$((") => 3,
+            text if text.starts_with("((") => 2,
+            _ => return Err(format!("Expected `$((` or `((` at index 0, found `{}`", text).into()),
+        };
+        let Some(content) = text[prefix..].strip_suffix("))") else {
+            return Err(format!("Expected `))` at index {}", text.len()).into());
+        };
+        parse_at(content, prefix)
+    }
+
+    /// Evaluates the expression with 64-bit wrapping arithmetic. Variables are read from and assigned to
+    /// `variables`, the value of a variable is itself evaluated as an expression and unset or empty ones are 0.
+    pub fn evaluate(&self, variables: &mut HashMap<String, String>) -> Result<i64, Box<dyn Error>> {
+        self.evaluate_with_depth(variables, 0)
+    }
+
+    fn evaluate_with_depth(
+        &self,
+        variables: &mut HashMap<String, String>,
+        depth: usize,
+    ) -> Result<i64, Box<dyn Error>> {
+        let evaluate = |expression: &Expression, variables: &mut HashMap<String, String>| {
+            expression.evaluate_with_depth(variables, depth)
+        };
+        Ok(match &self.kind {
+            ExpressionKind::Number(number) => *number,
+            ExpressionKind::Variable(name) => variable(name, variables, depth, self.span)?,
+            ExpressionKind::Unary(operator, operand) => {
+                let operand = evaluate(operand, variables)?;
+                match operator {
+                    UnaryOperator::Plus => operand,
+                    UnaryOperator::Minus => operand.wrapping_neg(),
+                    UnaryOperator::Not => (operand == 0) as i64,
+                    UnaryOperator::BitwiseNot => !operand,
+                }
+            }
+            ExpressionKind::Increment(operator, name) => {
+                let value = variable(name, variables, depth, self.span)?;
+                let (new_value, result) = match operator {
+                    IncrementOperator::PreIncrement => {
+                        (value.wrapping_add(1), value.wrapping_add(1))
+                    }
+                    IncrementOperator::PreDecrement => {
+                        (value.wrapping_sub(1), value.wrapping_sub(1))
+                    }
+                    IncrementOperator::PostIncrement => (value.wrapping_add(1), value),
+                    IncrementOperator::PostDecrement => (value.wrapping_sub(1), value),
+                };
+                variables.insert(name.clone(), new_value.to_string());
+                result
+            }
+            // Only one side of these is evaluated :
+            ExpressionKind::Binary(BinaryOperator::And, left, right) => {
+                (evaluate(left, variables)? != 0 && evaluate(right, variables)? != 0) as i64
+            }
+            ExpressionKind::Binary(BinaryOperator::Or, left, right) => {
+                (evaluate(left, variables)? != 0 || evaluate(right, variables)? != 0) as i64
+            }
+            ExpressionKind::Conditional(condition, then, otherwise) => {
+                match evaluate(condition, variables)? != 0 {
+                    true => evaluate(then, variables)?,
+                    false => evaluate(otherwise, variables)?,
+                }
+            }
+            ExpressionKind::Binary(operator, left, right) => {
+                let left_value = evaluate(left, variables)?;
+                let right_value = evaluate(right, variables)?;
+                apply(*operator, left_value, right_value, right.span)?
+            }
+            ExpressionKind::Assignment {
+                name,
+                operator,
+                value,
+            } => {
+                let mut result = evaluate(value, variables)?;
+                if let Some(operator) = operator {
+                    let current = variable(name, variables, depth, self.span)?;
+                    result = apply(*operator, current, result, value.span)?;
+                }
+                variables.insert(name.clone(), result.to_string());
+                result
+            }
+        })
+    }
+}
+
+/// Parses `content`, located at `offset` in the text errors and spans refer to
+pub(crate) fn parse_at(content: &str, offset: usize) -> Result<Expression, Box<dyn Error>> {
+    let symbols = lex(content, offset)?;
+    let end = offset + content.len();
+    if symbols.is_empty() {
+        return Ok(Expression {
+            kind: ExpressionKind::Number(0),
+            span: Span::new(end, end),
+        });
+    }
+    let mut parser = Parser {
+        symbols,
+        position: 0,
+        end,
+        depth: 0,
+    };
+    let expression = parser.parse_comma()?;
+    match parser.symbols.get(parser.position) {
+        Some((symbol, span)) => Err(unexpected(symbol, *span)),
+        None => Ok(expression),
+    }
+}
+
+fn variable(
+    name: &str,
+    variables: &mut HashMap<String, String>,
+    depth: usize,
+    span: Span,
+) -> Result<i64, Box<dyn Error>> {
+    let value = match variables.get(name) {
+        Some(value) if !value.trim().is_empty() => value.clone(),
+        _ => return Ok(0),
+    };
+    if depth == MAX_RECURSION {
+        return Err(format!(
+            "Expression recursion level exceeded at index {}",
+            span.start
+        )
+        .into());
+    }
+    Expression::parse(&value)?.evaluate_with_depth(variables, depth + 1)
+}
+
+/// `span` is the right operand, which a division by zero is reported at
+fn apply(
+    operator: BinaryOperator,
+    left: i64,
+    right: i64,
+    span: Span,
+) -> Result<i64, Box<dyn Error>> {
+    Ok(match operator {
+        BinaryOperator::Power => {
+            if right < 0 {
+                return Err(format!("Exponent less than 0 at index {}", span.start).into());
+            }
+            let (mut base, mut exponent, mut result) = (left, right, 1i64);
+            while exponent > 0 {
+                if exponent & 1 == 1 {
+                    result = result.wrapping_mul(base);
+                }
+                base = base.wrapping_mul(base);
+                exponent >>= 1;
+            }
+            result
+        }
+        BinaryOperator::Multiply => left.wrapping_mul(right),
+        BinaryOperator::Divide | BinaryOperator::Remainder if right == 0 => {
+            return Err(format!("Division by zero at index {}", span.start).into());
+        }
+        BinaryOperator::Divide => left.wrapping_div(right),
+        BinaryOperator::Remainder => left.wrapping_rem(right),
+        BinaryOperator::Add => left.wrapping_add(right),
+        BinaryOperator::Subtract => left.wrapping_sub(right),
+        BinaryOperator::ShiftLeft => left.wrapping_shl(right as u32),
+        BinaryOperator::ShiftRight => left.wrapping_shr(right as u32),
+        BinaryOperator::Less => (left < right) as i64,
+        BinaryOperator::LessEqual => (left <= right) as i64,
+        BinaryOperator::Greater => (left > right) as i64,
+        BinaryOperator::GreaterEqual => (left >= right) as i64,
+        BinaryOperator::Equal => (left == right) as i64,
+        BinaryOperator::NotEqual => (left != right) as i64,
+        BinaryOperator::BitwiseAnd => left & right,
+        BinaryOperator::BitwiseXor => left ^ right,
+        BinaryOperator::BitwiseOr => left | right,
+        BinaryOperator::And => (left != 0 && right != 0) as i64,
+        BinaryOperator::Or => (left != 0 || right != 0) as i64,
+        BinaryOperator::Comma => right,
+    })
+}
+
+fn lex(content: &str, offset: usize) -> Result<Vec<(Symbol, Span)>, Box<dyn Error>> {
+    let mut symbols: Vec<(Symbol, Span)> = Vec::new();
+    let mut position = 0;
+    while let Some(char) = content[position..].chars().next() {
+        let start = position;
+        let rest = &content[position..];
+        let word_length = |from: usize| {
+            rest[from..]
+                .find(|char: char| {
+                    !(char.is_ascii_alphanumeric() || ['_', '@', '#'].contains(&char))
+                })
+                .map_or(rest.len(), |length| from + length)
+        };
+        let symbol = if char.is_ascii_whitespace() {
+            position += char.len_utf8();
+            continue;
+        } else if char.is_ascii_digit() {
+            position += word_length(0);
+            Symbol::Number(number(&content[start..position], offset + start)?)
+        } else if char.is_ascii_alphabetic() || char == '_' {
+            position += name_length(rest);
+            Symbol::Name(content[start..position].to_string())
+        } else if char == '$' {
+            // `$x`, `${x}` and positional parameters are the variables they refer to :
+            let (name, length) = match rest[1..].strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], end + 3),
+                    None => ("", 0),
+                },
+                None if rest[1..].starts_with(|char: char| char.is_ascii_digit()) => {
+                    (&rest[1..2], 2)
+                }
+                None => (
+                    &rest[1..1 + name_length(&rest[1..])],
+                    1 + name_length(&rest[1..]),
+                ),
+            };
+            let is_name = !name.is_empty()
+                && (name_length(name) == name.len()
+                    || name.chars().all(|char| char.is_ascii_digit()));
+            if !is_name {
+                return Err(format!(
+                    "Cannot statically evaluate `{}` in an arithmetic expression at index {}",
+                    rest.split_whitespace().next().unwrap_or(rest),
+                    offset + start
+                )
+                .into());
+            }
+            position += length;
+            Symbol::Name(name.to_string())
+        } else if let Some(&operator) = OPERATORS
+            .iter()
+            .find(|operator| rest.starts_with(**operator))
+        {
+            // `++` and `--` are only increments next to a variable, `1--2` is `1 - -2` :
+            let previous_name = matches!(symbols.last(), Some((Symbol::Name(_), _)));
+            let next_name = rest[operator.len()..]
+                .trim_start()
+                .starts_with(|char: char| char.is_ascii_alphabetic() || char == '_' || char == '$');
+            let operator = match operator {
+                "++" | "--" if !previous_name && !next_name => &operator[..1],
+                operator => operator,
+            };
+            position += operator.len();
+            Symbol::Operator(operator)
+        } else {
+            return Err(format!(
+                "Unexpected character `{}` at index {}",
+                char,
+                offset + start
+            )
+            .into());
+        };
+        symbols.push((symbol, Span::new(offset + start, offset + position)));
+    }
+    Ok(symbols)
+}
+
+fn name_length(text: &str) -> usize {
+    match text.starts_with(|char: char| char.is_ascii_alphabetic() || char == '_') {
+        true => text
+            .find(|char: char| !char.is_ascii_alphanumeric() && char != '_')
+            .unwrap_or(text.len()),
+        false => 0,
+    }
+}
+
+/// > Constants with a leading 0 are interpreted as octal numbers. A leading ‘0x’ or ‘0X’ denotes hexadecimal.
+/// > Otherwise, numbers take the form [base#]n, where the optional base is a decimal number between 2 and 64
+/// > representing the arithmetic base, and n is a number in that base. [...] The digits greater than 9 are
+/// > represented by the lowercase letters, the uppercase letters, ‘@’, and ‘_’, in that order. If base is less
+/// > than or equal to 36, lowercase and uppercase letters may be used interchangeably.
+fn number(literal: &str, index: usize) -> Result<i64, Box<dyn Error>> {
+    let (base, digits) = if let Some((base, digits)) = literal.split_once('#') {
+        match base.parse::<u32>() {
+            Ok(base) if (2..=64).contains(&base) => (base, digits),
+            _ => {
+                return Err(format!("Invalid arithmetic base `{}` at index {}", base, index).into())
+            }
+        }
+    } else if let Some(digits) = literal
+        .strip_prefix("0x")
+        .or_else(|| literal.strip_prefix("0X"))
+    {
+        (16, digits)
+    } else if literal.len() > 1 && literal.starts_with('0') {
+        (8, &literal[1..])
+    } else {
+        (10, literal)
+    };
+
+    let mut value: i64 = 0;
+    for char in digits.chars() {
+        let digit = match char {
+            '0'..='9' => char as u32 - '0' as u32,
+            'a'..='z' => char as u32 - 'a' as u32 + 10,
+            'A'..='Z' if base <= 36 => char as u32 - 'A' as u32 + 10,
+            'A'..='Z' => char as u32 - 'A' as u32 + 36,
+            '@' => 62,
+            _ => 63, // `_`, `#` is a second base separator and too great for any base
+        };
+        if digit >= base || char == '#' {
+            return Err(
+                format!("Value too great for base `{}` at index {}", literal, index).into(),
+            );
+        }
+        value = value.wrapping_mul(base as i64).wrapping_add(digit as i64);
+    }
+    if digits.is_empty() && base != 8 {
+        return Err(format!("Invalid number `{}` at index {}", literal, index).into());
+    }
+    Ok(value)
+}
+
+fn unexpected(symbol: &Symbol, span: Span) -> Box<dyn Error> {
+    let text = match symbol {
+        Symbol::Number(number) => number.to_string(),
+        Symbol::Name(name) => name.clone(),
+        Symbol::Operator(operator) => operator.to_string(),
+    };
+    format!("Unexpected `{}` at index {}", text, span.start).into()
+}
+
+struct Parser {
+    symbols: Vec<(Symbol, Span)>,
+    position: usize,
+    end: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek_operator(&self) -> Option<&'static str> {
+        match self.symbols.get(self.position) {
+            Some((Symbol::Operator(operator), _)) => Some(operator),
+            _ => None,
+        }
+    }
+
+    fn next(&mut self) -> Result<(Symbol, Span), Box<dyn Error>> {
+        let Some(symbol) = self.symbols.get(self.position).cloned() else {
+            return Err(format!(
+                "Unexpected end of arithmetic expression at index {}",
+                self.end
+            )
+            .into());
+        };
+        self.position += 1;
+        Ok(symbol)
+    }
+
+    /// Runs `parse` one nesting level deeper, see [MAX_DEPTH]
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Expression, Box<dyn Error>>,
+    ) -> Result<Expression, Box<dyn Error>> {
+        if self.depth == MAX_DEPTH {
+            let start = self
+                .symbols
+                .get(self.position)
+                .map_or(self.end, |(_, span)| span.start);
+            return Err(format!(
+                "Nesting deeper than {} levels at index {}",
+                MAX_DEPTH, start
+            )
+            .into());
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), Box<dyn Error>> {
+        match self.next()? {
+            (Symbol::Operator(operator), _) if operator == expected => Ok(()),
+            (symbol, span) => Err(unexpected(&symbol, span)),
+        }
+    }
+
+    fn parse_comma(&mut self) -> Result<Expression, Box<dyn Error>> {
+        let mut left = self.parse_assignment()?;
+        while self.peek_operator() == Some(",") {
+            self.position += 1;
+            let right = self.parse_assignment()?;
+            left = binary(BinaryOperator::Comma, left, right);
+        }
+        Ok(left)
+    }
+
+    /// Assignments are right-associative : `a = b += 2`
+    fn parse_assignment(&mut self) -> Result<Expression, Box<dyn Error>> {
+        if let (Some((Symbol::Name(name), span)), Some((Symbol::Operator(operator), _))) = (
+            self.symbols.get(self.position).cloned(),
+            self.symbols.get(self.position + 1),
+        ) {
+            if let Some((_, assignment)) = ASSIGNMENTS.iter().find(|(text, _)| text == operator) {
+                self.position += 2;
+                let value = self.nested(Self::parse_assignment)?;
+                return Ok(Expression {
+                    span: Span::new(span.start, value.span.end),
+                    kind: ExpressionKind::Assignment {
+                        name,
+                        operator: *assignment,
+                        value: Box::new(value),
+                    },
+                });
+            }
+        }
+        self.parse_conditional()
+    }
+
+    fn parse_conditional(&mut self) -> Result<Expression, Box<dyn Error>> {
+        let condition = self.parse_binary(0)?;
+        if self.peek_operator() != Some("?") {
+            return Ok(condition);
+        }
+        self.position += 1;
+        let then = self.nested(Self::parse_comma)?;
+        self.expect(":")?;
+        let otherwise = self.nested(Self::parse_assignment)?;
+        Ok(Expression {
+            span: Span::new(condition.span.start, otherwise.span.end),
+            kind: ExpressionKind::Conditional(
+                Box::new(condition),
+                Box::new(then),
+                Box::new(otherwise),
+            ),
+        })
+    }
+
+    /// The operators of `level` and above, by precedence climbing : the right operand only takes the
+    /// operators that bind tighter
+    fn parse_binary(&mut self, level: usize) -> Result<Expression, Box<dyn Error>> {
+        let mut left = self.parse_power()?;
+        while let Some((precedence, operator)) = self
+            .peek_binary()
+            .filter(|(precedence, _)| *precedence >= level)
+        {
+            self.position += 1;
+            let right = self.parse_binary(precedence + 1)?;
+            left = binary(operator, left, right);
+        }
+        Ok(left)
+    }
+
+    /// The binary operator at the current position and its index in [PRECEDENCE]
+    fn peek_binary(&self) -> Option<(usize, BinaryOperator)> {
+        let text = self.peek_operator()?;
+        PRECEDENCE
+            .iter()
+            .enumerate()
+            .find_map(|(precedence, operators)| {
+                operators
+                    .iter()
+                    .find(|(operator, _)| *operator == text)
+                    .map(|(_, operator)| (precedence, *operator))
+            })
+    }
+
+    /// `**` is right-associative and binds looser than unary operators : `-2**2` is 4
+    fn parse_power(&mut self) -> Result<Expression, Box<dyn Error>> {
+        let base = self.parse_unary()?;
+        if self.peek_operator() != Some("**") {
+            return Ok(base);
+        }
+        self.position += 1;
+        let exponent = self.nested(Self::parse_power)?;
+        Ok(binary(BinaryOperator::Power, base, exponent))
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, Box<dyn Error>> {
+        let (symbol, span) = self.next()?;
+        let operator = match symbol {
+            Symbol::Operator("+") => UnaryOperator::Plus,
+            Symbol::Operator("-") => UnaryOperator::Minus,
+            Symbol::Operator("!") => UnaryOperator::Not,
+            Symbol::Operator("~") => UnaryOperator::BitwiseNot,
+            Symbol::Operator(increment @ ("++" | "--")) => {
+                let (name, name_span) = match self.next()? {
+                    (Symbol::Name(name), name_span) => (name, name_span),
+                    (symbol, span) => return Err(unexpected(&symbol, span)),
+                };
+                let operator = match increment {
+                    "++" => IncrementOperator::PreIncrement,
+                    _ => IncrementOperator::PreDecrement,
+                };
+                return Ok(Expression {
+                    kind: ExpressionKind::Increment(operator, name),
+                    span: Span::new(span.start, name_span.end),
+                });
+            }
+            symbol => return self.parse_postfix(symbol, span),
+        };
+        let operand = self.nested(Self::parse_unary)?;
+        Ok(Expression {
+            span: Span::new(span.start, operand.span.end),
+            kind: ExpressionKind::Unary(operator, Box::new(operand)),
+        })
+    }
+
+    fn parse_postfix(&mut self, symbol: Symbol, span: Span) -> Result<Expression, Box<dyn Error>> {
+        match symbol {
+            Symbol::Number(number) => Ok(Expression {
+                kind: ExpressionKind::Number(number),
+                span,
+            }),
+            Symbol::Name(name) => {
+                let operator = match self.peek_operator() {
+                    Some("++") => IncrementOperator::PostIncrement,
+                    Some("--") => IncrementOperator::PostDecrement,
+                    _ => {
+                        return Ok(Expression {
+                            kind: ExpressionKind::Variable(name),
+                            span,
+                        })
+                    }
+                };
+                let end = self.symbols[self.position].1.end;
+                self.position += 1;
+                Ok(Expression {
+                    kind: ExpressionKind::Increment(operator, name),
+                    span: Span::new(span.start, end),
+                })
+            }
+            Symbol::Operator("(") => {
+                let inner = self.nested(Self::parse_comma)?;
+                let end = match self.next()? {
+                    (Symbol::Operator(")"), close) => close.end,
+                    (symbol, span) => return Err(unexpected(&symbol, span)),
+                };
+                Ok(Expression {
+                    kind: inner.kind,
+                    span: Span::new(span.start, end),
+                })
+            }
+            symbol => Err(unexpected(&symbol, span)),
+        }
+    }
+}
+
+fn binary(operator: BinaryOperator, left: Expression, right: Expression) -> Expression {
+    Expression {
+        span: Span::new(left.span.start, right.span.end),
+        kind: ExpressionKind::Binary(operator, Box::new(left), Box::new(right)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arithmetic::{BinaryOperator, Expression, ExpressionKind, IncrementOperator};
+    use crate::tokens::span::Span;
+    use std::collections::HashMap;
+
+    fn evaluate(content: &str) -> i64 {
+        Expression::parse(content)
+            .unwrap()
+            .evaluate(&mut HashMap::new())
+            .unwrap()
+    }
+
+    fn error(content: &str) -> String {
+        match Expression::parse(content) {
+            Ok(expression) => expression
+                .evaluate(&mut HashMap::new())
+                .unwrap_err()
+                .to_string(),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_precedence() {
+        assert_eq!(7, evaluate("1 + 2 * 3"));
+        assert_eq!(9, evaluate("(1 + 2) * 3"));
+        assert_eq!(2, evaluate("7 / 3"));
+        assert_eq!(-1, evaluate("-7 % 3"));
+        assert_eq!(512, evaluate("2 ** 3 ** 2"));
+        assert_eq!(4, evaluate("-2 ** 2"));
+        assert_eq!(1, evaluate("1 + 2 == 3"));
+        assert_eq!(
+            1,
+            evaluate("1 < 2 && 2 <= 2 && 3 > 2 && 3 >= 4 - 1 && 1 != 2")
+        );
+        assert_eq!(8, evaluate("1 << 2 + 1"));
+        assert_eq!(2, evaluate("8 >> 2"));
+        assert_eq!(6, evaluate("2 | 4 & 6"));
+        assert_eq!(5, evaluate("6 ^ 3"));
+        assert_eq!(0, evaluate("!5"));
+        assert_eq!(-6, evaluate("~5"));
+        assert_eq!(1, evaluate("0 || 2"));
+        assert_eq!(3, evaluate("1, 2, 3"));
+        assert_eq!(20, evaluate("0 ? 10 : 1 ? 20 : 30"));
+        assert_eq!(3, evaluate("1--2"));
+        assert_eq!(1, evaluate("++1"));
+        assert_eq!(0, evaluate(""));
+        assert_eq!(0, evaluate("  "));
+    }
+
+    #[test]
+    fn test_evaluate_numbers() {
+        assert_eq!(255, evaluate("0xff"));
+        assert_eq!(255, evaluate("0XFF"));
+        assert_eq!(8, evaluate("010"));
+        assert_eq!(0, evaluate("0"));
+        assert_eq!(5, evaluate("2#101"));
+        assert_eq!(35, evaluate("36#z"));
+        assert_eq!(35, evaluate("36#Z"));
+        assert_eq!(61, evaluate("64#Z"));
+        assert_eq!(62, evaluate("64#@"));
+        assert_eq!(63, evaluate("64#_"));
+        assert_eq!(i64::MIN, evaluate("9223372036854775808"));
+        assert_eq!(i64::MIN, evaluate("9223372036854775807 + 1"));
+        assert_eq!(0, evaluate("2 ** 64"));
+        assert_eq!(i64::MIN, evaluate("-9223372036854775808 / -1"));
+        assert_eq!("Value too great for base `08` at index 0", error("08"));
+        assert_eq!(
+            "Value too great for base `2#12` at index 2",
+            error("1+2#12")
+        );
+        assert_eq!("Invalid arithmetic base `65` at index 0", error("65#1"));
+        assert_eq!("Value too great for base `1a` at index 0", error("1a"));
+    }
+
+    #[test]
+    fn test_evaluate_variables() {
+        let mut variables: HashMap<String, String> = [("a", "5"), ("b", "a * 2"), ("empty", "")]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let mut evaluate = |content: &str| {
+            Expression::parse(content)
+                .unwrap()
+                .evaluate(&mut variables)
+                .unwrap()
+        };
+        assert_eq!(15, evaluate("a + b"));
+        assert_eq!(15, evaluate("$a + ${b}"));
+        assert_eq!(0, evaluate("unset + empty"));
+        assert_eq!(5, evaluate("a++"));
+        assert_eq!(7, evaluate("++a"));
+        assert_eq!(7, evaluate("a--"));
+        assert_eq!(5, evaluate("--a"));
+        assert_eq!(3, evaluate("x = y = 3"));
+        assert_eq!(13, evaluate("x += 10"));
+        assert_eq!(52, evaluate("x <<= 2"));
+        assert_eq!(2, evaluate("x %= 5"));
+        assert_eq!(1, evaluate("0 && (z = 1), z = 1"));
+        assert_eq!(1, evaluate("(c = 1) || (c = 2), c"));
+        assert_eq!(2, evaluate("x"));
+        assert_eq!(Some(&String::from("5")), variables.get("a"));
+
+        let mut looping: HashMap<String, String> = HashMap::from([
+            (String::from("a"), String::from("b")),
+            (String::from("b"), String::from("a")),
+        ]);
+        assert_eq!(
+            "Expression recursion level exceeded at index 0",
+            Expression::parse("b")
+                .unwrap()
+                .evaluate(&mut looping)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        assert_eq!("Division by zero at index 4", error("1 / 0"));
+        assert_eq!("Division by zero at index 4", error("1 % (0)"));
+        assert_eq!("Division by zero at index 12", error("x = 1, x /= 0"));
+        assert_eq!("Exponent less than 0 at index 5", error("2 ** -1"));
+        assert_eq!(
+            "Unexpected end of arithmetic expression at index 3",
+            error("1 +")
+        );
+        assert_eq!("Unexpected `)` at index 1", error("1)"));
+        assert_eq!("Unexpected `2` at index 2", error("1 2"));
+        assert_eq!("Unexpected `:` at index 2", error("1 : 2"));
+        assert_eq!("Unexpected `1` at index 5", error("a ++ 1"));
+        assert_eq!("Unexpected character `[` at index 1", error("a[1]"));
+        assert_eq!(
+            "Cannot statically evaluate `$(id)` in an arithmetic expression at index 2",
+            error("1+$(id)")
+        );
+    }
+
+    #[test]
+    fn test_parse_construct() {
+        let expression = Expression::parse_construct("$((x++ + 1))").unwrap();
+        assert_eq!(Span::new(3, 10), expression.span);
+        let ExpressionKind::Binary(BinaryOperator::Add, left, _) = expression.kind else {
+            panic!("Expected an addition");
+        };
+        assert_eq!(
+            ExpressionKind::Increment(IncrementOperator::PostIncrement, String::from("x")),
+            left.kind
+        );
+        assert_eq!(Span::new(3, 6), left.span);
+        assert_eq!(
+            3,
+            Expression::parse_construct("((1 + 2))")
+                .unwrap()
+                .evaluate(&mut HashMap::new())
+                .unwrap()
+        );
+        assert_eq!(
+            "Division by zero at index 6",
+            Expression::parse_construct("((1 / 0))")
+                .unwrap()
+                .evaluate(&mut HashMap::new())
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(Expression::parse_construct("1 + 2").is_err());
+    }
+
+    #[test]
+    fn test_parse_nesting_limit() {
+        let parentheses = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(1, evaluate(&parentheses(128)));
+        assert_eq!(
+            "Nesting deeper than 128 levels at index 129",
+            error(&parentheses(129))
+        );
+        assert_eq!(
+            "Nesting deeper than 128 levels at index 129",
+            error(&parentheses(200000))
+        );
+        assert_eq!(
+            "Nesting deeper than 128 levels at index 129",
+            error(&"-".repeat(200000))
+        );
+        assert_eq!(
+            "Nesting deeper than 128 levels at index 514",
+            error(&"1?1:".repeat(200000))
+        );
+        assert_eq!(
+            "Nesting deeper than 128 levels at index 258",
+            error(&"a=".repeat(200000))
+        );
+        assert_eq!(
+            "Nesting deeper than 128 levels at index 387",
+            error(&format!("{}1", "2**".repeat(200000)))
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use bash_tokenizer::dialect::Dialect;
+use bash_tokenizer::tokenize_with_dialect;
+use bash_tokenizer::tokens::tokens::Token;
+use std::io::Read;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: bash-tokenize [--format json|ndjson|table] [--dialect DIALECT] [-c COMMAND | FILE]
+
+Prints the tokens of a script, read from FILE, from COMMAND or from standard input when neither is given.
+
+Options:
+  -c COMMAND         Tokenize COMMAND instead of a file
+  --format FORMAT    json (default): an array of tokens, ndjson: one token per line, table: aligned columns
+  --dialect DIALECT  The shell the script is written for: bash (default), posix, dash, ksh93, zsh or ash
+  -h, --help         Print this help
+
+Every token has a kind, its text and a span of byte offsets into the input.
+
+Exit status: 0 when the input was tokenized, 1 when it has a syntax error the tokenizer rejects (unterminated
+quotes, ...), 2 for invalid arguments or input that can't be read.";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Format {
+    Json,
+    Ndjson,
+    Table,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Input {
+    Command(String),
+    File(String),
+    Stdin,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Options {
+    format: Format,
+    dialect: Dialect,
+    input: Input,
+}
+
+fn main() -> ExitCode {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_arguments(&arguments) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("bash-tokenize: {}\n\n{}", error, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let script = match read_input(&options.input) {
+        Ok(script) => script,
+        Err(error) => {
+            eprintln!("bash-tokenize: {}", error);
+            return ExitCode::from(2);
+        }
+    };
+    match tokenize_with_dialect(script, &options.dialect) {
+        Ok(tokens) => {
+            print!("{}", format_tokens(&tokens, options.format));
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("bash-tokenize: {}", error);
+            ExitCode::from(1)
+        }
+    }
+}
+
+/// The options in `arguments`, `None` when help was asked for
+fn parse_arguments(arguments: &[String]) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        format: Format::Json,
+        dialect: Dialect::BASH,
+        input: Input::Stdin,
+    };
+    let mut arguments = arguments.iter();
+    while let Some(argument) = arguments.next() {
+        let mut value = |option: &str| {
+            arguments
+                .next()
+                .cloned()
+                .ok_or_else(|| format!("`{}` requires a value", option))
+        };
+        match argument.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-c" => set_input(&mut options.input, Input::Command(value(argument)?))?,
+            "--format" => {
+                options.format = match value(argument)?.as_str() {
+                    "json" => Format::Json,
+                    "ndjson" => Format::Ndjson,
+                    "table" => Format::Table,
+                    format => return Err(format!("Unknown format `{}`", format)),
+                }
+            }
+            "--dialect" => {
+                options.dialect = value(argument)?
+                    .parse()
+                    .map_err(|error: Box<dyn std::error::Error>| error.to_string())?
+            }
+            "-" => set_input(&mut options.input, Input::Stdin)?,
+            option if option.starts_with('-') => {
+                return Err(format!("Unknown option `{}`", option))
+            }
+            path => set_input(&mut options.input, Input::File(path.to_string()))?,
+        }
+    }
+    Ok(Some(options))
+}
+
+fn set_input(input: &mut Input, value: Input) -> Result<(), String> {
+    match input {
+        Input::Stdin => {
+            *input = value;
+            Ok(())
+        }
+        _ => Err(String::from("Expected a single `-c` command or file")),
+    }
+}
+
+fn read_input(input: &Input) -> Result<String, String> {
+    match input {
+        Input::Command(command) => Ok(command.clone()),
+        Input::File(path) => std::fs::read_to_string(path)
+            .map_err(|error| format!("Cannot read {}: {}", path, error)),
+        Input::Stdin => {
+            let mut script = String::new();
+            std::io::stdin()
+                .read_to_string(&mut script)
+                .map_err(|error| format!("Cannot read standard input: {}", error))?;
+            Ok(script)
+        }
+    }
+}
+
+fn format_tokens(tokens: &[Token], format: Format) -> String {
+    match format {
+        Format::Json if tokens.is_empty() => String::from("[]\n"),
+        Format::Json => {
+            let lines: Vec<String> = tokens
+                .iter()
+                .map(|token| format!("  {}", token_json(token)))
+                .collect();
+            format!("[\n{}\n]\n", lines.join(",\n"))
+        }
+        Format::Ndjson => tokens
+            .iter()
+            .map(|token| token_json(token) + "\n")
+            .collect(),
+        Format::Table => {
+            let kinds: Vec<String> = tokens
+                .iter()
+                .map(|token| format!("{:?}", token.kind))
+                .collect();
+            let width = kinds.iter().map(String::len).max().unwrap_or(0).max(4);
+            let mut table = format!("{:>6} {:>6}  {:<width$}  TEXT\n", "START", "END", "KIND");
+            for (token, kind) in tokens.iter().zip(kinds) {
+                // Debug quoting keeps newlines and tabs of the text on the row :
+                table.push_str(&format!(
+                    "{:>6} {:>6}  {:<width$}  {:?}\n",
+                    token.span.start, token.span.end, kind, token.text
+                ));
+            }
+            table
+        }
+    }
+}
+
+/// `{"kind":"Word","text":"ls","span":{"start":0,"end":2},"alias":null}`
+fn token_json(token: &Token) -> String {
+    let alias = match &token.alias {
+        Some(alias) => json_string(alias),
+        None => String::from("null"),
+    };
+    format!(
+        "{{\"kind\":\"{:?}\",\"text\":{},\"span\":{{\"start\":{},\"end\":{}}},\"alias\":{}}}",
+        token.kind,
+        json_string(&token.text),
+        token.span.start,
+        token.span.end,
+        alias
+    )
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for char in s.chars() {
+        match char {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            char if char.is_control() => json.push_str(&format!("\\u{:04x}", char as u32)),
+            char => json.push(char),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{format_tokens, json_string, parse_arguments, Format, Input, Options};
+    use bash_tokenizer::dialect::Dialect;
+    use bash_tokenizer::tokenize;
+
+    fn arguments(arguments: &[&str]) -> Result<Option<Options>, String> {
+        let arguments: Vec<String> = arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .collect();
+        parse_arguments(&arguments)
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        assert_eq!(
+            Ok(Some(Options {
+                format: Format::Json,
+                dialect: Dialect::BASH,
+                input: Input::Stdin,
+            })),
+            arguments(&[])
+        );
+        assert_eq!(
+            Ok(Some(Options {
+                format: Format::Table,
+                dialect: Dialect::DASH,
+                input: Input::Command(String::from("ls -l")),
+            })),
+            arguments(&["--format", "table", "-c", "ls -l", "--dialect", "dash"])
+        );
+        assert_eq!(
+            Input::File(String::from("build.sh")),
+            arguments(&["build.sh"]).unwrap().unwrap().input
+        );
+        assert_eq!(Ok(None), arguments(&["-c", "ls", "--help"]));
+        assert_eq!(
+            Err(String::from("`-c` requires a value")),
+            arguments(&["-c"])
+        );
+        assert_eq!(
+            Err(String::from("Unknown format `xml`")),
+            arguments(&["--format", "xml"])
+        );
+        assert_eq!(
+            Err(String::from("Expected a single `-c` command or file")),
+            arguments(&["-c", "ls", "build.sh"])
+        );
+        assert_eq!(Err(String::from("Unknown option `-x`")), arguments(&["-x"]));
+    }
+
+    #[test]
+    fn test_format_tokens() {
+        let tokens = tokenize(String::from("echo \"a\tb\"\n")).unwrap();
+        assert_eq!(
+            "[\n  {\"kind\":\"Word\",\"text\":\"echo\",\"span\":{\"start\":0,\"end\":4},\"alias\":null},\n  \
+             {\"kind\":\"WhiteSpace\",\"text\":\" \",\"span\":{\"start\":4,\"end\":5},\"alias\":null},\n  \
+             {\"kind\":\"DoubleQuote\",\"text\":\"\\\"a\\tb\\\"\",\"span\":{\"start\":5,\"end\":10},\"alias\":null},\n  \
+             {\"kind\":\"Newline\",\"text\":\"\\n\",\"span\":{\"start\":10,\"end\":11},\"alias\":null}\n]\n",
+            format_tokens(&tokens, Format::Json)
+        );
+        assert_eq!("[]\n", format_tokens(&[], Format::Json));
+        assert_eq!("", format_tokens(&[], Format::Ndjson));
+        assert_eq!(
+            "{\"kind\":\"Word\",\"text\":\"é\",\"span\":{\"start\":0,\"end\":2},\"alias\":null}\n",
+            format_tokens(&tokenize(String::from("é")).unwrap(), Format::Ndjson)
+        );
+        let table = [
+            " START    END  KIND         TEXT",
+            "     0      4  Word         \"echo\"",
+            "     4      5  WhiteSpace   \" \"",
+            "     5     10  DoubleQuote  \"\\\"a\\tb\\\"\"",
+            "    10     11  Newline      \"\\n\"",
+        ];
+        assert_eq!(
+            table.join("\n") + "\n",
+            format_tokens(&tokens, Format::Table)
+        );
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!("\"a\\\\b\\u001b\"", json_string("a\\b\u{1b}"));
+    }
+}
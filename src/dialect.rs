@@ -0,0 +1,232 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Dialect
+/// The shell a script is written for, every field turns a syntax feature on or off. Start from one of the presets
+/// and change what's different : `Dialect { extglob: true, ..Dialect::BASH }`
+///
+/// When a feature is off its characters mean what they mean in POSIX : without `dollar_single_quotes`, `$'a'`
+/// is a literal `$` followed by the single-quoted string `'a'`, without `redirect_output_and_error` `ls &>x`
+/// runs `ls` in the background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dialect {
+    pub dollar_single_quotes: bool,        // $'...'
+    pub dollar_double_quotes: bool,        // $"..."
+    pub here_strings: bool,                // <<<
+    pub redirect_output_and_error: bool,   // &> and &>>
+    pub pipe_output_and_error: bool,       // |&
+    pub process_substitution: bool,        // <(...) and >(...)
+    pub conditional_command: bool,         // [[ ... ]]
+    pub arithmetic_command: bool,          // (( ... )) and for (( ...; ...; ... ))
+    pub extglob: bool,                     // ?(...) *(...) +(...) @(...) !(...), `shopt -s extglob`
+    pub brace_expansion: bool,             // {a,b} and {1..3}
+    pub case_fallthrough: bool,            // ;& ends a case item and runs the next one
+    pub case_continue: bool,               // ;;& ends a case item and tests the next patterns
+    pub history_expansion: bool,           // !!, !$, ^old^new, only interactive shells expand them
+    pub bash_version: Option<BashVersion>, // Reject what's newer than this release, `None` allows everything
+}
+
+impl Dialect {
+    /// Bash with its default options, `extglob` and `history_expansion` are off like in a non-interactive shell
+    pub const BASH: Dialect = Dialect {
+        dollar_single_quotes: true,
+        dollar_double_quotes: true,
+        here_strings: true,
+        redirect_output_and_error: true,
+        pipe_output_and_error: true,
+        process_substitution: true,
+        conditional_command: true,
+        arithmetic_command: true,
+        extglob: false,
+        brace_expansion: true,
+        case_fallthrough: true,
+        case_continue: true,
+        history_expansion: false,
+        bash_version: None,
+    };
+
+    /// IEEE 1003.1-2024, which added `$'...'` and `;&`
+    pub const POSIX: Dialect = Dialect {
+        dollar_single_quotes: true,
+        case_fallthrough: true,
+        ..Dialect::DASH
+    };
+
+    /// The Debian Almquist shell, `/bin/sh` on Debian and Ubuntu
+    pub const DASH: Dialect = Dialect {
+        dollar_single_quotes: false,
+        dollar_double_quotes: false,
+        here_strings: false,
+        redirect_output_and_error: false,
+        pipe_output_and_error: false,
+        process_substitution: false,
+        conditional_command: false,
+        arithmetic_command: false,
+        extglob: false,
+        brace_expansion: false,
+        case_fallthrough: false,
+        case_continue: false,
+        history_expansion: false,
+        bash_version: None,
+    };
+
+    /// The AT&T Korn shell, where extended patterns and `;&` come from. `|&` starts a co-process there, so it
+    /// isn't a pipe
+    pub const KSH93: Dialect = Dialect {
+        redirect_output_and_error: false,
+        pipe_output_and_error: false,
+        extglob: true,
+        case_continue: false,
+        ..Dialect::BASH
+    };
+
+    /// Zsh with its default options, `$"..."` isn't special and extended patterns need `setopt kshglob`
+    pub const ZSH: Dialect = Dialect {
+        dollar_double_quotes: false,
+        case_continue: false,
+        ..Dialect::BASH
+    };
+
+    /// The ash of BusyBox with its bash compatibility options, `/bin/sh` on Alpine. `[[` is a command like `[`
+    /// there, not a conditional expression
+    pub const BUSYBOX_ASH: Dialect = Dialect {
+        dollar_single_quotes: true,
+        here_strings: true,
+        redirect_output_and_error: true,
+        ..Dialect::DASH
+    };
+
+    /// Err when [Dialect::bash_version] is older than `required`, the message names the `construct` and
+    /// where it was found.
+    pub fn require_bash(
+        &self,
+        required: BashVersion,
+        construct: &str,
+        index: usize,
+    ) -> Result<(), String> {
+        match self.bash_version {
+            Some(version) if version < required => Err(format!(
+                "`{}` requires bash {} (targeting {}) at index {}",
+                construct, required, version, index
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Dialect::BASH
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = Box<dyn Error>;
+
+    /// Reads the name of a preset : `bash`, `posix` (or `sh`), `dash`, `ksh93` (or `ksh`), `zsh` and `ash` (or
+    /// `busybox`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bash" => Ok(Dialect::BASH),
+            "posix" | "sh" => Ok(Dialect::POSIX),
+            "dash" => Ok(Dialect::DASH),
+            "ksh93" | "ksh" => Ok(Dialect::KSH93),
+            "zsh" => Ok(Dialect::ZSH),
+            "ash" | "busybox" => Ok(Dialect::BUSYBOX_ASH),
+            _ => Err(format!("Unknown dialect `{}`", s).into()),
+        }
+    }
+}
+
+/// BashVersion
+/// A bash release, `major.minor`. Set it as [Dialect::bash_version] to find the syntax an older bash can't
+/// run, e.g. `;;&` and `|&` came with 4.0 and `${var@Q}` with 4.4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BashVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl BashVersion {
+    pub const fn new(major: u8, minor: u8) -> BashVersion {
+        BashVersion { major, minor }
+    }
+}
+
+impl fmt::Display for BashVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for BashVersion {
+    type Err = Box<dyn Error>;
+
+    /// Reads `4.2`, `5` or `5.2.15`, the patch level is ignored
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('.');
+        let major = parts.next().unwrap_or_default();
+        let minor = parts.next().unwrap_or("0");
+        match (major.parse(), minor.parse()) {
+            (Ok(major), Ok(minor)) => Ok(BashVersion::new(major, minor)),
+            _ => Err(format!("Invalid bash version `{}`", s).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::{BashVersion, Dialect};
+
+    #[test]
+    fn test_bash_version() {
+        assert!(BashVersion::new(4, 0) < BashVersion::new(4, 2));
+        assert!(BashVersion::new(3, 2) < BashVersion::new(4, 0));
+        assert!(BashVersion::new(5, 0) > BashVersion::new(4, 4));
+        assert_eq!("4.2", BashVersion::new(4, 2).to_string());
+        assert_eq!(BashVersion::new(4, 2), "4.2".parse().unwrap());
+        assert_eq!(BashVersion::new(5, 0), "5".parse().unwrap());
+        assert_eq!(BashVersion::new(5, 2), "5.2.15".parse().unwrap());
+        assert!("four".parse::<BashVersion>().is_err());
+        assert!("".parse::<BashVersion>().is_err());
+    }
+
+    #[test]
+    fn test_dialect_from_str() {
+        assert_eq!(Dialect::BASH, "bash".parse().unwrap());
+        assert_eq!(Dialect::POSIX, "sh".parse().unwrap());
+        assert_eq!(Dialect::DASH, "Dash".parse().unwrap());
+        assert_eq!(Dialect::KSH93, "ksh".parse().unwrap());
+        assert_eq!(Dialect::KSH93, "ksh93".parse().unwrap());
+        assert_eq!(Dialect::ZSH, "zsh".parse().unwrap());
+        assert_eq!(Dialect::BUSYBOX_ASH, "ash".parse().unwrap());
+        assert_eq!(Dialect::BUSYBOX_ASH, "BusyBox".parse().unwrap());
+        assert_eq!(
+            "Unknown dialect `fish`",
+            "fish".parse::<Dialect>().unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_require_bash() {
+        let centos_6 = Dialect {
+            bash_version: Some(BashVersion::new(4, 1)),
+            ..Dialect::BASH
+        };
+        assert!(Dialect::BASH
+            .require_bash(BashVersion::new(5, 2), "@k", 0)
+            .is_ok());
+        assert!(centos_6
+            .require_bash(BashVersion::new(4, 0), "|&", 0)
+            .is_ok());
+        assert_eq!(
+            "`\\u` requires bash 4.2 (targeting 4.1) at index 3",
+            centos_6
+                .require_bash(BashVersion::new(4, 2), "\\u", 3)
+                .unwrap_err()
+        );
+    }
+}
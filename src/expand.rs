@@ -0,0 +1,870 @@
+use crate::argv::cannot_evaluate;
+use crate::arithmetic;
+use crate::dialect::Dialect;
+use crate::lexer::brace_expansion::expand_braces;
+use crate::lexer::dollar_single_quotes::decode_dollar_single_quotes;
+use crate::lexer::nested::tokenize_nested;
+use crate::lexer::parameter_expansion::is_special_parameter;
+use crate::lexer::pattern::Pattern;
+use crate::tokenize;
+use crate::tokens::word::{Word, WordPart, WordPartKind};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// expand
+/// > [3.5 Shell Expansions](https://www.gnu.org/software/bash/manual/bash.html#Shell-Expansions)
+/// > The order of expansions is: brace expansion; tilde expansion, parameter and variable expansion,
+/// > arithmetic expansion, and command substitution (done in a left-to-right fashion); word splitting;
+/// > pathname expansion; and quote removal.
+///
+/// Returns the fields `word` expands to, without running a shell. `environment` holds the variables and the
+/// positional parameters (`"1"`, `"2"`, ...), `HOME`, `PWD` and `OLDPWD` are used for tilde prefixes and
+/// `IFS` for word splitting. Unset variables expand to nothing, like in bash without `set -u`.
+///
+/// Anything that can't be resolved from the environment is an error : command and process substitutions,
+/// `~user`, indirect and array expansions and pathname expansions that would match files. Arithmetic
+/// expansions are evaluated with [arithmetic::Expression], division by zero is an error too.
+pub fn expand(
+    word: &Word,
+    environment: &HashMap<String, String>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let expander = Expander::new(environment);
+    let has_braces = word
+        .parts
+        .iter()
+        .any(|part| part.kind == WordPartKind::BraceExpansion);
+    if !has_braces {
+        return expander.expand_word(word, 0);
+    }
+
+    let mut fields = Vec::new();
+    for expanded in expand_braces(&word.text())? {
+        let expanded = Word::parse(&expanded, &Dialect::BASH)?;
+        fields.extend(expander.expand_word(&expanded, word.span.start)?);
+    }
+    Ok(fields)
+}
+
+/// A character of a word after expansion, and what word splitting and pathname expansion do with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Piece {
+    Char {
+        char: char,
+        quoted: bool, // Never splits a field nor acts as a wildcard
+        split: bool,  // The result of an unquoted expansion, IFS characters split it
+    },
+    Field, // A field even if nothing is added to it : `""`
+    Break, // The end of a field : between the positional parameters of `"$@"`
+}
+
+struct Expander<'a> {
+    variables: &'a HashMap<String, String>,
+    positional: Vec<&'a str>,
+}
+
+impl<'a> Expander<'a> {
+    fn new(variables: &'a HashMap<String, String>) -> Expander<'a> {
+        let positional = (1..)
+            .map_while(|number: usize| variables.get(&number.to_string()).map(String::as_str))
+            .collect();
+        Expander {
+            variables,
+            positional,
+        }
+    }
+
+    /// `offset` is added to the spans of `word` in errors
+    fn expand_word(&self, word: &Word, offset: usize) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut pieces = Vec::new();
+        for part in &word.parts {
+            self.expand_part(part, false, false, offset, &mut pieces)?;
+        }
+
+        let ifs = self.variables.get("IFS").map_or(" \t\n", String::as_str);
+        let mut fields = Vec::new();
+        for field in split_fields(&pieces, ifs) {
+            let text: String = field.iter().map(|(char, _)| char).collect();
+            // Quoted characters only match themselves, so we escape them :
+            let pattern: String = field
+                .iter()
+                .flat_map(|(char, quoted)| match quoted {
+                    true => vec!['\\', *char],
+                    false => vec![*char],
+                })
+                .collect();
+            if !Pattern::parse(&pattern).is_literal() {
+                return Err(cannot_evaluate(
+                    "pathname expansion",
+                    &text,
+                    offset + word.span.start,
+                ));
+            }
+            fields.push(text);
+        }
+        Ok(fields)
+    }
+
+    /// `quoted` when the part is inside double quotes, `in_expansion` when it's part of the word of a
+    /// parameter expansion (`${x:-a b}`), where unquoted characters are split like the value of a variable.
+    fn expand_part(
+        &self,
+        part: &WordPart,
+        quoted: bool,
+        in_expansion: bool,
+        offset: usize,
+        pieces: &mut Vec<Piece>,
+    ) -> Result<(), Box<dyn Error>> {
+        let index = offset + part.span.start;
+        match &part.kind {
+            WordPartKind::Literal | WordPartKind::Pattern | WordPartKind::BraceExpansion => {
+                let mut chars = part.text.chars();
+                while let Some(char) = chars.next() {
+                    match (char, quoted) {
+                        ('\\', _) => match chars.next() {
+                            Some('\n') => {} // Line continuation
+                            Some(escaped) => pieces.push(quoted_char(escaped)),
+                            None => pieces.push(quoted_char('\\')),
+                        },
+                        (char, quoted) => pieces.push(Piece::Char {
+                            char,
+                            quoted,
+                            split: in_expansion && !quoted,
+                        }),
+                    }
+                }
+            }
+            WordPartKind::TildePrefix => {
+                let variable = match part.text.as_str() {
+                    "~" => "HOME",
+                    "~+" => "PWD",
+                    "~-" => "OLDPWD",
+                    _ => return Err(cannot_evaluate("tilde prefix", &part.text, index)),
+                };
+                let Some(directory) = self.variables.get(variable) else {
+                    return Err(cannot_evaluate("tilde prefix", &part.text, index));
+                };
+                pieces.extend(directory.chars().map(quoted_char));
+            }
+            WordPartKind::SingleQuoted => {
+                pieces.push(Piece::Field);
+                let inner = &part.text[1..part.text.len() - 1];
+                pieces.extend(inner.chars().map(quoted_char));
+            }
+            WordPartKind::DollarSingleQuoted => {
+                pieces.push(Piece::Field);
+                let decoded = decode_dollar_single_quotes(&part.text);
+                pieces.extend(String::from_utf8_lossy(&decoded).chars().map(quoted_char));
+            }
+            WordPartKind::DoubleQuoted(children) | WordPartKind::DollarDoubleQuoted(children) => {
+                // `"$@"` without positional parameters is no field at all :
+                let only_positional = !children.is_empty()
+                    && children
+                        .iter()
+                        .all(|child| ["$@", "${@}"].contains(&child.text.as_str()));
+                if !only_positional {
+                    pieces.push(Piece::Field);
+                }
+                for child in children {
+                    match child.kind {
+                        WordPartKind::Literal => pieces
+                            .extend(decode_double_quoted(&child.text).chars().map(quoted_char)),
+                        _ => self.expand_part(child, true, in_expansion, offset, pieces)?,
+                    }
+                }
+            }
+            WordPartKind::ParameterExpansion => {
+                self.expand_parameter(&part.text, quoted, index, pieces)?;
+            }
+            WordPartKind::CommandSubstitution => {
+                return Err(cannot_evaluate("command substitution", &part.text, index));
+            }
+            WordPartKind::ArithmeticExpansion => {
+                // > All tokens in the expression undergo parameter and variable expansion, command
+                // > substitution, and quote removal.
+                let mut content = part.text[3..part.text.len() - 2].to_string();
+                if content.contains(['$', '`']) {
+                    content = self.expand_string(&content, index + 3)?;
+                }
+                let expression = arithmetic::parse_at(&content, index + 3)?;
+                // Assignments in the expression don't change the environment we were given :
+                let value = expression.evaluate(&mut self.variables.clone())?;
+                push_value(&value.to_string(), quoted, pieces);
+            }
+            WordPartKind::ProcessSubstitution => {
+                return Err(cannot_evaluate("process substitution", &part.text, index));
+            }
+            WordPartKind::HistoryExpansion => {
+                return Err(cannot_evaluate("history expansion", &part.text, index));
+            }
+        }
+        Ok(())
+    }
+
+    /// > [3.5.3 Shell Parameter Expansion](https://www.gnu.org/software/bash/manual/bash.html#Shell-Parameter-Expansion)
+    ///
+    /// `$name`, `${name}`, `${#name}`, the defaults `${name:-word}` (`:=`, `:?`, `:+` and without colon),
+    /// the pattern removals `${name#pattern}` (`##`, `%`, `%%`), the substitutions `${name/pattern/string}`
+    /// (`//`, `/#`, `/%`) and the case modifications `${name^^}` (`^`, `,`, `,,`).
+    fn expand_parameter(
+        &self,
+        text: &str,
+        quoted: bool,
+        index: usize,
+        pieces: &mut Vec<Piece>,
+    ) -> Result<(), Box<dyn Error>> {
+        let unsupported = || cannot_evaluate("parameter expansion", text, index);
+        let Some(inner) = text
+            .strip_prefix("${")
+            .and_then(|text| text.strip_suffix('}'))
+        else {
+            return self.expand_value(&text[1..], quoted, pieces); // `$name`
+        };
+        // Where the word after the operator is located, it runs up to the closing `}` :
+        let word_index = |word: &str| index + text.len() - 1 - word.len();
+
+        if let Some(name) = inner.strip_prefix('#').filter(|name| is_parameter(name)) {
+            let length = match name {
+                "@" | "*" => self.positional.len(),
+                name => self.value(name).unwrap_or_default().chars().count(),
+            };
+            push_value(&length.to_string(), quoted, pieces);
+            return Ok(());
+        }
+
+        let name_length = parameter_length(inner);
+        let (name, operation) = inner.split_at(name_length);
+        if name.is_empty() {
+            return Err(unsupported());
+        }
+        let value = self.value(name);
+        if operation.is_empty() {
+            return self.expand_value(name, quoted, pieces);
+        }
+
+        // `${name:-word}` also uses the word when the value is empty, `${name-word}` only when it's unset :
+        let (check_empty, default) = match operation.strip_prefix(':') {
+            Some(rest) if rest.starts_with(['-', '=', '?', '+']) => (true, rest),
+            _ => (false, operation),
+        };
+        let is_set = value
+            .as_ref()
+            .is_some_and(|value| !(check_empty && value.is_empty()));
+        if let Some(operator) = default.chars().next().filter(|char| "-=?+".contains(*char)) {
+            let word = &default[1..];
+            return match (operator, is_set) {
+                ('-' | '=', true) | ('?', true) => self.expand_value(name, quoted, pieces),
+                ('-' | '=', false) | ('+', true) => {
+                    self.expand_inner(word, word_index(word), quoted, pieces)
+                }
+                ('+', false) => Ok(()),
+                _ => {
+                    let message = match word.is_empty() {
+                        true => String::from("parameter null or not set"),
+                        false => self.expand_string(word, word_index(word))?,
+                    };
+                    Err(format!("{}: {} at index {}", name, message, index).into())
+                }
+            };
+        }
+
+        let value = value.unwrap_or_default();
+        let result = if let Some(word) = operation.strip_prefix("##") {
+            remove_prefix(&value, &self.expand_pattern(word, word_index(word))?, true)
+        } else if let Some(word) = operation.strip_prefix('#') {
+            remove_prefix(&value, &self.expand_pattern(word, word_index(word))?, false)
+        } else if let Some(word) = operation.strip_prefix("%%") {
+            remove_suffix(&value, &self.expand_pattern(word, word_index(word))?, true)
+        } else if let Some(word) = operation.strip_prefix('%') {
+            remove_suffix(&value, &self.expand_pattern(word, word_index(word))?, false)
+        } else if let Some(substitution) = operation.strip_prefix('/') {
+            let (mode, substitution) = match substitution.chars().next() {
+                Some('/') => (Substitution::All, &substitution[1..]),
+                Some('#') => (Substitution::Prefix, &substitution[1..]),
+                Some('%') => (Substitution::Suffix, &substitution[1..]),
+                _ => (Substitution::First, substitution),
+            };
+            let (pattern, replacement) = match split_unquoted(substitution, '/') {
+                Some(slash) => (&substitution[..slash], &substitution[slash + 1..]),
+                None => (substitution, ""),
+            };
+            let replacement_index = word_index(replacement);
+            let pattern_index = replacement_index - pattern.len() - 1;
+            substitute(
+                &value,
+                &self.expand_pattern(pattern, pattern_index)?,
+                &self.expand_string(replacement, replacement_index)?,
+                mode,
+            )
+        } else if let Some(word) = ["^^", "^", ",,", ","]
+            .iter()
+            .find_map(|operator| operation.strip_prefix(operator))
+        {
+            let pattern = match word.is_empty() {
+                true => Pattern::parse("?"),
+                false => self.expand_pattern(word, word_index(word))?,
+            };
+            let upper = operation.starts_with('^');
+            let all = operation.len() - word.len() == 2;
+            value
+                .chars()
+                .enumerate()
+                .map(|(position, char)| {
+                    if (all || position == 0) && pattern.matches(&char.to_string()) {
+                        match upper {
+                            true => char.to_uppercase().collect(),
+                            false => char.to_lowercase().collect(),
+                        }
+                    } else {
+                        char.to_string()
+                    }
+                })
+                .collect()
+        } else {
+            return Err(unsupported()); // Substrings, transformations, arrays, ...
+        };
+        push_value(&result, quoted, pieces);
+        Ok(())
+    }
+
+    /// The value of the parameter `name`, the positional parameters for `$@` and `$*`
+    fn expand_value(
+        &self,
+        name: &str,
+        quoted: bool,
+        pieces: &mut Vec<Piece>,
+    ) -> Result<(), Box<dyn Error>> {
+        match (name, quoted) {
+            ("@", true) => {
+                // > "$@" is equivalent to "$1" "$2" ...
+                for (position, parameter) in self.positional.iter().enumerate() {
+                    if position != 0 {
+                        pieces.push(Piece::Break);
+                    }
+                    pieces.push(Piece::Field);
+                    push_value(parameter, true, pieces);
+                }
+            }
+            ("@" | "*", false) => {
+                for (position, parameter) in self.positional.iter().enumerate() {
+                    if position != 0 {
+                        pieces.push(Piece::Break);
+                    }
+                    push_value(parameter, false, pieces);
+                }
+            }
+            ("*", true) => {
+                // > "$*" is equivalent to "$1c$2c...", where c is the first character of the value of IFS
+                let separator = match self.variables.get("IFS") {
+                    Some(ifs) => ifs.chars().next().map(String::from).unwrap_or_default(),
+                    None => String::from(" "),
+                };
+                push_value(&self.positional.join(&separator), true, pieces);
+            }
+            (name, quoted) => push_value(&self.value(name).unwrap_or_default(), quoted, pieces),
+        }
+        Ok(())
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        match name {
+            "#" => Some(self.positional.len().to_string()),
+            "@" | "*" if self.positional.is_empty() => None,
+            "@" | "*" => Some(self.positional.join(" ")),
+            name => self.variables.get(name).cloned(),
+        }
+    }
+
+    /// Expands the word of a parameter expansion, `${x:-$HOME/"a b"}`, `index` is where it's located
+    fn expand_inner(
+        &self,
+        word: &str,
+        index: usize,
+        quoted: bool,
+        pieces: &mut Vec<Piece>,
+    ) -> Result<(), Box<dyn Error>> {
+        for token in tokenize(word.to_string())? {
+            match WordPart::from_token(&token, &Dialect::BASH) {
+                Some(part) => self.expand_part(&part, quoted, true, index, pieces)?,
+                None => pieces.extend(token.text.chars().map(|char| Piece::Char {
+                    char,
+                    quoted,
+                    split: !quoted,
+                })),
+            }
+        }
+        Ok(())
+    }
+
+    fn expand_string(&self, word: &str, index: usize) -> Result<String, Box<dyn Error>> {
+        let mut pieces = Vec::new();
+        self.expand_inner(word, index, true, &mut pieces)?;
+        Ok(pieces
+            .iter()
+            .filter_map(|piece| match piece {
+                Piece::Char { char, .. } => Some(*char),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// The word of `${x#word}` as a pattern, the quoted characters of its expansion only match themselves
+    fn expand_pattern(&self, word: &str, index: usize) -> Result<Pattern, Box<dyn Error>> {
+        let mut pieces = Vec::new();
+        self.expand_inner(word, index, false, &mut pieces)?;
+        let pattern: String = pieces
+            .iter()
+            .flat_map(|piece| match piece {
+                Piece::Char {
+                    char, quoted: true, ..
+                } => vec!['\\', *char],
+                Piece::Char { char, .. } => vec![*char],
+                _ => vec![],
+            })
+            .collect();
+        Ok(Pattern::parse(&pattern))
+    }
+}
+
+/// > [3.5.7 Word Splitting](https://www.gnu.org/software/bash/manual/bash.html#Word-Splitting)
+/// > Any character in IFS that is not IFS whitespace, along with any adjacent IFS whitespace characters,
+/// > delimits a field. A sequence of IFS whitespace characters is also treated as a delimiter.
+///
+/// Returns the characters of every field and whether they're quoted.
+fn split_fields(pieces: &[Piece], ifs: &str) -> Vec<Vec<(char, bool)>> {
+    let mut fields = Vec::new();
+    let mut field: Option<Vec<(char, bool)>> = None;
+    let mut delimited = false; // Whether IFS whitespace just ended a field
+    for piece in pieces {
+        match *piece {
+            Piece::Field => {
+                field.get_or_insert_with(Vec::new);
+            }
+            Piece::Break => fields.extend(field.take()),
+            Piece::Char {
+                char,
+                quoted: false,
+                split: true,
+            } if ifs.contains(char) => {
+                if char.is_ascii_whitespace() {
+                    if let Some(ended) = field.take() {
+                        fields.push(ended);
+                        delimited = true;
+                    }
+                } else if delimited {
+                    delimited = false; // Part of the same delimiter : `a : b`
+                } else {
+                    fields.push(field.take().unwrap_or_default());
+                }
+            }
+            Piece::Char { char, quoted, .. } => {
+                field.get_or_insert_with(Vec::new).push((char, quoted));
+                delimited = false;
+            }
+        }
+    }
+    fields.extend(field);
+    fields
+}
+
+fn quoted_char(char: char) -> Piece {
+    Piece::Char {
+        char,
+        quoted: true,
+        split: false,
+    }
+}
+
+/// The result of an expansion, split into fields unless it's quoted
+fn push_value(value: &str, quoted: bool, pieces: &mut Vec<Piece>) {
+    pieces.extend(value.chars().map(|char| Piece::Char {
+        char,
+        quoted,
+        split: !quoted,
+    }));
+}
+
+/// > The backslash retains its special meaning only when followed by one of the following characters:
+/// > ‘$’, ‘`’, ‘"’, ‘\’, or newline.
+fn decode_double_quoted(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(char) = chars.next() {
+        match (char, chars.peek()) {
+            ('\\', Some('\n')) => {
+                chars.next();
+            }
+            ('\\', Some(escaped @ ('$' | '`' | '"' | '\\'))) => {
+                decoded.push(*escaped);
+                chars.next();
+            }
+            (char, _) => decoded.push(char),
+        }
+    }
+    decoded
+}
+
+/// Whether `name` is a whole parameter name : `x`, `10`, `@`
+fn is_parameter(name: &str) -> bool {
+    !name.is_empty() && parameter_length(name) == name.len()
+}
+
+/// The length in bytes of the parameter name at the start of the inside of `${...}`
+fn parameter_length(inner: &str) -> usize {
+    match inner.chars().next() {
+        Some(char) if char.is_ascii_digit() => inner
+            .find(|char: char| !char.is_ascii_digit())
+            .unwrap_or(inner.len()),
+        Some(char) if char.is_ascii_alphabetic() || char == '_' => inner
+            .find(|char: char| !char.is_ascii_alphanumeric() && char != '_')
+            .unwrap_or(inner.len()),
+        Some(char) if is_special_parameter(char) => 1,
+        _ => 0,
+    }
+}
+
+/// The byte offset of the first `separator` in `word` that isn't quoted, escaped or part of an expansion
+fn split_unquoted(word: &str, separator: char) -> Option<usize> {
+    let chars: Vec<char> = word.chars().collect();
+    let offsets: Vec<usize> = word.char_indices().map(|(offset, _)| offset).collect();
+    let mut pointer = 0;
+    while let Some(&offset) = offsets.get(pointer) {
+        if word[offset..].starts_with(separator) {
+            return Some(offset);
+        }
+        match tokenize_nested(&chars, pointer, &Dialect::BASH) {
+            Ok(nested_pointer) if nested_pointer > pointer => pointer = nested_pointer,
+            _ => pointer += 1,
+        }
+    }
+    None
+}
+
+/// The offsets of the character boundaries of `value`, its end included
+fn boundaries(value: &str) -> Vec<usize> {
+    value
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(value.len()))
+        .collect()
+}
+
+fn remove_prefix(value: &str, pattern: &Pattern, longest: bool) -> String {
+    let mut ends = boundaries(value);
+    if longest {
+        ends.reverse();
+    }
+    match ends.into_iter().find(|end| pattern.matches(&value[..*end])) {
+        Some(end) => value[end..].to_string(),
+        None => value.to_string(),
+    }
+}
+
+fn remove_suffix(value: &str, pattern: &Pattern, longest: bool) -> String {
+    let mut starts = boundaries(value);
+    if !longest {
+        starts.reverse();
+    }
+    match starts
+        .into_iter()
+        .find(|start| pattern.matches(&value[*start..]))
+    {
+        Some(start) => value[..start].to_string(),
+        None => value.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Substitution {
+    First,  // ${x/a/b}
+    All,    // ${x//a/b}
+    Prefix, // ${x/#a/b}
+    Suffix, // ${x/%a/b}
+}
+
+/// > The pattern is expanded to produce a pattern just as in filename expansion. Parameter is expanded and the
+/// > longest match of pattern against its value is replaced with string.
+fn substitute(value: &str, pattern: &Pattern, replacement: &str, mode: Substitution) -> String {
+    let offsets = boundaries(value);
+    match mode {
+        Substitution::Prefix => {
+            match offsets
+                .iter()
+                .rev()
+                .find(|end| pattern.matches(&value[..**end]))
+            {
+                Some(end) => format!("{}{}", replacement, &value[*end..]),
+                None => value.to_string(),
+            }
+        }
+        Substitution::Suffix => match offsets
+            .iter()
+            .find(|start| pattern.matches(&value[**start..]))
+        {
+            Some(start) => format!("{}{}", &value[..*start], replacement),
+            None => value.to_string(),
+        },
+        Substitution::First | Substitution::All => {
+            let mut result = String::new();
+            let mut position = 0;
+            while position + 1 < offsets.len() {
+                let start = offsets[position];
+                // The longest match, an empty match isn't replaced :
+                let end = (position + 1..offsets.len())
+                    .rev()
+                    .find(|end| pattern.matches(&value[start..offsets[*end]]));
+                match end {
+                    Some(end) => {
+                        result.push_str(replacement);
+                        position = end;
+                        if mode == Substitution::First {
+                            break;
+                        }
+                    }
+                    None => {
+                        result.push_str(&value[start..offsets[position + 1]]);
+                        position += 1;
+                    }
+                }
+            }
+            result.push_str(&value[offsets[position]..]);
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::expand::expand;
+    use crate::tokens::word::Word;
+    use std::collections::HashMap;
+
+    fn environment() -> HashMap<String, String> {
+        [
+            ("HOME", "/home/alice"),
+            ("PREFIX", "/usr/local"),
+            ("TOOL", "rg"),
+            ("empty", ""),
+            ("spaced", "  a  b "),
+            ("path", "/usr/lib/libfoo.so.1"),
+            ("name", "hello world"),
+            ("1", "one"),
+            ("2", "two words"),
+            ("3", ""),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+    }
+
+    fn fields(word: &str) -> Vec<String> {
+        expand(&Word::parse(word, &Dialect::BASH).unwrap(), &environment()).unwrap()
+    }
+
+    fn fields_with(word: &str, name: &str, value: &str) -> Vec<String> {
+        let mut environment = environment();
+        environment.insert(name.to_string(), value.to_string());
+        expand(&Word::parse(word, &Dialect::BASH).unwrap(), &environment).unwrap()
+    }
+
+    fn error(word: &str) -> String {
+        expand(&Word::parse(word, &Dialect::BASH).unwrap(), &environment())
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn test_expand_parameters() {
+        assert_eq!(vec!["/usr/local/bin/rg"], fields("$PREFIX/bin/$TOOL"));
+        assert_eq!(
+            vec!["/usr/local/bin/rg"],
+            fields("${PREFIX}/bin/\"${TOOL}\"")
+        );
+        assert_eq!(Vec::<String>::new(), fields("$unset"));
+        assert_eq!(Vec::<String>::new(), fields("$empty"));
+        assert_eq!(vec![""], fields("\"$unset\""));
+        assert_eq!(vec!["a", "b"], fields("$spaced"));
+        assert_eq!(vec!["  a  b "], fields("\"$spaced\""));
+        assert_eq!(vec!["x", "a", "b", "y"], fields("x${spaced}y"));
+        assert_eq!(vec!["11"], fields("${#name}"));
+        assert_eq!(vec!["3"], fields("$#"));
+        assert_eq!(vec!["3"], fields("${#@}"));
+    }
+
+    #[test]
+    fn test_expand_defaults() {
+        assert_eq!(vec!["default"], fields("${unset:-default}"));
+        assert_eq!(vec!["default"], fields("${empty:-default}"));
+        assert_eq!(Vec::<String>::new(), fields("${empty-default}"));
+        assert_eq!(vec!["rg"], fields("${TOOL:-default}"));
+        assert_eq!(vec!["a", "b c"], fields("${unset:-a \"b c\"}"));
+        assert_eq!(vec!["a b c"], fields("\"${unset:-a b c}\""));
+        assert_eq!(vec!["/home/alice/x"], fields("${unset:-~/x}"));
+        assert_eq!(vec!["/usr/local"], fields("${unset:=$PREFIX}"));
+        assert_eq!(vec!["set"], fields("${TOOL:+set}"));
+        assert_eq!(Vec::<String>::new(), fields("${empty:+set}"));
+        assert_eq!(vec!["set"], fields("${empty+set}"));
+        assert_eq!(vec!["rg"], fields("${TOOL:?missing}"));
+        assert_eq!(
+            "unset: missing tool at index 0",
+            error("${unset:?missing tool}")
+        );
+        assert_eq!(
+            "empty: parameter null or not set at index 2",
+            error("a=${empty:?}")
+        );
+    }
+
+    #[test]
+    fn test_expand_patterns() {
+        assert_eq!(vec!["usr/lib/libfoo.so.1"], fields("${path#*/}"));
+        assert_eq!(vec!["libfoo.so.1"], fields("${path##*/}"));
+        assert_eq!(vec!["/usr/lib/libfoo.so"], fields("${path%.*}"));
+        assert_eq!(vec!["/usr/lib/libfoo"], fields("${path%%.so*}"));
+        assert_eq!(vec!["/usr/lib/libfoo.so.1"], fields("${path#\"*\"/}"));
+        assert_eq!(vec!["hello_world"], fields("${name/ /_}"));
+        assert_eq!(vec!["h*llo world"], fields("\"${name/[aeiou]/*}\""));
+        assert_eq!(vec!["h-ll- w-rld"], fields("\"${name//[aeiou]/-}\""));
+        assert_eq!(vec!["jello", "world"], fields("${name/#h/j}"));
+        assert_eq!(vec!["hello there"], fields("\"${name/%w*/there}\""));
+        assert_eq!(vec![">hello world"], fields("\"${name/#/>}\""));
+        assert_eq!(vec!["hll", "wrld"], fields("${name//[aeiou]}"));
+        assert_eq!(vec!["a/b"], fields_with("${x//\\//\\/}", "x", "a/b"));
+    }
+
+    #[test]
+    fn test_expand_case() {
+        assert_eq!(vec!["HELLO WORLD"], fields("\"${name^^}\""));
+        assert_eq!(vec!["Hello world"], fields("\"${name^}\""));
+        assert_eq!(vec!["heLLo worLd"], fields("\"${name^^l}\""));
+        assert_eq!(vec!["hELLO"], fields_with("${x,}", "x", "HELLO"));
+        assert_eq!(vec!["hello"], fields_with("${x,,}", "x", "HELLO"));
+    }
+
+    #[test]
+    fn test_expand_positional() {
+        assert_eq!(vec!["one", "two words", ""], fields("\"$@\""));
+        assert_eq!(vec!["one", "two", "words"], fields("$@"));
+        assert_eq!(vec!["one two words "], fields("\"$*\""));
+        assert_eq!(vec!["-one", "two words", "+"], fields("\"-$@+\""));
+        assert_eq!(vec!["one,two words,"], fields_with("\"$*\"", "IFS", ","));
+        assert_eq!(vec!["two words"], fields("\"$2\""));
+
+        let empty = HashMap::new();
+        let word = Word::parse("\"$@\"", &Dialect::BASH).unwrap();
+        assert_eq!(Vec::<String>::new(), expand(&word, &empty).unwrap());
+        let word = Word::parse("\"$@\"''", &Dialect::BASH).unwrap();
+        assert_eq!(vec![""], expand(&word, &empty).unwrap());
+    }
+
+    #[test]
+    fn test_expand_word_splitting() {
+        assert_eq!(vec!["a", "b", "c"], fields_with("$x", "x", " a\tb\n c "));
+        assert_eq!(vec!["a::b"], fields_with("$x", "x", "a::b"));
+        let mut environment = environment();
+        environment.insert(String::from("IFS"), String::from(" :"));
+        for (value, expected) in [
+            ("a::b", vec!["a", "", "b"]),
+            ("a : b", vec!["a", "b"]),
+            (" :b", vec!["", "b"]),
+            ("a:", vec!["a"]),
+        ] {
+            environment.insert(String::from("x"), String::from(value));
+            let word = Word::parse("$x", &Dialect::BASH).unwrap();
+            assert_eq!(expected, expand(&word, &environment).unwrap());
+        }
+        environment.insert(String::from("IFS"), String::from(":"));
+        environment.insert(String::from("x"), String::from("a b:c"));
+        let word = Word::parse("$x", &Dialect::BASH).unwrap();
+        assert_eq!(vec!["a b", "c"], expand(&word, &environment).unwrap());
+        environment.insert(String::from("IFS"), String::new());
+        assert_eq!(vec!["a b:c"], expand(&word, &environment).unwrap());
+    }
+
+    #[test]
+    fn test_expand_quotes_and_tilde() {
+        assert_eq!(vec!["a b$x\\n"], fields("'a b$x\\n'"));
+        assert_eq!(vec!["a\n"], fields("$'a\\n'"));
+        assert_eq!(vec!["$x \\a \" \\"], fields("\"\\$x \\a \\\" \\\\\""));
+        assert_eq!(vec!["a b"], fields("a\\ b"));
+        assert_eq!(vec!["/home/alice/.config"], fields("~/.config"));
+        assert_eq!(vec!["x=/home/alice"], fields("x=~"));
+        assert_eq!(vec!["~"], fields("\"~\""));
+        assert_eq!(vec!["*.txt"], fields("'*'.txt"));
+        assert_eq!(vec!["*"], fields_with("\"$x\"", "x", "*"));
+    }
+
+    #[test]
+    fn test_expand_arithmetic() {
+        assert_eq!(vec!["lib2"], fields("lib$((1 + 1))"));
+        assert_eq!(vec!["3"], fields("$(( $# ))"));
+        assert_eq!(vec!["4"], fields("$((${#TOOL} * 2))"));
+        assert_eq!(vec!["-1"], fields("\"$((x = 5, x - 6))\""));
+        assert_eq!(vec!["8"], fields_with("$((1 << x))", "x", "3"));
+        assert_eq!("Division by zero at index 9", error("a-$((1 / unset))"));
+        assert_eq!(
+            "Unexpected end of arithmetic expression at index 7",
+            error("$((1 / $unset))")
+        );
+        assert_eq!(
+            "Unexpected end of arithmetic expression at index 6",
+            error("$((1 +))")
+        );
+    }
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(vec!["a1", "a2", "b1", "b2"], fields("{a,b}{1..2}"));
+        assert_eq!(vec!["rg.bak", "rg.orig"], fields("$TOOL{.bak,.orig}"));
+        assert_eq!(vec!["{a,b}"], fields("'{a,b}'"));
+        assert_eq!(
+            "Brace expansion to more than 1000000 words at index 0",
+            error("{1..99999999999}")
+        );
+    }
+
+    #[test]
+    fn test_expand_unresolvable() {
+        assert_eq!(
+            "Cannot statically evaluate command substitution `$(id)` at index 2",
+            error("a-$(id)")
+        );
+        assert_eq!(
+            "Cannot statically evaluate command substitution ``id`` at index 2",
+            error("\"a`id`\"")
+        );
+        assert_eq!(
+            "Cannot statically evaluate process substitution `<(ls)` at index 0",
+            error("<(ls)")
+        );
+        assert_eq!(
+            "Cannot statically evaluate tilde prefix `~bob` at index 0",
+            error("~bob/x")
+        );
+        assert_eq!(
+            "Cannot statically evaluate pathname expansion `/usr/local/*` at index 0",
+            error("$PREFIX/*")
+        );
+        assert_eq!(
+            "Cannot statically evaluate pathname expansion `*` at index 0",
+            expand(
+                &Word::parse("$x", &Dialect::BASH).unwrap(),
+                &HashMap::from([(String::from("x"), String::from("*"))])
+            )
+            .unwrap_err()
+            .to_string()
+        );
+        assert_eq!(
+            "Cannot statically evaluate parameter expansion `${name:0:2}` at index 1",
+            error("\"${name:0:2}\"")
+        );
+        assert_eq!(
+            "Cannot statically evaluate parameter expansion `${!name}` at index 0",
+            error("${!name}")
+        );
+        assert_eq!(
+            "Cannot statically evaluate command substitution `$(id)` at index 9",
+            error("${unset:-$(id)}")
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use crate::dialect::Dialect;
+use crate::tokenize_with_dialect;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+/// The tokens of a script, see [bash_tokenize]. C code only handles it through a pointer.
+pub struct BashTokenList {
+    tokens: Vec<BashToken>,
+}
+
+struct BashToken {
+    kind: CString,
+    text: Vec<u8>, // NUL-terminated, the script itself can contain NUL bytes
+    span: BashSpan,
+}
+
+/// The location of a token as byte offsets into the script, `end` is exclusive
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BashSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// bash_tokenize
+/// Tokenizes the `len` bytes of UTF-8 at `script`, which don't need a terminating NUL. `dialect` is the
+/// NUL-terminated name of a preset, `"bash"`, `"posix"`, `"dash"`, `"ksh93"`, `"zsh"` or `"ash"`, or NULL for
+/// bash.
+///
+/// Returns the tokens, to free with [bash_token_list_free]. When the script can't be tokenized it returns
+/// NULL and, if `error` isn't NULL, points `*error` to the message, to free with [bash_error_free].
+///
+/// # Safety
+/// `script` must point to `len` readable bytes, it can be NULL when `len` is 0. `dialect` must be NULL or a
+/// NUL-terminated string and `error` NULL or writable.
+#[no_mangle]
+pub unsafe extern "C" fn bash_tokenize(
+    script: *const c_char,
+    len: usize,
+    dialect: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut BashTokenList {
+    let script = match script.is_null() {
+        true => &[][..],
+        false => std::slice::from_raw_parts(script.cast::<u8>(), len),
+    };
+    let dialect = match dialect.is_null() {
+        true => None,
+        false => Some(CStr::from_ptr(dialect)),
+    };
+    match tokenize_bytes(script, dialect) {
+        Ok(list) => Box::into_raw(Box::new(list)),
+        Err(message) => {
+            if !error.is_null() {
+                // Messages can quote the script, which may contain NUL bytes :
+                *error = CString::new(message.replace('\0', "\\0"))
+                    .map_or(ptr::null_mut(), CString::into_raw);
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+fn tokenize_bytes(script: &[u8], dialect: Option<&CStr>) -> Result<BashTokenList, String> {
+    let script = std::str::from_utf8(script)
+        .map_err(|error| format!("Script isn't valid UTF-8 at index {}", error.valid_up_to()))?;
+    let dialect: Dialect = match dialect {
+        Some(name) => name
+            .to_string_lossy()
+            .parse()
+            .map_err(|error: Box<dyn std::error::Error>| error.to_string())?,
+        None => Dialect::BASH,
+    };
+    let tokens =
+        tokenize_with_dialect(script.to_string(), &dialect).map_err(|error| error.to_string())?;
+    let tokens = tokens
+        .into_iter()
+        .map(|token| BashToken {
+            kind: CString::new(format!("{:?}", token.kind)).unwrap_or_default(),
+            text: token.text.into_bytes().into_iter().chain([0]).collect(),
+            span: BashSpan {
+                start: token.span.start,
+                end: token.span.end,
+            },
+        })
+        .collect();
+    Ok(BashTokenList { tokens })
+}
+
+/// The token at `index`, `None` for a NULL list or an index past the end
+unsafe fn token<'a>(list: *const BashTokenList, index: usize) -> Option<&'a BashToken> {
+    list.as_ref()?.tokens.get(index)
+}
+
+/// bash_token_list_len
+/// The number of tokens in `list`, 0 for NULL.
+///
+/// # Safety
+/// `list` must be NULL or returned by [bash_tokenize] and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn bash_token_list_len(list: *const BashTokenList) -> usize {
+    list.as_ref().map_or(0, |list| list.tokens.len())
+}
+
+/// bash_token_kind
+/// The kind of the token at `index` as a NUL-terminated name, `"Word"`, `"Pipe"`, ... like the JSON of the
+/// `serde` feature. NULL when `index` is out of range. The name lives as long as `list`.
+///
+/// # Safety
+/// `list` must be NULL or returned by [bash_tokenize] and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn bash_token_kind(
+    list: *const BashTokenList,
+    index: usize,
+) -> *const c_char {
+    token(list, index).map_or(ptr::null(), |token| token.kind.as_ptr())
+}
+
+/// bash_token_text
+/// The text of the token at `index`, NUL-terminated, with its length in bytes in `*len` unless `len` is NULL.
+/// The length is the way to go for scripts with NUL bytes. NULL when `index` is out of range. The text lives
+/// as long as `list`.
+///
+/// # Safety
+/// `list` must be NULL or returned by [bash_tokenize] and not freed yet, `len` NULL or writable.
+#[no_mangle]
+pub unsafe extern "C" fn bash_token_text(
+    list: *const BashTokenList,
+    index: usize,
+    len: *mut usize,
+) -> *const c_char {
+    let Some(token) = token(list, index) else {
+        return ptr::null();
+    };
+    if !len.is_null() {
+        *len = token.text.len() - 1;
+    }
+    token.text.as_ptr().cast()
+}
+
+/// bash_token_span
+/// The span of the token at `index`, `{0, 0}` when `index` is out of range.
+///
+/// # Safety
+/// `list` must be NULL or returned by [bash_tokenize] and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn bash_token_span(list: *const BashTokenList, index: usize) -> BashSpan {
+    token(list, index).map_or(BashSpan::default(), |token| token.span)
+}
+
+/// bash_token_list_free
+/// Frees the tokens returned by [bash_tokenize], doing nothing for NULL.
+///
+/// # Safety
+/// `list` must be NULL or returned by [bash_tokenize], and not be used or freed again afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn bash_token_list_free(list: *mut BashTokenList) {
+    if !list.is_null() {
+        drop(Box::from_raw(list));
+    }
+}
+
+/// bash_error_free
+/// Frees an error message from [bash_tokenize], doing nothing for NULL.
+///
+/// # Safety
+/// `error` must be NULL or a message set by [bash_tokenize], and not be used or freed again afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn bash_error_free(error: *mut c_char) {
+    if !error.is_null() {
+        drop(CString::from_raw(error));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ffi::{
+        bash_error_free, bash_token_kind, bash_token_list_free, bash_token_list_len,
+        bash_token_span, bash_token_text, bash_tokenize, BashSpan,
+    };
+    use std::ffi::{c_char, CStr};
+    use std::ptr;
+
+    /// The error message of tokenizing `script`
+    fn error(script: &[u8], dialect: Option<&CStr>) -> String {
+        let dialect = dialect.map_or(ptr::null(), CStr::as_ptr);
+        let mut error: *mut c_char = ptr::null_mut();
+        unsafe {
+            let list = bash_tokenize(script.as_ptr().cast(), script.len(), dialect, &mut error);
+            assert!(list.is_null());
+            let message = CStr::from_ptr(error).to_string_lossy().into_owned();
+            bash_error_free(error);
+            message
+        }
+    }
+
+    #[test]
+    fn test_bash_tokenize() {
+        let script = "echo é\0| x"; // Without the `|`
+        unsafe {
+            let list = bash_tokenize(script.as_ptr().cast(), 8, ptr::null(), ptr::null_mut());
+            assert_eq!(3, bash_token_list_len(list));
+            assert_eq!(c"Word", CStr::from_ptr(bash_token_kind(list, 2)));
+            assert_eq!(BashSpan { start: 5, end: 8 }, bash_token_span(list, 2));
+            let mut len = 0;
+            let text = bash_token_text(list, 2, &mut len);
+            assert_eq!(3, len);
+            assert_eq!(
+                b"\xc3\xa9\0",
+                std::slice::from_raw_parts(text.cast::<u8>(), len)
+            );
+
+            assert!(bash_token_kind(list, 3).is_null());
+            assert!(bash_token_text(list, 3, &mut len).is_null());
+            assert_eq!(BashSpan::default(), bash_token_span(list, 3));
+            bash_token_list_free(list);
+
+            let list = bash_tokenize(c"a |& b".as_ptr(), 6, c"dash".as_ptr(), ptr::null_mut());
+            assert_eq!(c"Pipe", CStr::from_ptr(bash_token_kind(list, 2)));
+            bash_token_list_free(list);
+
+            let list = bash_tokenize(ptr::null(), 0, ptr::null(), ptr::null_mut());
+            assert_eq!(0, bash_token_list_len(list));
+            bash_token_list_free(list);
+            assert_eq!(0, bash_token_list_len(ptr::null()));
+            bash_token_list_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_bash_tokenize_errors() {
+        assert_eq!(
+            "Unterminated string literal at index 5",
+            error(b"echo 'a", None)
+        );
+        assert_eq!(
+            "Script isn't valid UTF-8 at index 3",
+            error(b"ls \xff", None)
+        );
+        assert_eq!("Unknown dialect `fish`", error(b"ls", Some(c"fish")));
+    }
+}
@@ -0,0 +1,369 @@
+use crate::dialect::Dialect;
+use crate::lexer::nested::tokenize_expansion;
+use crate::lexer::pattern::Pattern;
+use crate::tokenize;
+use crate::tokens::span::Span;
+use crate::tokens::tokens::{Token, TokenKind};
+use std::ops::Range;
+
+/// detect_shell_injection
+/// Checks whether `user_input`, spliced into `command`, changes the structure of the command.
+/// For every place the user input occurs in the command we look at the tokens it occupies, it's an injection when it :
+/// - Crosses token boundaries : `a; rm -rf /`, `a b` or `a' 'b`
+/// - Is an operator or an expansion on its own : `|`, `$HOME`
+/// - Introduces an expansion inside a token that allows them, on its own or with the characters after it :
+///   `"$(id)"`, `${x:-$(id)}`, a `$` before `HOME`
+/// - Is more than a name, a number or a plain word inside an expansion : `${x:=evil}`, `$((1, x=5))`
+/// - Introduces a new command through a newline
+///
+/// A command that can't be tokenized is reported as an injection, as we can't tell what the user input did.
+pub fn detect_shell_injection(command: &str, user_input: &str) -> bool {
+    if user_input.is_empty() {
+        return false;
+    }
+    let tokens = match tokenize(command.to_string()) {
+        Ok(tokens) => tokens,
+        Err(_) => return true,
+    };
+
+    // Occurrences can overlap (`aa` in `aaa`), so we check every starting position :
+    (0..command.len())
+        .filter(|start| command.is_char_boundary(*start))
+        .filter(|start| command[*start..].starts_with(user_input))
+        .any(|start| {
+            let span = Span::new(start, start + user_input.len());
+            changes_structure(&tokens, span, user_input)
+        })
+}
+
+fn changes_structure(tokens: &[Token], span: Span, user_input: &str) -> bool {
+    let mut overlapping = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| token.span.overlaps(&span));
+    let Some((index, token)) = overlapping.next() else {
+        return false;
+    };
+    if overlapping.next().is_some() {
+        return true; // Crosses token boundaries
+    }
+
+    match token.kind {
+        // Nothing inside of these can change the meaning of the token :
+        TokenKind::SingleQuote | TokenKind::DollarSingleQuote => false,
+        // A number right before a redirection operator is the file descriptor it redirects, `2>file` :
+        TokenKind::Number => tokens
+            .get(index + 1)
+            .is_some_and(|next| next.kind.is_redirection()),
+        // Whitespace only separates words, a newline starts a new command :
+        TokenKind::WhiteSpace => false,
+        TokenKind::Newline => true,
+        // Text inside an existing comment is never executed, but it can't start one :
+        TokenKind::Comment => span.start <= token.span.start,
+        // A trailing backslash would escape whatever follows the user input :
+        TokenKind::Word => user_input.ends_with('\\'),
+        // A wildcard turns the argument into the names of the files it matches :
+        TokenKind::Pattern => {
+            user_input.ends_with('\\') || !Pattern::parse(user_input).is_literal()
+        }
+        // New alternatives or a sequence turn one argument into several :
+        TokenKind::BraceExpansion => {
+            user_input.contains([',', '{', '}', '\\'])
+                || user_input.contains("..")
+                || introduces_expansion(user_input)
+        }
+        // Any part of `~user` picks the home directory it's replaced by :
+        TokenKind::TildePrefix => true,
+        // Any part of `!!` picks the command line it's replaced by :
+        TokenKind::HistoryExpansion => true,
+        TokenKind::DoubleQuote
+        | TokenKind::DollarDoubleQuote
+        | TokenKind::HereDocument
+        | TokenKind::ParameterExpansion
+        | TokenKind::ArithmeticExpansion
+        | TokenKind::ArithmeticCommand => {
+            // The user input is checked together with the characters around it, `$` and `(id)` make `$(id)` :
+            let chars: Vec<char> = token.text.chars().collect();
+            let char_index =
+                |offset: usize| token.text[..offset - token.span.start].chars().count();
+            let user = char_index(span.start)..char_index(span.end);
+            if token.kind == TokenKind::ArithmeticCommand {
+                let inner = 2..chars.len() - 2;
+                return !(inner.start <= user.start && user.end <= inner.end)
+                    || changes_expansions(&chars, inner, user, Context::Arithmetic);
+            }
+            changes_expansions(&chars, 0..chars.len(), user, Context::Quoted)
+        }
+        TokenKind::CommandSubstitution | TokenKind::ProcessSubstitution => {
+            let delimiters = if token.text.starts_with('`') {
+                (1, 1)
+            } else {
+                (2, 1)
+            };
+            let inner = Span::new(
+                token.span.start + delimiters.0,
+                token.span.end.saturating_sub(delimiters.1),
+            );
+            if !inner.contains(&span) {
+                return true; // The user input is (part of) the `$(` or `)`
+            }
+            // The user input is part of a nested command, which we check like any other command :
+            let inner_text = &token.text[delimiters.0..token.text.len() - delimiters.1];
+            detect_shell_injection(inner_text, user_input)
+        }
+        // Operators and keywords :
+        _ => true,
+    }
+}
+
+/// What the text between the expansions of a token is
+enum Context {
+    Quoted,     // Double quotes or a here-document, where it's literal
+    Parameter,  // The name or word of a parameter expansion
+    Arithmetic, // An arithmetic expression
+}
+
+/// Whether the user input at the character indexes `user`, inside `range` of `chars`, adds or changes an
+/// expansion : it's (part of) the `$` or `` ` `` starting one, or it's inside one and more than a name, a
+/// number or a plain word.
+fn changes_expansions(
+    chars: &[char],
+    range: Range<usize>,
+    user: Range<usize>,
+    context: Context,
+) -> bool {
+    let mut pointer = range.start;
+    while pointer < range.end {
+        match chars[pointer] {
+            // A trailing backslash would escape whatever follows the user input :
+            '\\' if user.contains(&pointer) && pointer + 1 == user.end => return true,
+            '\\' => pointer += 2,
+            '$' | '`' => {
+                let end = match tokenize_expansion(chars, pointer, &Dialect::BASH) {
+                    Ok(end) => end,
+                    Err(_) => return true, // e.g. an unterminated `$(`
+                };
+                if end == pointer {
+                    pointer += 1;
+                } else if user.contains(&pointer) {
+                    return true; // The user input starts the expansion
+                } else if user.start < end && pointer < user.end {
+                    return changes_expansion(chars, pointer..end, user);
+                } else {
+                    pointer = end;
+                }
+            }
+            _ => pointer += 1,
+        }
+    }
+    let text: String = chars[user].iter().collect();
+    match context {
+        Context::Quoted => false,
+        // A different operator or a `}` changes what the expansion does, `${x:=evil}` :
+        Context::Parameter => !text
+            .chars()
+            .all(|char| char.is_alphanumeric() || char == '_' || char == '.'),
+        // A single operand, `1, x=5` would add an assignment :
+        Context::Arithmetic => !text
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_'),
+    }
+}
+
+/// Whether the user input at `user`, after the `$` or `` ` `` of the expansion at `expansion`, changes it
+fn changes_expansion(chars: &[char], expansion: Range<usize>, user: Range<usize>) -> bool {
+    let Range { start, end } = expansion;
+    let (inner, context) = match chars[start + 1..end] {
+        ['(', '(', .., ')', ')'] if chars[start] == '$' => {
+            (start + 3..end - 2, Some(Context::Arithmetic))
+        }
+        ['(', ..] => (start + 2..end - 1, None),
+        _ if chars[start] == '`' => (start + 1..end - 1, None),
+        ['{', ..] => (start + 2..end - 1, Some(Context::Parameter)),
+        _ => (start + 1..end, Some(Context::Parameter)),
+    };
+    if !(inner.start <= user.start && user.end <= inner.end) {
+        return true; // The user input is (part of) the `$(`, `${` or `}`
+    }
+    match context {
+        Some(context) => changes_expansions(chars, inner, user, context),
+        // The user input is part of a nested command, which we check like any other command :
+        None => {
+            let inner_text: String = chars[inner].iter().collect();
+            let user_input: String = chars[user].iter().collect();
+            detect_shell_injection(&inner_text, &user_input)
+        }
+    }
+}
+
+/// Whether `user_input` contains an expansion, checked with the same lexers that tokenize them.
+fn introduces_expansion(user_input: &str) -> bool {
+    let chars: Vec<char> = user_input.chars().collect();
+    (0..chars.len())
+        .filter(|pointer| chars[*pointer] == '$' || chars[*pointer] == '`')
+        .any(
+            |pointer| match tokenize_expansion(&chars, pointer, &Dialect::BASH) {
+                Ok(expansion_pointer) => expansion_pointer != pointer,
+                Err(_) => true, // e.g. an unterminated `$(`
+            },
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::injection::detect_shell_injection;
+
+    #[test]
+    fn test_safe_user_input() {
+        assert!(!detect_shell_injection("ls -l", "-l"));
+        assert!(!detect_shell_injection("cat file.txt", "file.txt"));
+        assert!(!detect_shell_injection("echo hello", "ell"));
+        assert!(!detect_shell_injection("echo 'hello world'", "hello world"));
+        assert!(!detect_shell_injection("echo 'a; rm -rf /'", "a; rm -rf /"));
+        assert!(!detect_shell_injection(
+            "echo \"hello world\"",
+            "hello world"
+        ));
+        assert!(!detect_shell_injection("echo \"it's\"", "it's"));
+        assert!(!detect_shell_injection("echo $'a\\nb'", "a\\nb"));
+        assert!(!detect_shell_injection("echo  a", " "));
+        assert!(!detect_shell_injection("echo $(echo safe)", "safe"));
+        assert!(!detect_shell_injection("echo \"$HOME\"/x", "x"));
+        assert!(!detect_shell_injection("echo \"price: $\"", "price: $"));
+    }
+
+    #[test]
+    fn test_user_input_not_in_command() {
+        assert!(!detect_shell_injection("ls -l", "; rm -rf /"));
+        assert!(!detect_shell_injection("ls -l", ""));
+    }
+
+    #[test]
+    fn test_operators() {
+        assert!(detect_shell_injection("ls; rm -rf /", "; rm -rf /"));
+        assert!(detect_shell_injection("ls;rm", ";"));
+        assert!(detect_shell_injection("ls | cat", "|"));
+        assert!(detect_shell_injection("ls && id", "&& id"));
+        assert!(detect_shell_injection("cat < /etc/passwd", "< /etc/passwd"));
+        assert!(detect_shell_injection("ls>out", ">"));
+        assert!(detect_shell_injection("(id)", "(id)"));
+    }
+
+    #[test]
+    fn test_crossing_token_boundaries() {
+        assert!(detect_shell_injection("echo hello world", "hello world"));
+        assert!(detect_shell_injection("echo 'x' 'y'", "x' 'y"));
+        assert!(detect_shell_injection("echo \"a\" \"b\"", "a\" \"b"));
+        assert!(detect_shell_injection("rm -rf /", "rm -rf /"));
+    }
+
+    #[test]
+    fn test_substitutions() {
+        assert!(detect_shell_injection("echo $(id)", "$(id)"));
+        assert!(detect_shell_injection("echo `id`", "`id`"));
+        assert!(detect_shell_injection("echo $HOME", "$HOME"));
+        assert!(detect_shell_injection("echo $((1+2))", "$((1+2))"));
+        assert!(detect_shell_injection("echo \"$(id)\"", "$(id)"));
+        assert!(detect_shell_injection("echo \"`id`\"", "`id`"));
+        assert!(detect_shell_injection("echo \"a $USER\"", "a $USER"));
+        assert!(detect_shell_injection("echo ${x:-$(id)}", "$(id)"));
+        assert!(detect_shell_injection("echo $(echo a; id)", "a; id"));
+        assert!(detect_shell_injection("echo $(echo a)", "$(echo"));
+        assert!(!detect_shell_injection("diff <(cat a) b", "a"));
+        assert!(detect_shell_injection("diff <(cat a; id) b", "a; id"));
+        assert!(detect_shell_injection("cat <(id)", "<(id)"));
+        assert!(!detect_shell_injection("((x += 2))", "2"));
+        assert!(detect_shell_injection("((x += $(id)))", "$(id)"));
+        // The user input only becomes an expansion with the characters after it :
+        assert!(detect_shell_injection("echo \"$(id)\"", "$"));
+        assert!(detect_shell_injection("echo $HOME", "$"));
+        assert!(detect_shell_injection("echo ${HOME}", "$"));
+        assert!(detect_shell_injection("echo $((1))", "$"));
+        assert!(detect_shell_injection("echo \"a$(id)\"", "a$"));
+        assert!(detect_shell_injection("echo \"$(echo a; id)\"", "a; id"));
+        assert!(!detect_shell_injection("echo \"$(echo a)\"", "a"));
+        assert!(!detect_shell_injection("echo \"\\$HOME\"", "$HOME"));
+        // Operators and assignments inside an expansion :
+        assert!(detect_shell_injection("echo $((1, x=5))", "1, x=5"));
+        assert!(detect_shell_injection("((x = 1 + 2))", "1 + 2"));
+        assert!(detect_shell_injection("echo ${x:=evil}", "x:=evil"));
+        assert!(detect_shell_injection("echo \"${x}\"", "x}"));
+        assert!(!detect_shell_injection("echo $((x + 1))", "x"));
+        assert!(!detect_shell_injection("echo ${x:-default}", "default"));
+        assert!(!detect_shell_injection("echo \"${HOME}\"", "HOME"));
+    }
+
+    #[test]
+    fn test_new_commands() {
+        assert!(detect_shell_injection("echo a\nid", "a\nid"));
+        assert!(detect_shell_injection("echo a\n\nid", "\n"));
+    }
+
+    #[test]
+    fn test_here_documents() {
+        assert!(!detect_shell_injection(
+            "cat <<EOF\nhello world\nEOF",
+            "hello world"
+        ));
+        assert!(detect_shell_injection("cat <<EOF\n$(id)\nEOF", "$(id)"));
+        assert!(detect_shell_injection("cat <<EOF\n$(id)\nEOF", "$"));
+        assert!(detect_shell_injection(
+            "cat <<EOF\na\nEOF\nid\nEOF",
+            "a\nEOF\nid"
+        ));
+    }
+
+    #[test]
+    fn test_comments() {
+        assert!(!detect_shell_injection("ls # a; b", "a; b"));
+        assert!(detect_shell_injection("ls # -l", "# -l"));
+        assert!(detect_shell_injection("ls #-l", "#"));
+    }
+
+    #[test]
+    fn test_escapes_and_broken_quoting() {
+        assert!(detect_shell_injection("echo a\\ b", "a\\"));
+        assert!(detect_shell_injection("echo 'it's'", "it's"));
+        assert!(detect_shell_injection("echo \"a\\\"", "a\\"));
+    }
+
+    #[test]
+    fn test_brace_expansions() {
+        assert!(detect_shell_injection("rm {a,/}", "{a,/}"));
+        assert!(detect_shell_injection("rm file{a,/}", "a,/"));
+        assert!(detect_shell_injection("rm {1..9}", "1..9"));
+        assert!(detect_shell_injection("rm x{a,b}", "x{a,b}"));
+        assert!(!detect_shell_injection("rm {a,b}", "a"));
+        assert!(!detect_shell_injection("rm '{a,b}'", "{a,b}"));
+        assert!(!detect_shell_injection("rm {a}", "{a}"));
+    }
+
+    #[test]
+    fn test_patterns() {
+        assert!(detect_shell_injection("rm *", "*"));
+        assert!(detect_shell_injection("cat /etc/*", "/etc/*"));
+        assert!(detect_shell_injection("cat file[0-9]", "[0-9]"));
+        assert!(!detect_shell_injection("cat notes*.txt", "notes"));
+        assert!(!detect_shell_injection("cat '*'", "*"));
+        assert!(!detect_shell_injection("cat \\*", "*"));
+    }
+
+    #[test]
+    fn test_io_numbers() {
+        assert!(detect_shell_injection("cmd 2>/etc/passwd", "2"));
+        assert!(detect_shell_injection("cmd 12>/etc/passwd", "1"));
+        assert!(detect_shell_injection("cat 0</etc/shadow", "0"));
+        assert!(!detect_shell_injection("head -n 2 file", "2"));
+        assert!(!detect_shell_injection("sleep 2 >out", "2"));
+    }
+
+    #[test]
+    fn test_tilde_prefixes() {
+        assert!(detect_shell_injection("cat ~/.ssh/id_rsa", "~"));
+        assert!(detect_shell_injection("cat ~root/notes", "root"));
+        assert!(detect_shell_injection("PATH=/bin:~/bin cmd", "~"));
+        assert!(!detect_shell_injection("cat ~/notes", "notes"));
+        assert!(!detect_shell_injection("cat a~", "~"));
+        assert!(!detect_shell_injection("cat '~'", "~"));
+    }
+}
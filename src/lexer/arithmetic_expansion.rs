@@ -0,0 +1,218 @@
+use crate::dialect::Dialect;
+use crate::lexer::nested::tokenize_nested;
+use std::collections::HashMap;
+
+/// tokenize_arithmetic_expansion
+/// > [3.5.5 Arithmetic Expansion](https://www.gnu.org/software/bash/manual/bash.html#Arithmetic-Expansion)
+/// > Arithmetic expansion allows the evaluation of an arithmetic expression and the substitution of the result.
+/// > The format for arithmetic expansion is: `$(( expression ))`
+///
+/// Like bash, `$((` that doesn't end with a matching `))` is not arithmetic (`$((ls) | a)` is a command
+/// substitution), in that case we return `start` and leave it to the command substitution lexer.
+pub fn tokenize_arithmetic_expansion(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut prefix = content.iter().skip(start).copied();
+    if prefix.next() != Some('$') || prefix.next() != Some('(') || prefix.next() != Some('(') {
+        return Ok(start);
+    }
+    let mut pointer = start + 3; // Skipped `$((`
+    let mut depth = 0;
+    loop {
+        match content.get(pointer).copied() {
+            Some('(') => depth += 1,
+            Some(')') if depth == 0 => {
+                if content.get(pointer + 1).copied() == Some(')') {
+                    return Ok(pointer + 2);
+                }
+                return Ok(start);
+            }
+            Some(')') => depth -= 1,
+            Some(_) => {
+                let nested_pointer = tokenize_nested(content, pointer, dialect)?;
+                if nested_pointer != pointer {
+                    pointer = nested_pointer;
+                    continue;
+                }
+            }
+            None => return Ok(start),
+        }
+        pointer += 1;
+    }
+}
+
+/// ArithmeticCommands
+/// > [3.2.5.2 Conditional Constructs](https://www.gnu.org/software/bash/manual/bash.html#Conditional-Constructs)
+/// > `(( expression ))`
+/// > The arithmetic expression is evaluated according to the rules described below (see Shell Arithmetic).
+///
+/// Finds the `((...))` arithmetic commands of a script, the caller knows where a command starts. Like `$((`, a
+/// `((` that doesn't end with a matching `))` is two parentheses : `((ls) | a)` is a subshell in a subshell.
+/// Every scan for a closing `))` remembers where the parentheses it passed close, so `((((` repeated is read
+/// once instead of once per `((`.
+#[derive(Debug, Default, Clone)]
+pub struct ArithmeticCommands {
+    closing: HashMap<usize, Option<usize>>, // The `)` matching the `(` at an index, `None` when there's none
+}
+
+impl ArithmeticCommands {
+    /// Returns the end of the arithmetic command starting at `start`, `start` when there's none
+    pub fn tokenize(
+        &mut self,
+        content: &[char],
+        start: usize,
+        dialect: &Dialect,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut prefix = content.iter().skip(start).copied();
+        if !dialect.arithmetic_command || prefix.next() != Some('(') || prefix.next() != Some('(') {
+            return Ok(start);
+        }
+        let closing = match self.closing.get(&(start + 1)) {
+            Some(closing) => *closing,
+            None => self.scan(content, start + 1, dialect)?,
+        };
+        match closing {
+            Some(end) if content.get(end + 1).copied() == Some(')') => Ok(end + 2),
+            _ => Ok(start),
+        }
+    }
+
+    /// Where the `)` matching the `(` at `open` is, the parentheses in between are recorded on the way
+    fn scan(
+        &mut self,
+        content: &[char],
+        open: usize,
+        dialect: &Dialect,
+    ) -> Result<Option<usize>, Box<dyn std::error::Error>> {
+        let mut opened = vec![open];
+        let mut pointer = open + 1;
+        while let Some(char) = content.get(pointer).copied() {
+            match char {
+                '(' => opened.push(pointer),
+                ')' => {
+                    if let Some(parenthesis) = opened.pop() {
+                        self.closing.insert(parenthesis, Some(pointer));
+                    }
+                    if opened.is_empty() {
+                        return Ok(Some(pointer));
+                    }
+                }
+                _ => {
+                    let nested_pointer = tokenize_nested(content, pointer, dialect)?;
+                    if nested_pointer != pointer {
+                        pointer = nested_pointer;
+                        continue;
+                    }
+                }
+            }
+            pointer += 1;
+        }
+        for parenthesis in opened {
+            self.closing.insert(parenthesis, None);
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::lexer::arithmetic_expansion::{tokenize_arithmetic_expansion, ArithmeticCommands};
+    use crate::lexer::chars;
+
+    #[test]
+    pub fn test_tokenize_arithmetic_expansion_basic() {
+        assert_eq!(
+            0,
+            tokenize_arithmetic_expansion(&chars("1+2"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_arithmetic_expansion(&chars("$(ls)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            8,
+            tokenize_arithmetic_expansion(&chars("$((1+2))"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            17,
+            tokenize_arithmetic_expansion(&chars("echo $(( a * 2 )) ok"), 5, &Dialect::BASH)
+                .unwrap()
+        );
+        assert_eq!(
+            12,
+            tokenize_arithmetic_expansion(&chars("$(((1+2)*3))"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            16,
+            tokenize_arithmetic_expansion(&chars("$((x+$(echo 1)))"), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_arithmetic_expansion_not_arithmetic() {
+        assert_eq!(
+            0,
+            tokenize_arithmetic_expansion(&chars("$((ls) | a)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_arithmetic_expansion(&chars("$((1+2)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_arithmetic_expansion(&chars("$(("), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_arithmetic_command() {
+        let tokenize = |content: &str, start: usize| {
+            ArithmeticCommands::default()
+                .tokenize(&chars(content), start, &Dialect::BASH)
+                .unwrap()
+        };
+        assert_eq!(9, tokenize("((x > 1))", 0));
+        assert_eq!(19, tokenize("for ((i=0;i<3;i++)); do", 4));
+        assert_eq!(15, tokenize("(( (a) + \")\" ))", 0));
+        assert_eq!(0, tokenize("((ls) | a)", 0));
+        assert_eq!(0, tokenize("((ls) )", 0));
+        assert_eq!(0, tokenize("((1 + 2)", 0));
+        assert_eq!(0, tokenize("(ls)", 0));
+        assert_eq!(
+            0,
+            ArithmeticCommands::default()
+                .tokenize(&chars("((x))"), 0, &Dialect::DASH)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_arithmetic_command_remembers_parentheses() {
+        let content = chars(&format!("{}a{}", "(".repeat(100000), ") ".repeat(100000)));
+        let mut arithmetic_commands = ArithmeticCommands::default();
+        for start in 0..99999 {
+            assert_eq!(
+                start,
+                arithmetic_commands
+                    .tokenize(&content, start, &Dialect::BASH)
+                    .unwrap()
+            );
+        }
+        let mut arithmetic_commands = ArithmeticCommands::default();
+        assert_eq!(
+            0,
+            arithmetic_commands
+                .tokenize(&chars("(((1))"), 0, &Dialect::BASH)
+                .unwrap()
+        );
+        assert_eq!(
+            6,
+            arithmetic_commands
+                .tokenize(&chars("(((1))"), 1, &Dialect::BASH)
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,500 @@
+use crate::dialect::Dialect;
+use crate::lexer::metacharacter::is_metacharacter;
+use crate::lexer::nested::{tokenize_nested, MAX_DEPTH};
+use std::error::Error;
+use std::ops::Range;
+
+/// tokenize_brace_expansion
+/// > [3.5.1 Brace Expansion](https://www.gnu.org/software/bash/manual/bash.html#Brace-Expansion)
+/// > Patterns to be brace expanded take the form of an optional preamble, followed by either a series of
+/// > comma-separated strings or a sequence expression between a pair of braces, followed by an optional
+/// > postscript.
+///
+/// Only the braces and what's between them, the preamble and postscript are words of their own that join
+/// with this one like `a"b"` does. A correctly-formed brace expansion has an unquoted comma or is a sequence
+/// expression, and like any word it can't contain unquoted whitespace or metacharacters : `{a}` and
+/// `{ echo; }` (a brace group) are left to [tokenize_word](crate::lexer::word::tokenize_word).
+pub fn tokenize_brace_expansion(content: &[char], start: usize, dialect: &Dialect) -> usize {
+    if !dialect.brace_expansion || content.get(start).copied() != Some('{') {
+        return start;
+    }
+    let mut pointer = start + 1; // Skipped `{`
+    let mut depth = 0;
+    loop {
+        match content.get(pointer).copied() {
+            Some('{') => depth += 1,
+            Some('}') if depth == 0 => break,
+            Some('}') => depth -= 1,
+            Some(char) if char.is_ascii_whitespace() || is_metacharacter(char) => return start,
+            Some(_) => match tokenize_nested(content, pointer, dialect) {
+                Ok(nested_pointer) if nested_pointer != pointer => {
+                    pointer = nested_pointer;
+                    continue;
+                }
+                Ok(_) => {}
+                Err(_) => return start, // Unterminated quotes are reported by their own lexer
+            },
+            None => return start,
+        }
+        pointer += 1;
+    }
+    let text: String = content[start..=pointer].iter().collect();
+    match parse_brace_expansion(&text) {
+        Some(_) => pointer + 1,
+        None => start,
+    }
+}
+
+/// The two forms of a brace expansion
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BraceExpansion {
+    Alternatives(Vec<String>), // {a,b,c}, an alternative can hold brace expansions itself : {a,b{c,d}}
+    Sequence {
+        start: String, // Both integers or both single letters : {1..10..2}, {a..z}
+        end: String,
+        increment: Option<i64>,
+    },
+}
+
+impl BraceExpansion {
+    /// The strings this brace expansion stands for, without expanding the nested brace expansions of an
+    /// alternative. A sequence can stand for more strings than fit in memory, see [BraceExpansion::count].
+    pub fn words(&self) -> Vec<String> {
+        match self {
+            BraceExpansion::Alternatives(alternatives) => alternatives.clone(),
+            BraceExpansion::Sequence {
+                start,
+                end,
+                increment,
+            } => sequence_words(start, end, increment.unwrap_or(1)),
+        }
+    }
+
+    /// How many strings [BraceExpansion::words] returns, `None` when they don't fit in a `usize`
+    pub fn count(&self) -> Option<usize> {
+        match self {
+            BraceExpansion::Alternatives(alternatives) => Some(alternatives.len()),
+            BraceExpansion::Sequence {
+                start,
+                end,
+                increment,
+            } => sequence_count(start, end, increment.unwrap_or(1)),
+        }
+    }
+}
+
+/// parse_brace_expansion
+/// Returns the structure of a brace expansion token (including the braces), `None` when it isn't a
+/// correctly-formed one.
+pub fn parse_brace_expansion(token: &str) -> Option<BraceExpansion> {
+    let inner = token.strip_prefix('{')?.strip_suffix('}')?;
+    let alternatives = split_alternatives(inner);
+    if alternatives.len() > 1 {
+        return Some(BraceExpansion::Alternatives(alternatives));
+    }
+
+    let mut parts = inner.split("..");
+    let (start, end) = (parts.next()?, parts.next()?);
+    let increment = match parts.next() {
+        Some(increment) => Some(increment.parse::<i64>().ok()?),
+        None => None,
+    };
+    let is_integer = |part: &str| part.parse::<i64>().is_ok();
+    let is_letter =
+        |part: &str| part.len() == 1 && part.starts_with(|c: char| c.is_ascii_alphabetic());
+    let is_sequence =
+        (is_integer(start) && is_integer(end)) || (is_letter(start) && is_letter(end));
+    if parts.next().is_some() || !is_sequence {
+        return None;
+    }
+    Some(BraceExpansion::Sequence {
+        start: start.to_string(),
+        end: end.to_string(),
+        increment,
+    })
+}
+
+/// The most words [expand_braces] returns
+pub const MAX_WORDS: usize = 1_000_000;
+
+/// expand_braces
+/// The words brace expansion turns `word` into, from left to right : `a{b,c}d{1..2}` is `ab1d ab2d ...`.
+/// Quoted braces and braces of a parameter expansion (`${x}`) aren't expanded, and the quotes are kept
+/// as they are. A word without a brace expansion is returned on its own.
+///
+/// Bash expands `{1..1000000000}` to a billion words, we count them first : more than [MAX_WORDS], or brace
+/// expansions nested more than 256 levels deep, are an error at the index of the first brace expansion.
+pub fn expand_braces(word: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let expansions = find_brace_expansions(word);
+    let Some(first) = expansions.first() else {
+        return Ok(vec![word.to_string()]);
+    };
+    count_words(&expansions, 0, first.index)?;
+    Ok(expand_words(word, &expansions))
+}
+
+/// A brace expansion of a word, `index` is the character its `{` is at and `bytes` where it is in the word
+struct FoundExpansion {
+    index: usize,
+    bytes: Range<usize>,
+    expansion: BraceExpansion,
+}
+
+/// The brace expansions of `word` from left to right, without the ones nested in their alternatives
+fn find_brace_expansions(word: &str) -> Vec<FoundExpansion> {
+    let dialect = Dialect::BASH;
+    let chars: Vec<char> = word.chars().collect();
+    let mut offsets: Vec<usize> = word.char_indices().map(|(offset, _)| offset).collect();
+    offsets.push(word.len());
+    let mut expansions = Vec::new();
+    let mut pointer = 0;
+    while pointer < chars.len() {
+        let end = tokenize_brace_expansion(&chars, pointer, &dialect);
+        if end != pointer {
+            let bytes = offsets[pointer]..offsets[end];
+            let Some(expansion) = parse_brace_expansion(&word[bytes.clone()]) else {
+                break;
+            };
+            expansions.push(FoundExpansion {
+                index: pointer,
+                bytes,
+                expansion,
+            });
+            pointer = end;
+            continue;
+        }
+        pointer = match tokenize_nested(&chars, pointer, &dialect) {
+            Ok(nested_pointer) if nested_pointer > pointer => nested_pointer,
+            _ => pointer + 1,
+        };
+    }
+    expansions
+}
+
+/// How many words `expansions` make together, `depth` levels into the alternatives of the brace expansion at
+/// `index`. Errs past [MAX_WORDS] words or [MAX_DEPTH] levels.
+fn count_words(
+    expansions: &[FoundExpansion],
+    depth: usize,
+    index: usize,
+) -> Result<usize, Box<dyn Error>> {
+    if depth > MAX_DEPTH {
+        return Err(format!(
+            "Nesting deeper than {} levels at index {}",
+            MAX_DEPTH, index
+        )
+        .into());
+    }
+    let mut count: usize = 1;
+    for found in expansions {
+        let words = match &found.expansion {
+            BraceExpansion::Alternatives(alternatives) => {
+                alternatives
+                    .iter()
+                    .try_fold(0, |words: usize, alternative| {
+                        let alternative_words =
+                            count_words(&find_brace_expansions(alternative), depth + 1, index)?;
+                        Ok::<_, Box<dyn Error>>(words.saturating_add(alternative_words))
+                    })?
+            }
+            sequence => sequence.count().unwrap_or(usize::MAX),
+        };
+        count = match count.checked_mul(words) {
+            Some(count) if count <= MAX_WORDS => count,
+            _ => {
+                let message = format!(
+                    "Brace expansion to more than {} words at index {}",
+                    MAX_WORDS, index
+                );
+                return Err(message.into());
+            }
+        };
+    }
+    Ok(count)
+}
+
+/// Every combination of the words of `expansions`, with the text of `word` around them
+fn expand_words(word: &str, expansions: &[FoundExpansion]) -> Vec<String> {
+    let mut words = vec![String::new()];
+    let mut previous_end = 0;
+    for found in expansions {
+        let preamble = &word[previous_end..found.bytes.start];
+        let expanded: Vec<String> = match &found.expansion {
+            BraceExpansion::Alternatives(alternatives) => alternatives
+                .iter()
+                .flat_map(|alternative| {
+                    expand_words(alternative, &find_brace_expansions(alternative))
+                })
+                .collect(),
+            sequence => sequence.words(),
+        };
+        words = words
+            .iter()
+            .flat_map(|word| {
+                expanded
+                    .iter()
+                    .map(move |expanded| format!("{}{}{}", word, preamble, expanded))
+            })
+            .collect();
+        previous_end = found.bytes.end;
+    }
+    for expanded in &mut words {
+        expanded.push_str(&word[previous_end..]);
+    }
+    words
+}
+
+/// Splits the inside of a brace expansion at the commas that aren't nested, quoted or escaped
+fn split_alternatives(inner: &str) -> Vec<String> {
+    let dialect = Dialect::BASH;
+    let mut alternatives = vec![String::new()];
+    let mut depth = 0;
+    let mut pointer = 0;
+    let chars: Vec<char> = inner.chars().collect();
+    while let Some(char) = chars.get(pointer) {
+        match char {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                alternatives.push(String::new());
+                pointer += 1;
+                continue;
+            }
+            _ => {}
+        }
+        let next_pointer = match tokenize_nested(&chars, pointer, &dialect) {
+            Ok(nested_pointer) if nested_pointer > pointer => nested_pointer.min(chars.len()),
+            _ => pointer + 1,
+        };
+        if let Some(alternative) = alternatives.last_mut() {
+            alternative.extend(&chars[pointer..next_pointer]);
+        }
+        pointer = next_pointer;
+    }
+    alternatives
+}
+
+fn sequence_count(start: &str, end: &str, increment: i64) -> Option<usize> {
+    let distance = match (start.parse::<i64>(), end.parse::<i64>()) {
+        (Ok(first), Ok(last)) => first.abs_diff(last),
+        _ => match (start.chars().next(), end.chars().next()) {
+            (Some(first), Some(last)) => u64::from(u32::from(first).abs_diff(u32::from(last))),
+            _ => return Some(0),
+        },
+    };
+    let steps = distance / increment.unsigned_abs().max(1);
+    usize::try_from(steps).ok()?.checked_add(1)
+}
+
+/// `{01..10}` pads the integers with zeroes, the sign of the increment doesn't matter
+fn sequence_words(start: &str, end: &str, increment: i64) -> Vec<String> {
+    let step = usize::try_from(increment.unsigned_abs().max(1)).unwrap_or(usize::MAX);
+    if let (Ok(first), Ok(last)) = (start.parse::<i64>(), end.parse::<i64>()) {
+        let has_leading_zero = |part: &str| part.trim_start_matches('-').starts_with('0');
+        let width = match has_leading_zero(start) || has_leading_zero(end) {
+            true => start.len().max(end.len()),
+            false => 0,
+        };
+        let values: Vec<i64> = match first <= last {
+            true => (first..=last).step_by(step).collect(),
+            false => (last..=first).rev().step_by(step).collect(),
+        };
+        return values
+            .into_iter()
+            .map(|value| format!("{:0width$}", value, width = width))
+            .collect();
+    }
+
+    let (Some(first), Some(last)) = (start.chars().next(), end.chars().next()) else {
+        return Vec::new();
+    };
+    let letters: Vec<char> = match first <= last {
+        true => (first..=last).step_by(step).collect(),
+        false => (last..=first).rev().step_by(step).collect(),
+    };
+    letters.into_iter().map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::lexer::brace_expansion::{
+        expand_braces, parse_brace_expansion, tokenize_brace_expansion, BraceExpansion,
+    };
+    use crate::lexer::chars;
+
+    #[test]
+    fn test_tokenize_brace_expansion() {
+        assert_eq!(
+            5,
+            tokenize_brace_expansion(&chars("{a,b}"), 0, &Dialect::BASH)
+        );
+        assert_eq!(
+            6,
+            tokenize_brace_expansion(&chars("a{b,c}d"), 1, &Dialect::BASH)
+        );
+        assert_eq!(
+            6,
+            tokenize_brace_expansion(&chars("{1..3}"), 0, &Dialect::BASH)
+        );
+        assert_eq!(
+            10,
+            tokenize_brace_expansion(&chars("{1..10..2}"), 0, &Dialect::BASH)
+        );
+        assert_eq!(
+            6,
+            tokenize_brace_expansion(&chars("{a..z}"), 0, &Dialect::BASH)
+        );
+        assert_eq!(
+            11,
+            tokenize_brace_expansion(&chars("{a,b{c,d}e}"), 0, &Dialect::BASH)
+        );
+        assert_eq!(
+            9,
+            tokenize_brace_expansion(&chars("{a,'b c'}"), 0, &Dialect::BASH)
+        );
+        assert_eq!(
+            8,
+            tokenize_brace_expansion(&chars("{${x},y}z"), 0, &Dialect::BASH)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_brace_expansion_no_match() {
+        for content in [
+            "{a}",
+            "{}",
+            "{ echo; }",
+            "{a, b}",
+            "{a,b",
+            "{a..}",
+            "{1..a}",
+            "{a..b..c}",
+            "{ab..c}",
+            "{'a,b'}",
+            "{a\\,b}",
+            "{a,b|c}",
+            "x{a,b}",
+            "{a..b..1..2}",
+        ] {
+            assert_eq!(
+                0,
+                tokenize_brace_expansion(&chars(content), 0, &Dialect::BASH),
+                "{}",
+                content
+            );
+        }
+        assert_eq!(
+            0,
+            tokenize_brace_expansion(&chars("{a,b}"), 0, &Dialect::DASH)
+        );
+    }
+
+    #[test]
+    fn test_parse_brace_expansion() {
+        assert_eq!(
+            Some(BraceExpansion::Alternatives(vec![
+                String::from("a"),
+                String::from("b{c,d}"),
+                String::new()
+            ])),
+            parse_brace_expansion("{a,b{c,d},}")
+        );
+        assert_eq!(
+            Some(BraceExpansion::Sequence {
+                start: String::from("1"),
+                end: String::from("10"),
+                increment: Some(2)
+            }),
+            parse_brace_expansion("{1..10..2}")
+        );
+        assert_eq!(
+            Some(BraceExpansion::Sequence {
+                start: String::from("a"),
+                end: String::from("e"),
+                increment: None
+            }),
+            parse_brace_expansion("{a..e}")
+        );
+        assert_eq!(None, parse_brace_expansion("{a}"));
+        assert_eq!(None, parse_brace_expansion("a,b"));
+    }
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(vec!["a", "b", "c"], expand_braces("{a,b,c}").unwrap());
+        assert_eq!(
+            vec!["preAsuf", "preBsuf"],
+            expand_braces("pre{A,B}suf").unwrap()
+        );
+        assert_eq!(
+            vec!["ab", "acd", "ace"],
+            expand_braces("a{b,c{d,e}}").unwrap()
+        );
+        assert_eq!(
+            vec!["a1", "a2", "b1", "b2"],
+            expand_braces("{a,b}{1,2}").unwrap()
+        );
+        assert_eq!(vec!["x", "xa"], expand_braces("x{,a}").unwrap());
+        assert_eq!(vec!["{x}a", "{x}b"], expand_braces("{x}{a,b}").unwrap());
+        assert_eq!(vec!["{xa", "{xb"], expand_braces("{x{a,b}").unwrap());
+        assert_eq!(vec!["'{a,b}'"], expand_braces("'{a,b}'").unwrap());
+        assert_eq!(vec!["${x,y}"], expand_braces("${x,y}").unwrap());
+        assert_eq!(vec!["'a b'", "c"], expand_braces("{'a b',c}").unwrap());
+        assert_eq!(vec!["file"], expand_braces("file").unwrap());
+        assert_eq!(vec![""], expand_braces("").unwrap());
+    }
+
+    #[test]
+    fn test_expand_braces_sequences() {
+        assert_eq!(vec!["1", "2", "3"], expand_braces("{1..3}").unwrap());
+        assert_eq!(vec!["3", "2", "1"], expand_braces("{3..1}").unwrap());
+        assert_eq!(
+            vec!["1", "3", "5", "7", "9"],
+            expand_braces("{1..10..2}").unwrap()
+        );
+        assert_eq!(
+            vec!["10", "7", "4", "1"],
+            expand_braces("{10..1..-3}").unwrap()
+        );
+        assert_eq!(vec!["-1", "0", "1"], expand_braces("{-1..1}").unwrap());
+        assert_eq!(vec!["08", "09", "10"], expand_braces("{08..10}").unwrap());
+        assert_eq!(vec!["a", "b", "c"], expand_braces("{a..c}").unwrap());
+        assert_eq!(vec!["e", "c", "a"], expand_braces("{e..a..2}").unwrap());
+        assert_eq!(vec!["x1y", "x2y"], expand_braces("x{1..2}y").unwrap());
+        assert_eq!(vec!["1", "2"], expand_braces("{1..2..0}").unwrap());
+    }
+
+    #[test]
+    fn test_expand_braces_limits() {
+        assert_eq!(1_000_000, expand_braces("{1..1000000}").unwrap().len());
+        assert_eq!(
+            "Brace expansion to more than 1000000 words at index 2",
+            expand_braces("a-{0..1000000}").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Brace expansion to more than 1000000 words at index 0",
+            expand_braces("{1..9223372036854775807}")
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Brace expansion to more than 1000000 words at index 0",
+            expand_braces("{-9223372036854775808..9223372036854775807}")
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Brace expansion to more than 1000000 words at index 0",
+            expand_braces(&"{a,b}".repeat(64)).unwrap_err().to_string()
+        );
+        let nested = format!("{}z{}", "{a,".repeat(300), "}".repeat(300));
+        assert_eq!(
+            "Nesting deeper than 256 levels at index 0",
+            expand_braces(&nested).unwrap_err().to_string()
+        );
+        assert_eq!(Some(10), parse_brace_expansion("{1..10}").unwrap().count());
+        assert_eq!(Some(3), parse_brace_expansion("{e..a..2}").unwrap().count());
+    }
+}
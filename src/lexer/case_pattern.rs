@@ -0,0 +1,200 @@
+use crate::tokens::tokens::TokenKind;
+
+/// Reserved words after which a command starts, so the next word can be `case` or `esac`
+pub(crate) const COMMAND_PREFIXES: [&str; 11] = [
+    "if", "then", "else", "elif", "do", "while", "until", "{", "}", "!", "time",
+];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum CaseState {
+    Subject,                    // After `case`, the word that's matched
+    In,                         // Waiting for `in`
+    Patterns { started: bool }, // The patterns of an item, `started` once a `(` or a pattern was read
+    Body,                       // The commands of an item, up to `;;`, `;&`, `;;&` or `esac`
+}
+
+/// CasePatterns
+/// > [3.2.5.2 Conditional Constructs](https://www.gnu.org/software/bash/manual/bash.html#Conditional-Constructs)
+/// > case word in [ [(] pattern [| pattern]…) command-list ;;]… esac
+/// > [...] The ‘|’ is used to separate multiple patterns, and the ‘)’ operator terminates a pattern list.
+///
+/// Follows the tokens of a script to tell the `(`, `|` and `)` around case patterns from parentheses and
+/// pipes, which are the same metacharacters. Nested `case` commands are kept on a stack and `case` and `esac`
+/// are only reserved words where a command can start, `echo case` is an argument. Between `[[` and `]]`
+/// parentheses group expressions, no command starts there.
+#[derive(Debug, Clone)]
+pub struct CasePatterns {
+    cases: Vec<CaseState>,
+    word: Option<String>, // The word being read, as long as tokens are adjacent
+    command_start: bool,
+    after_for: bool, // The previous word is a `for` where a command starts, `for ((...))`
+    conditional: bool, // Between `[[` and `]]`
+}
+
+impl Default for CasePatterns {
+    fn default() -> Self {
+        CasePatterns {
+            cases: Vec::new(),
+            word: None,
+            command_start: true,
+            after_for: false,
+            conditional: false,
+        }
+    }
+}
+
+impl CasePatterns {
+    /// The kind of the next token, `kind` as it was tokenized or one of the case pattern kinds
+    pub fn next(&mut self, kind: TokenKind, text: &str) -> TokenKind {
+        if kind.is_word() {
+            self.word.get_or_insert_with(String::new).push_str(text);
+            return kind;
+        }
+        if let Some(word) = self.word.take() {
+            self.end_word(&word);
+        }
+        let kind = match (self.cases.last_mut(), kind) {
+            (_, TokenKind::WhiteSpace | TokenKind::Comment) => return kind,
+            (Some(CaseState::Patterns { started }), TokenKind::LParenthesis) if !*started => {
+                *started = true;
+                TokenKind::CasePatternStart
+            }
+            (Some(CaseState::Patterns { .. }), TokenKind::Pipe) => TokenKind::CasePatternSeparator,
+            (Some(state @ CaseState::Patterns { .. }), TokenKind::RParenthesis) => {
+                *state = CaseState::Body;
+                TokenKind::CasePatternEnd
+            }
+            (
+                Some(state @ CaseState::Body),
+                TokenKind::DoubleSemicolon
+                | TokenKind::SemicolonAmpersand
+                | TokenKind::DoubleSemicolonAmpersand,
+            ) => {
+                *state = CaseState::Patterns { started: false };
+                kind
+            }
+            _ => kind,
+        };
+        // Redirections are followed by their target, any other operator by a command :
+        self.command_start =
+            !(self.conditional || kind.is_redirection() || kind == TokenKind::HereDocument);
+        self.after_for = false;
+        kind
+    }
+
+    /// Whether a `((` at the next token is an arithmetic command : where a command starts, but not where a
+    /// case pattern does, and after `for`
+    pub fn arithmetic_command_start(&self) -> bool {
+        match (&self.word, self.cases.last()) {
+            (Some(word), _) => self.command_start && word == "for",
+            (None, Some(CaseState::Patterns { .. })) => false,
+            (None, _) => self.command_start || self.after_for,
+        }
+    }
+
+    fn end_word(&mut self, word: &str) {
+        let command_start = std::mem::replace(&mut self.command_start, false);
+        self.after_for = command_start && word == "for";
+        if self.conditional {
+            self.conditional = word != "]]";
+            return;
+        }
+        match self.cases.last_mut() {
+            Some(state @ CaseState::Subject) => *state = CaseState::In,
+            Some(state @ CaseState::In) if word == "in" => {
+                *state = CaseState::Patterns { started: false }
+            }
+            Some(CaseState::Patterns { started: false }) if word == "esac" => {
+                self.cases.pop();
+            }
+            Some(CaseState::Patterns { started }) => *started = true,
+            Some(CaseState::Body) if command_start && word == "esac" => {
+                self.cases.pop();
+            }
+            _ if command_start && word == "case" => self.cases.push(CaseState::Subject),
+            _ if command_start && word == "[[" => self.conditional = true,
+            _ => self.command_start = command_start && COMMAND_PREFIXES.contains(&word),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenize;
+    use crate::tokens::tokens::TokenKind;
+
+    /// The kinds of the `(`, `|` and `)` tokens of `script`
+    fn delimiters(script: &str) -> Vec<TokenKind> {
+        tokenize(script.to_string())
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .filter(|kind| {
+                matches!(
+                    kind,
+                    TokenKind::LParenthesis
+                        | TokenKind::RParenthesis
+                        | TokenKind::Pipe
+                        | TokenKind::CasePatternStart
+                        | TokenKind::CasePatternSeparator
+                        | TokenKind::CasePatternEnd
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_case_patterns() {
+        use TokenKind::*;
+        assert_eq!(
+            vec![
+                CasePatternSeparator,
+                CasePatternEnd,
+                Pipe,
+                CasePatternStart,
+                CasePatternEnd,
+                LParenthesis,
+                RParenthesis,
+            ],
+            delimiters("case $1 in a|b) ls | wc;; (c) (pwd);; esac")
+        );
+        assert_eq!(
+            vec![CasePatternEnd, CasePatternEnd, CasePatternEnd],
+            delimiters("case x in\n  a) echo a;&\n  b) echo b;;&\n  *) echo c\nesac")
+        );
+        // `esac` as a pattern and as an argument :
+        assert_eq!(
+            vec![CasePatternStart, CasePatternEnd, LParenthesis, RParenthesis],
+            delimiters("case x in (esac) echo esac;; esac; (ls)")
+        );
+    }
+
+    #[test]
+    fn test_case_patterns_nested() {
+        use TokenKind::*;
+        assert_eq!(
+            vec![
+                CasePatternEnd,
+                LParenthesis,
+                CasePatternEnd,
+                RParenthesis,
+                CasePatternEnd,
+                Pipe,
+            ],
+            delimiters("case a in a) (case b in b) ;; esac) ;; *) ;; esac | cat")
+        );
+        // Not reserved words here :
+        assert_eq!(
+            vec![Pipe, RParenthesis],
+            delimiters("echo case x in a | b)")
+        );
+        assert_eq!(
+            vec![LParenthesis, CasePatternEnd, RParenthesis],
+            delimiters("if (case x in a) esac) then :; fi")
+        );
+        assert_eq!(
+            vec![LParenthesis, RParenthesis, LParenthesis, RParenthesis],
+            delimiters("[[ ( case ) == x ]] && (ls)")
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use crate::dialect::Dialect;
+use crate::lexer::nested::tokenize_list;
+
+/// tokenize_command_substitution
+/// > [3.5.4 Command Substitution](https://www.gnu.org/software/bash/manual/bash.html#Command-Substitution)
+/// > Command substitution allows the output of a command to replace the command itself.
+/// > The standard form of command substitution occurs when a command is enclosed as follows: `$(command)`
+/// > or (deprecated) `` `command` ``.
+///
+/// For `$(command)` we look for the closing parenthesis, see [tokenize_list].
+pub fn tokenize_command_substitution(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut prefix = content.iter().skip(start).copied();
+    match (prefix.next(), prefix.next()) {
+        (Some('`'), _) => tokenize_backticks(content, start),
+        (Some('$'), Some('(')) => match tokenize_list(content, start + 2, dialect)? {
+            Some(end) => Ok(end + 1),
+            None => Err(format!("Unterminated command substitution at index {}", start).into()),
+        },
+        _ => Ok(start),
+    }
+}
+
+/// In the old-style backquote form of substitution, backslash retains its literal meaning except when
+/// followed by ‘$’, ‘`’, or ‘\’. Skipping the character after a backslash covers all of these.
+fn tokenize_backticks(content: &[char], start: usize) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut pointer = start + 1; // Skipped the opening backtick
+    loop {
+        match content.get(pointer).copied() {
+            Some('`') => return Ok(pointer + 1),
+            Some('\\') => pointer += 2,
+            Some(_) => pointer += 1,
+            None => {
+                return Err(format!("Unterminated command substitution at index {}", start).into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::lexer::chars;
+    use crate::lexer::command_substitution::tokenize_command_substitution;
+
+    #[test]
+    pub fn test_tokenize_command_substitution_basic() {
+        assert_eq!(
+            0,
+            tokenize_command_substitution(&chars("echo"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_command_substitution(&chars("$HOME"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_command_substitution(&chars("(ls)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            5,
+            tokenize_command_substitution(&chars("$(ls)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            13,
+            tokenize_command_substitution(&chars("echo $(ls -l) ok"), 5, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            3,
+            tokenize_command_substitution(&chars("$()"), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_command_substitution_nested() {
+        assert_eq!(
+            15,
+            tokenize_command_substitution(&chars("$(echo $(date))"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            13,
+            tokenize_command_substitution(&chars("$(echo ')' x)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            13,
+            tokenize_command_substitution(&chars("$(echo \")\" x)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            10,
+            tokenize_command_substitution(&chars("$(echo \\))"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            12,
+            tokenize_command_substitution(&chars("$( (ls) | a)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            11,
+            tokenize_command_substitution(&chars("$((ls) | a)"), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_command_substitution_case() {
+        let content = "$(case $x in a|b) echo a;; (c) (ls);; esac) ok";
+        assert_eq!(
+            43,
+            tokenize_command_substitution(&chars(content), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            12,
+            tokenize_command_substitution(&chars("$(echo # )\n)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            14,
+            tokenize_command_substitution(&chars("$(echo case a))"), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_backticks() {
+        assert_eq!(
+            6,
+            tokenize_command_substitution(&chars("`date`"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            11,
+            tokenize_command_substitution(&chars("echo `date` ok"), 5, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            8,
+            tokenize_command_substitution(&chars("`a \\` b`"), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_command_substitution_unterminated() {
+        assert_eq!(
+            "Unterminated command substitution at index 5",
+            tokenize_command_substitution(&chars("echo $(ls"), 5, &Dialect::BASH)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Unterminated command substitution at index 0",
+            tokenize_command_substitution(&chars("$(echo ')'"), 0, &Dialect::BASH)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Unterminated command substitution at index 0",
+            tokenize_command_substitution(&chars("`date"), 0, &Dialect::BASH)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use crate::tokens::tokens::TokenKind;
+
+/// tokenize_comment
+/// > [3.1.3 Comments](https://www.gnu.org/software/bash/manual/bash.html#Comments)
+/// > A word beginning with ‘#’ causes that word and all remaining characters on that line to be ignored.
+///
+/// The newline itself is not part of the comment. `previous` is the kind of the token before `start`, `None` at
+/// the start of the input : a `#` right after (part of) a word (`a#b`, `$(ls)#x`) doesn't start one, it only
+/// does after whitespace or an operator.
+pub fn tokenize_comment(content: &[char], start: usize, previous: Option<TokenKind>) -> usize {
+    if content.get(start) != Some(&'#') || previous.is_some_and(|kind| kind.is_word()) {
+        return start;
+    }
+    start
+        + content
+            .iter()
+            .skip(start)
+            .take_while(|char| **char != '\n')
+            .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::chars;
+    use crate::lexer::comment::tokenize_comment;
+    use crate::tokens::tokens::TokenKind;
+
+    #[test]
+    pub fn test_tokenize_comment_basic() {
+        assert_eq!(0, tokenize_comment(&chars("echo"), 0, None));
+        assert_eq!(1, tokenize_comment(&chars("#"), 0, None));
+        assert_eq!(9, tokenize_comment(&chars("# comment"), 0, None));
+        assert_eq!(9, tokenize_comment(&chars("# comment\necho"), 0, None));
+        assert_eq!(
+            14,
+            tokenize_comment(&chars("echo # comment"), 5, Some(TokenKind::WhiteSpace))
+        );
+        assert_eq!(
+            7,
+            tokenize_comment(&chars("echo;#x"), 5, Some(TokenKind::Semicolon))
+        );
+        assert_eq!(7, tokenize_comment(&chars("# ü 'a\"\nb"), 0, None));
+    }
+
+    #[test]
+    pub fn test_tokenize_comment_inside_word() {
+        assert_eq!(1, tokenize_comment(&chars("a#b"), 1, Some(TokenKind::Word)));
+        assert_eq!(
+            3,
+            tokenize_comment(&chars("'a'#b"), 3, Some(TokenKind::SingleQuote))
+        );
+        assert_eq!(5, tokenize_comment(&chars("echo"), 5, None));
+        assert_eq!(
+            5,
+            tokenize_comment(&chars("$(ls)#x"), 5, Some(TokenKind::CommandSubstitution))
+        );
+        assert_eq!(
+            4,
+            tokenize_comment(&chars("${x}#y"), 4, Some(TokenKind::ParameterExpansion))
+        );
+    }
+}
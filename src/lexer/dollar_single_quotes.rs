@@ -1,46 +1,45 @@
+use crate::dialect::{BashVersion, Dialect};
 macro_rules! increment_pointer {
     ($pointer:expr, $content:expr, $start:expr, $char:expr) => {{
         $pointer += 1;
-        if $pointer >= $content.len() {
-            return Err(format!("Unterminated dollar-single-quote at index {}", $start).into());
-        }
-        $char = $content.chars().nth($pointer).unwrap();
+        $char = match $content.get($pointer).copied() {
+            Some(char) => char,
+            None => {
+                return Err(format!("Unterminated dollar-single-quote at index {}", $start).into())
+            }
+        };
     }};
 }
 macro_rules! decrement_pointer {
-    ($pointer:expr, $content:expr, $char:expr) => {{
-        $pointer -= 1;
-        $char = $content.chars().nth($pointer).unwrap();
+    ($pointer:expr) => {{
+        $pointer = $pointer.saturating_sub(1);
     }};
 }
 
 /// tokenize_dollar_single_quotes
 /// > [3.1.2.4 ANSI-C Quoting](https://www.gnu.org/software/bash/manual/bash.html#ANSI_002dC-Quoting)
 /// > Character sequences of the form `$’string’` are treated as a special kind of single quotes
+///
 /// It's basically the same as single_quotes.rs except we need to validate backslashes.
+/// Shells without ANSI-C quoting (`dialect.dollar_single_quotes`) read `$'` as a literal `$` and a single quote.
 pub fn tokenize_dollar_single_quotes(
-    content: &String,
+    content: &[char],
     start: usize,
+    dialect: &Dialect,
 ) -> Result<usize, Box<dyn std::error::Error>> {
-    let mut pointer = start;
-    let mut char = content.chars().nth(pointer).unwrap();
-
-    // Validation of `$'` start :
-    if char != '$' || !(pointer + 1 < content.len()) {
-        // Check it's a dollar single quoted string and that we can fetch the following character.
-        return Ok(start);
-    }
-    pointer += 1; // Skipped `$`
-    char = content.chars().nth(pointer).unwrap();
-    if char != '\'' {
-        // A dollar-single-quote always starts with `$'`
+    // Validation of `$'` start, a dollar-single-quote always starts with `$'` :
+    let mut prefix = content.iter().skip(start).copied();
+    if !dialect.dollar_single_quotes || prefix.next() != Some('$') || prefix.next() != Some('\'') {
         return Ok(start);
     }
+    let mut pointer = start + 1; // Skipped `$`
+    let mut char;
 
-    while char != '\'' || pointer == start + 1 {
-        // Add 1 for the offset of `$`
+    loop {
         increment_pointer!(pointer, content, start, char);
-        char = content.chars().nth(pointer).unwrap();
+        if char == '\'' {
+            break;
+        }
 
         // Check for backslashes :
         if char == '\\' {
@@ -49,10 +48,7 @@ pub fn tokenize_dollar_single_quotes(
                 continue; // Specified in IEEE 1003.1-2024
             }
             if char == '\'' {
-                // We want to skip over the character, but the pointer should remain intact :
-                increment_pointer!(pointer, content, start, char);
-                pointer -= 1;
-                continue;
+                continue; // An escaped single quote doesn't end the string
             }
 
             // \cX yields the control character :
@@ -89,7 +85,7 @@ pub fn tokenize_dollar_single_quotes(
                 }
                 increment_pointer!(pointer, content, start, char);
                 if !char.is_ascii_hexdigit() {
-                    decrement_pointer!(pointer, content, char); // Reset pointer here.
+                    decrement_pointer!(pointer); // Reset pointer here.
                 }
                 continue;
             }
@@ -102,11 +98,36 @@ pub fn tokenize_dollar_single_quotes(
             }
             if octal_counter != 0 {
                 // This means it matched \ddd, so we can safely continue :
-                decrement_pointer!(pointer, content, char); // Go back one character (pointer should end on octal)
+                decrement_pointer!(pointer); // Go back one character (pointer should end on octal)
+                continue;
+            }
+
+            // \uHHHH and \UHHHHHHHH yield the Unicode character (one to four or eight hexadecimal digits) :
+            if char == 'u' || char == 'U' {
+                let escape = format!("\\{}", char);
+                dialect.require_bash(BashVersion::new(4, 2), &escape, pointer - 1)?;
+                let max_digits = if char == 'u' { 4 } else { 8 };
+                increment_pointer!(pointer, content, start, char);
+                if !char.is_ascii_hexdigit() {
+                    return Err(format!(
+                        "{} not followed by a hexadecimal character at index {}",
+                        escape, pointer
+                    )
+                    .into());
+                }
+                let mut digits = 1;
+                while digits < max_digits {
+                    increment_pointer!(pointer, content, start, char);
+                    if !char.is_ascii_hexdigit() {
+                        decrement_pointer!(pointer); // Pointer ends on the last digit
+                        break;
+                    }
+                    digits += 1;
+                }
                 continue;
             }
 
-            // Bash-specific : \E, \?, \uHHHH, \UHHHHHHHH currently not supported.
+            // Bash-specific : \E, \? currently not supported.
 
             // Backslash matched nothing :
             return Err(format!(r"\ followed by an invalid character at index {}", pointer).into());
@@ -116,19 +137,112 @@ pub fn tokenize_dollar_single_quotes(
     Ok(pointer + 1) // We always point to the character that is excluded
 }
 
+/// decode_dollar_single_quotes
+/// Returns the bytes a dollar-single-quoted string (including the `$'` and `'`) stands for, the escapes are
+/// the ones accepted by [tokenize_dollar_single_quotes]. `\xHH` and `\ddd` can produce bytes that aren't
+/// valid UTF-8. Like bash, a NUL byte ends the string.
+pub fn decode_dollar_single_quotes(token: &str) -> Vec<u8> {
+    let inner = token
+        .strip_prefix("$'")
+        .and_then(|inner| inner.strip_suffix('\''))
+        .unwrap_or(token);
+    let mut chars = inner.chars().peekable();
+    let mut bytes: Vec<u8> = Vec::new();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            bytes.extend_from_slice(char.encode_utf8(&mut [0; 4]).as_bytes());
+            continue;
+        }
+        let byte = match chars.next() {
+            Some('a') => 0x07,
+            Some('b') => 0x08,
+            Some('e') => 0x1b,
+            Some('f') => 0x0c,
+            Some('n') => b'\n',
+            Some('r') => b'\r',
+            Some('t') => b'\t',
+            Some('v') => 0x0b,
+            Some(char @ ('\\' | '\'' | '"')) => char as u8,
+            Some('c') => match chars.next() {
+                Some('?') => 0x7f,
+                Some('\\') => {
+                    chars.next(); // \c\\ has a second backslash
+                    0x1c
+                }
+                Some(char) => (char.to_ascii_uppercase() as u8) & 0x1f,
+                None => break,
+            },
+            Some('x') => {
+                let mut value = 0;
+                for _ in 0..2 {
+                    match chars.peek().and_then(|char| char.to_digit(16)) {
+                        Some(digit) => value = value * 16 + digit,
+                        None => break,
+                    }
+                    chars.next();
+                }
+                value as u8
+            }
+            Some(escape @ ('u' | 'U')) => {
+                let max_digits = if escape == 'u' { 4 } else { 8 };
+                let mut value = 0;
+                for _ in 0..max_digits {
+                    match chars.peek().and_then(|char| char.to_digit(16)) {
+                        Some(digit) => value = value * 16 + digit,
+                        None => break,
+                    }
+                    chars.next();
+                }
+                match char::from_u32(value) {
+                    Some('\0') => break,
+                    Some(char) => bytes.extend_from_slice(char.encode_utf8(&mut [0; 4]).as_bytes()),
+                    None => {} // Not a Unicode scalar value, e.g. a surrogate
+                }
+                continue;
+            }
+            Some(char) if is_ascii_octal(char) => {
+                let mut value = char.to_digit(8).unwrap_or(0);
+                for _ in 0..2 {
+                    match chars.peek().filter(|char| is_ascii_octal(**char)) {
+                        Some(char) => value = value * 8 + char.to_digit(8).unwrap_or(0),
+                        None => break,
+                    }
+                    chars.next();
+                }
+                (value & 0xff) as u8
+            }
+            Some(char) => {
+                // Not a valid escape, keep the backslash :
+                bytes.push(b'\\');
+                bytes.extend_from_slice(char.encode_utf8(&mut [0; 4]).as_bytes());
+                continue;
+            }
+            None => b'\\',
+        };
+        if byte == 0 {
+            break;
+        }
+        bytes.push(byte);
+    }
+    bytes
+}
+
 fn is_ascii_octal(c: char) -> bool {
-    c >= '0' && c <= '7'
+    ('0'..='7').contains(&c)
 }
 
 #[cfg(test)]
 mod tests {
-
-    use crate::lexer::dollar_single_quotes::tokenize_dollar_single_quotes;
+    use crate::dialect::{BashVersion, Dialect};
+    use crate::lexer::chars;
+    use crate::lexer::dollar_single_quotes::{
+        decode_dollar_single_quotes, tokenize_dollar_single_quotes,
+    };
     macro_rules! test {
         ($string:expr, $start:expr, $end:expr) => {{
             assert_eq!(
                 $end,
-                tokenize_dollar_single_quotes(&String::from($string), $start).unwrap()
+                tokenize_dollar_single_quotes(&chars($string), $start, &Dialect::BASH).unwrap()
             );
         }};
     }
@@ -136,7 +250,7 @@ mod tests {
         ($string:expr, $start:expr, $throws:expr) => {{
             assert_eq!(
                 $throws,
-                tokenize_dollar_single_quotes(&String::from($string), $start)
+                tokenize_dollar_single_quotes(&chars($string), $start, &Dialect::BASH)
                     .unwrap_err()
                     .to_string()
             );
@@ -158,6 +272,9 @@ mod tests {
 
         test!(r"$'Hello 'World'", 0, 9);
         test!(r"$'Hello \' World'", 0, 17);
+        test!(r"$'\''", 0, 5);
+        test!(r"$'a\'\''", 0, 8);
+        test!(r"$'\\'", 0, 5);
         test!("$'Hello \\\" World'", 0, 17);
         test_throws!(
             r"$'Hello World \'\c0'",
@@ -353,5 +470,125 @@ mod tests {
             3,
             "Unterminated dollar-single-quote at index 3"
         );
+        test_throws!(r"$'\", 0, "Unterminated dollar-single-quote at index 0");
+        test_throws!(r"$'\c", 0, "Unterminated dollar-single-quote at index 0");
+        test_throws!(r"$'\x", 0, "Unterminated dollar-single-quote at index 0");
+        test_throws!(r"$'\x1", 0, "Unterminated dollar-single-quote at index 0");
+        test_throws!(r"$'\0", 0, "Unterminated dollar-single-quote at index 0");
+        test_throws!(r"$'\'", 0, "Unterminated dollar-single-quote at index 0");
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        test!(" ", 1, 1);
+        test!("$", 0, 0);
+        test!("$'", 5, 5);
+        test!("é$'ü'", 1, 5); // Multi-byte characters
+        test_throws!("é$'ü", 1, "Unterminated dollar-single-quote at index 1");
+    }
+
+    #[test]
+    fn test_dialect_without_dollar_single_quotes() {
+        assert_eq!(
+            0,
+            tokenize_dollar_single_quotes(&chars("$'a'"), 0, &Dialect::DASH).unwrap()
+        );
+        assert_eq!(
+            4,
+            tokenize_dollar_single_quotes(&chars("$'a'"), 0, &Dialect::POSIX).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unicode() {
+        test!(r"$'\u00e9'", 0, 9);
+        test!(r"$'\u20AC and \U0001F600'", 0, 24);
+        test!(r"$'\u7 \U1'", 0, 10);
+        test!(r"$'\u12345'", 0, 10); // At most four digits, `5` is a regular character
+        test_throws!(
+            r"$'\uG'",
+            0,
+            r"\u not followed by a hexadecimal character at index 4"
+        );
+        test_throws!(
+            r"$'\U'",
+            0,
+            r"\U not followed by a hexadecimal character at index 4"
+        );
+
+        let bash_4_1 = Dialect {
+            bash_version: Some(BashVersion::new(4, 1)),
+            ..Dialect::BASH
+        };
+        assert_eq!(
+            r"`\u` requires bash 4.2 (targeting 4.1) at index 4",
+            tokenize_dollar_single_quotes(&chars(r"$'a \u00e9'"), 0, &bash_4_1)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            8,
+            tokenize_dollar_single_quotes(&chars(r"$'\n\\u'"), 0, &bash_4_1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            b"Hello World".to_vec(),
+            decode_dollar_single_quotes(r"$'Hello World'")
+        );
+        assert_eq!(b"".to_vec(), decode_dollar_single_quotes(r"$''"));
+        assert_eq!(
+            b"a\nb\tc".to_vec(),
+            decode_dollar_single_quotes(r"$'a\nb\tc'")
+        );
+        assert_eq!(
+            b"\x07\x08\x1b\x0c\r\x0b".to_vec(),
+            decode_dollar_single_quotes(r"$'\a\b\e\f\r\v'")
+        );
+        assert_eq!(
+            b"it's \"\\".to_vec(),
+            decode_dollar_single_quotes(r#"$'it\'s \"\\'"#)
+        );
+        assert_eq!(
+            "héllo".as_bytes().to_vec(),
+            decode_dollar_single_quotes("$'héllo'")
+        );
+    }
+
+    #[test]
+    fn test_decode_control_char() {
+        assert_eq!(vec![0x01, 0x01], decode_dollar_single_quotes(r"$'\ca\cA'"));
+        assert_eq!(vec![0x1b, 0x1d], decode_dollar_single_quotes(r"$'\c[\c]'"));
+        assert_eq!(vec![0x7f, 0x1f], decode_dollar_single_quotes(r"$'\c?\c_'"));
+        assert_eq!(vec![0x1c, b'x'], decode_dollar_single_quotes(r"$'\c\\x'"));
+    }
+
+    #[test]
+    fn test_decode_unicode() {
+        assert_eq!(
+            "\u{e9}\u{20ac}".as_bytes().to_vec(),
+            decode_dollar_single_quotes(r"$'\u00e9\u20AC'")
+        );
+        assert_eq!(
+            "\u{1f600}!".as_bytes().to_vec(),
+            decode_dollar_single_quotes(r"$'\U1F600!'")
+        );
+        assert_eq!(b"u".to_vec(), decode_dollar_single_quotes(r"$'\u75'"));
+        assert_eq!(b"ab".to_vec(), decode_dollar_single_quotes(r"$'a\uD800b'")); // Surrogates are dropped
+        assert_eq!(b"a".to_vec(), decode_dollar_single_quotes(r"$'a\u0000b'"));
+    }
+
+    #[test]
+    fn test_decode_hexadecimal_and_octal() {
+        assert_eq!(vec![0xff, b'G'], decode_dollar_single_quotes(r"$'\xFFG'"));
+        assert_eq!(vec![0x07, b' '], decode_dollar_single_quotes(r"$'\x7 '"));
+        assert_eq!(vec![0x12, b'3'], decode_dollar_single_quotes(r"$'\x123'"));
+        assert_eq!(vec![0o123, b'4'], decode_dollar_single_quotes(r"$'\1234'"));
+        assert_eq!(vec![0o7, b'8'], decode_dollar_single_quotes(r"$'\78'"));
+        assert_eq!(vec![0xff], decode_dollar_single_quotes(r"$'\777'"));
+        assert_eq!(b"a".to_vec(), decode_dollar_single_quotes(r"$'a\0b'")); // NUL ends the string
+        assert_eq!(b"a".to_vec(), decode_dollar_single_quotes(r"$'a\x00b'"));
     }
 }
@@ -0,0 +1,201 @@
+use crate::dialect::Dialect;
+use crate::lexer::history_expansion::tokenize_history_expansion;
+use crate::lexer::nested::tokenize_expansion;
+
+/// tokenize_double_quotes
+/// > [3.1.2.3 Double Quotes](https://www.gnu.org/software/bash/manual/bash.html#Double-Quotes)
+/// > Enclosing characters in double quotes (‘"’) preserves the literal value of all characters within the quotes,
+/// > with the exception of ‘$’, ‘`’, ‘\’, and, when history expansion is enabled, ‘!’.
+///
+/// Expansions can contain double quotes of their own (`"$(echo "hi")"`), so we skip over them as a whole. So
+/// are history expansions with `dialect.history_expansion`, which bash replaces before it reads the quotes :
+/// `"!!:s/a/"/"` is a single string.
+pub fn tokenize_double_quotes(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    if content.get(start).copied() != Some('"') {
+        return Ok(start);
+    }
+    let mut pointer: usize = start + 1;
+    loop {
+        match content.get(pointer).copied() {
+            Some('"') => break,
+            Some('\\') => pointer += 2, // The backslash always covers the next character
+            Some('!') => {
+                let history_pointer = tokenize_history_expansion(content, pointer, dialect);
+                pointer = history_pointer.max(pointer + 1);
+            }
+            Some(_) => {
+                let expansion_pointer = tokenize_expansion(content, pointer, dialect)?;
+                pointer = if expansion_pointer != pointer {
+                    expansion_pointer
+                } else {
+                    pointer + 1
+                };
+            }
+            None => {
+                return Err(format!("Unterminated double-quote at index {}", start).into());
+            }
+        }
+    }
+    Ok(pointer + 1) // We always point to the character that is excluded
+}
+
+/// tokenize_dollar_double_quotes
+/// > [3.1.2.5 Locale-Specific Translation](https://www.gnu.org/software/bash/manual/bash.html#Locale-Translation)
+/// > Prefixing a double-quoted string with a dollar sign (‘$’), such as `$"hello, world"`, will cause the string to
+/// > be translated according to the current locale.
+///
+/// Apart from the `$` these are double quotes, shells without them (`dialect.dollar_double_quotes`) read a
+/// literal `$` followed by a double-quoted string.
+pub fn tokenize_dollar_double_quotes(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut prefix = content.iter().skip(start).copied();
+    if !dialect.dollar_double_quotes || prefix.next() != Some('$') || prefix.next() != Some('"') {
+        return Ok(start);
+    }
+    tokenize_double_quotes(content, start + 1, dialect)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::lexer::chars;
+    use crate::lexer::double_quotes::{tokenize_dollar_double_quotes, tokenize_double_quotes};
+
+    #[test]
+    pub fn test_tokenize_double_quotes_basic() {
+        assert_eq!(
+            0,
+            tokenize_double_quotes(&chars("NoQuotes"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            3,
+            tokenize_double_quotes(&chars("NoQuotes"), 3, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            2,
+            tokenize_double_quotes(&chars(r#""""#), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            13,
+            tokenize_double_quotes(&chars(r#""Hello World""#), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            18,
+            tokenize_double_quotes(&chars(r#"echo "Hello World" ok"#), 5, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            6,
+            tokenize_double_quotes(&chars(r#""it's""#), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_double_quotes_escapes() {
+        assert_eq!(
+            6,
+            tokenize_double_quotes(&chars(r#""a\"b""#), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            6,
+            tokenize_double_quotes(&chars(r#""a\\b""#), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            4,
+            tokenize_double_quotes(&chars(r#""\\""#), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_double_quotes_expansions() {
+        assert_eq!(
+            15,
+            tokenize_double_quotes(&chars(r#""$(echo "hi")!""#), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            13,
+            tokenize_double_quotes(&chars(r#""`echo "hi"`""#), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            12,
+            tokenize_double_quotes(&chars(r#""${x:-"a"}b""#), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            7,
+            tokenize_double_quotes(&chars(r#""$HOME""#), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            3,
+            tokenize_double_quotes(&chars(r#""$""#), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_double_quotes_history_expansions() {
+        let interactive = Dialect {
+            history_expansion: true,
+            ..Dialect::BASH
+        };
+        assert_eq!(
+            4,
+            tokenize_double_quotes(&chars(r#""!!""#), 0, &interactive).unwrap()
+        );
+        assert_eq!(
+            11,
+            tokenize_double_quotes(&chars(r#""!!:s/"/x/" y"#), 0, &interactive).unwrap()
+        );
+        assert_eq!(
+            7,
+            tokenize_double_quotes(&chars(r#""!!:s/"/x/" y"#), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            4,
+            tokenize_double_quotes(&chars(r#""a!" b"#), 0, &interactive).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_double_quotes_unterminated() {
+        assert_eq!(
+            "Unterminated double-quote at index 5",
+            tokenize_double_quotes(&chars(r#"echo "Hello ; world"#), 5, &Dialect::BASH)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Unterminated double-quote at index 0",
+            tokenize_double_quotes(&chars(r#""abc\"#), 0, &Dialect::BASH)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Unterminated double-quote at index 8",
+            tokenize_double_quotes(&chars(r#""$(echo ""#), 0, &Dialect::BASH)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_dollar_double_quotes() {
+        assert_eq!(
+            8,
+            tokenize_dollar_double_quotes(&chars("$\"hello\" x"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_dollar_double_quotes(&chars("\"hello\""), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_dollar_double_quotes(&chars("$\"hello\""), 0, &Dialect::DASH).unwrap()
+        );
+        assert!(tokenize_dollar_double_quotes(&chars("$\"a"), 0, &Dialect::BASH).is_err());
+    }
+}
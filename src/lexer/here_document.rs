@@ -0,0 +1,137 @@
+/// tokenize_here_document
+/// > [3.6.6 Here Documents](https://www.gnu.org/software/bash/manual/bash.html#Here-Documents)
+/// > This type of redirection instructs the shell to read input from the current source until a line containing
+/// > only word (with no trailing blanks) is seen. All of the lines read up to that point are then used as the
+/// > standard input for a command.
+/// > If the redirection operator is ‘<<-’, then all leading tab characters are stripped from input lines and the
+/// > line containing delimiter.
+///
+/// `start` is the beginning of the line after the redirection, the body ends with the line containing the delimiter
+/// (its newline excluded). Like bash we accept a here-document that is delimited by the end of the input.
+pub fn tokenize_here_document(
+    content: &[char],
+    start: usize,
+    delimiter: &str,
+    strip_tabs: bool,
+) -> usize {
+    let mut pointer = start;
+    let mut line = String::new();
+    for char in content.iter().skip(start).copied() {
+        if char == '\n' {
+            if is_delimiter(&line, delimiter, strip_tabs) {
+                return pointer;
+            }
+            line.clear();
+        } else {
+            line.push(char);
+        }
+        pointer += 1;
+    }
+    pointer
+}
+
+fn is_delimiter(line: &str, delimiter: &str, strip_tabs: bool) -> bool {
+    match strip_tabs {
+        true => line.trim_start_matches('\t') == delimiter,
+        false => line == delimiter,
+    }
+}
+
+/// here_document_delimiter
+/// > No parameter and variable expansion, command substitution, arithmetic expansion, or filename expansion is
+/// > performed on word. If any part of word is quoted, the delimiter is the result of quote removal on word,
+/// > and the lines in the here-document are not expanded.
+///
+/// Returns the delimiter and whether any part of the word was quoted.
+pub fn here_document_delimiter(word: &str) -> (String, bool) {
+    let mut delimiter = String::new();
+    let mut quoted = false;
+    let mut chars = word.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '\'' | '"' => quoted = true,
+            '$' if matches!(chars.peek(), Some('\'' | '"')) => {} // `$'EOF'` and `$"EOF"`
+            '\\' => {
+                quoted = true;
+                if let Some(escaped) = chars.next() {
+                    delimiter.push(escaped);
+                }
+            }
+            char => delimiter.push(char),
+        }
+    }
+    (delimiter, quoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::chars;
+    use crate::lexer::here_document::{here_document_delimiter, tokenize_here_document};
+
+    #[test]
+    fn test_tokenize_here_document_basic() {
+        assert_eq!(3, tokenize_here_document(&chars("EOF"), 0, "EOF", false));
+        assert_eq!(
+            6,
+            tokenize_here_document(&chars("hi\nEOF\necho"), 0, "EOF", false)
+        );
+        assert_eq!(
+            15,
+            tokenize_here_document(&chars("a\n EOF\nEOF \nEOF\n"), 0, "EOF", false)
+        );
+        assert_eq!(
+            16,
+            tokenize_here_document(&chars("cat <<EOF\nhi\nEOF\nls"), 10, "EOF", false)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_here_document_strip_tabs() {
+        assert_eq!(
+            8,
+            tokenize_here_document(&chars("\ta\n\t\tEOF\n"), 0, "EOF", true)
+        );
+        assert_eq!(
+            9,
+            tokenize_here_document(&chars("\ta\n\t\tEOF\n"), 0, "EOF", false)
+        );
+        assert_eq!(
+            11,
+            tokenize_here_document(&chars("a\n  EOF\nEOF"), 0, "EOF", true)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_here_document_unterminated() {
+        assert_eq!(
+            7,
+            tokenize_here_document(&chars("a\nb\nc\nd"), 0, "EOF", false)
+        );
+        assert_eq!(0, tokenize_here_document(&chars(""), 0, "EOF", false));
+    }
+
+    #[test]
+    fn test_here_document_delimiter() {
+        assert_eq!((String::from("EOF"), false), here_document_delimiter("EOF"));
+        assert_eq!(
+            (String::from("EOF"), true),
+            here_document_delimiter("'EOF'")
+        );
+        assert_eq!(
+            (String::from("EOF"), true),
+            here_document_delimiter("\"EOF\"")
+        );
+        assert_eq!(
+            (String::from("EOF"), true),
+            here_document_delimiter("E\\OF")
+        );
+        assert_eq!(
+            (String::from("EOF"), true),
+            here_document_delimiter("E'O'F")
+        );
+        assert_eq!(
+            (String::from("$EOF"), false),
+            here_document_delimiter("$EOF")
+        );
+    }
+}
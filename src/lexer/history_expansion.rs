@@ -0,0 +1,231 @@
+use crate::dialect::Dialect;
+
+/// Characters that end the string of `!string`, besides whitespace
+const EVENT_DELIMITERS: [char; 11] = [':', ';', '&', '|', '(', ')', '<', '>', '"', '\'', '`'];
+
+/// tokenize_history_expansion
+/// > [9.3 History Expansion](https://www.gnu.org/software/bash/manual/bash.html#History-Interaction)
+/// > History expansions are introduced by the appearance of the history expansion character, which is ‘!’ by
+/// > default. Only ‘\’ and ‘'’ may be used to escape the history expansion character [...] Several characters
+/// > inhibit history expansion if found immediately following the history expansion character, even if it is
+/// > unquoted: space, tab, newline, carriage return, and ‘=’. If the extglob shell option is enabled, ‘(’
+/// > will also inhibit expansion.
+///
+/// An event designator (`!!`, `!n`, `!-n`, `!string`, `!?string?`, `!#`), an optional word designator (`:2`,
+/// `^`, `$`, `*`, `1-3`) and modifiers (`:h`, `:s/old/new/`, `:gs/a/b/`, ...) : `!!:1:h`. `!$`, `!^`, `!*`
+/// and `!:1-3` are short for the words of the previous command, `^old^new^` at the start of a line substitutes
+/// in it. Only with `dialect.history_expansion`, which interactive shells have. Single quotes and backslashes
+/// are tokenized before we get here, double quotes call this for every `!` they contain, and a `!` followed
+/// by `(` is always a literal as it can start `!(pattern)` or negate a subshell.
+pub fn tokenize_history_expansion(content: &[char], start: usize, dialect: &Dialect) -> usize {
+    if !dialect.history_expansion {
+        return start;
+    }
+    let end = match content.get(start) {
+        Some('!') => event(content, start + 1),
+        Some('^') if start == 0 || content[start - 1] == '\n' => quick_substitution(content, start),
+        _ => None,
+    };
+    end.unwrap_or(start)
+}
+
+/// The event designator after the `!` at `start - 1`, followed by the word designator and modifiers
+fn event(chars: &[char], start: usize) -> Option<usize> {
+    let pointer = match *chars.get(start)? {
+        ' ' | '\t' | '\n' | '\r' | '=' | '(' => return None,
+        '!' | '#' => start + 1,
+        // `!$` is `!!:$` and `!:1-3` is `!!:1-3` :
+        '^' | '$' | '*' | '%' => return Some(modifiers(chars, word_designator(chars, start)?)),
+        ':' => return Some(modifiers(chars, word_designator(chars, start + 1)?)),
+        '?' => {
+            match (start + 1..chars.len()).find(|pointer| ['?', '\n'].contains(&chars[*pointer])) {
+                Some(close) if chars[close] == '?' => close + 1,
+                Some(close) => close,
+                None => chars.len(),
+            }
+        }
+        char if char == '-' || char.is_ascii_digit() => {
+            let digits = start + usize::from(char == '-');
+            let end = digits + count(chars, digits, |char| char.is_ascii_digit());
+            match end == digits {
+                true => return None,
+                false => end,
+            }
+        }
+        _ => {
+            start
+                + count(chars, start, |char| {
+                    !char.is_ascii_whitespace() && !EVENT_DELIMITERS.contains(&char)
+                })
+        }
+    };
+    if pointer == start {
+        return None;
+    }
+    let pointer = match chars.get(pointer) {
+        Some(':') => word_designator(chars, pointer + 1).unwrap_or(pointer),
+        Some('^' | '$' | '*' | '-' | '%') => word_designator(chars, pointer).unwrap_or(pointer),
+        _ => pointer,
+    };
+    Some(modifiers(chars, pointer))
+}
+
+/// > n, ^, $, %, x-y, *, x*, x-
+fn word_designator(chars: &[char], start: usize) -> Option<usize> {
+    let word = |pointer: usize| match chars.get(pointer)? {
+        '^' | '$' | '%' => Some(pointer + 1),
+        char if char.is_ascii_digit() => {
+            Some(pointer + count(chars, pointer, |char| char.is_ascii_digit()))
+        }
+        _ => None,
+    };
+    match chars.get(start)? {
+        '*' => Some(start + 1),
+        '-' => Some(word(start + 1).unwrap_or(start + 1)), // `-y` is `0-y`
+        _ => {
+            let end = word(start)?;
+            match chars.get(end) {
+                Some('*') => Some(end + 1),
+                Some('-') => Some(word(end + 1).unwrap_or(end + 1)),
+                _ => Some(end),
+            }
+        }
+    }
+}
+
+/// Every `:modifier` from `start`, an unknown one ends the expansion before its `:`
+fn modifiers(chars: &[char], start: usize) -> usize {
+    let mut pointer = start;
+    while chars.get(pointer) == Some(&':') {
+        let end = match chars.get(pointer + 1) {
+            Some('h' | 't' | 'r' | 'e' | 'p' | 'q' | 'x' | '&') => pointer + 2,
+            Some('s') => substitution(chars, pointer + 2),
+            Some('g' | 'a' | 'G') => match chars.get(pointer + 2) {
+                Some('s') => substitution(chars, pointer + 3),
+                Some('&') => pointer + 3,
+                _ => break,
+            },
+            _ => break,
+        };
+        pointer = end;
+    }
+    pointer
+}
+
+/// `/old/new/` from `start`, any character can be the delimiter. The last delimiter is optional at the end of
+/// the line.
+fn substitution(chars: &[char], start: usize) -> usize {
+    let Some(&delimiter) = chars.get(start).filter(|char| !char.is_ascii_whitespace()) else {
+        return start;
+    };
+    let mut pointer = start + 1;
+    let mut delimiters = 0;
+    while delimiters < 2 {
+        match chars.get(pointer) {
+            None | Some('\n') => break,
+            Some('\\') if chars.get(pointer + 1) == Some(&delimiter) => pointer += 2,
+            Some(char) => {
+                if *char == delimiter {
+                    delimiters += 1;
+                }
+                pointer += 1;
+            }
+        }
+    }
+    pointer
+}
+
+/// > ^string1^string2^
+/// > Quick Substitution. Repeat the last command, replacing string1 with string2.
+fn quick_substitution(chars: &[char], start: usize) -> Option<usize> {
+    let line = &chars[start + 1..];
+    let line = &line[..line
+        .iter()
+        .position(|char| *char == '\n')
+        .unwrap_or(line.len())];
+    match line.contains(&'^') {
+        true => Some(substitution(chars, start)),
+        false => None,
+    }
+}
+
+fn count(chars: &[char], start: usize, predicate: impl Fn(char) -> bool) -> usize {
+    chars
+        .iter()
+        .skip(start)
+        .take_while(|char| predicate(**char))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::lexer::chars;
+    use crate::lexer::history_expansion::tokenize_history_expansion;
+
+    const INTERACTIVE: Dialect = Dialect {
+        history_expansion: true,
+        ..Dialect::BASH
+    };
+
+    fn history(content: &str, start: usize) -> usize {
+        tokenize_history_expansion(&chars(content), start, &INTERACTIVE)
+    }
+
+    #[test]
+    fn test_tokenize_history_expansion_events() {
+        assert_eq!(2, history("!!", 0));
+        assert_eq!(7, history("sudo !!", 5));
+        assert_eq!(3, history("!42", 0));
+        assert_eq!(3, history("!-2 x", 0));
+        assert_eq!(4, history("!git status", 0));
+        assert_eq!(4, history("!git;ls", 0));
+        assert_eq!(7, history("!?make? x", 0));
+        assert_eq!(6, history("!?make", 0));
+        assert_eq!(2, history("!#", 0));
+        assert_eq!(
+            0,
+            tokenize_history_expansion(&chars("!!"), 0, &Dialect::BASH)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_history_expansion_designators() {
+        assert_eq!(7, history("echo !$", 5));
+        assert_eq!(2, history("!^", 0));
+        assert_eq!(2, history("!* x", 0));
+        assert_eq!(4, history("!!:2", 0));
+        assert_eq!(6, history("!!:1-3", 0));
+        assert_eq!(5, history("!!:2*", 0));
+        assert_eq!(4, history("!-2$ x", 0));
+        assert_eq!(5, history("!vi:1:", 0));
+        assert_eq!(6, history("!!:$:h", 0));
+        assert_eq!(9, history("!!:s/a/b/ x", 0));
+        assert_eq!(13, history("!!:gs/\\//_/:p", 0));
+        assert_eq!(8, history("!!:s/a/b", 0));
+        assert_eq!(4, history("!!:&:z", 0));
+        assert_eq!(5, history("!:1-3 x", 0));
+        assert_eq!(5, history("!:$:h", 0));
+        assert_eq!(0, history("!: x", 0));
+    }
+
+    #[test]
+    fn test_tokenize_history_expansion_inhibited() {
+        assert_eq!(0, history("! ls", 0));
+        assert_eq!(4, history("echo!", 4));
+        assert_eq!(1, history("a!=b", 1));
+        assert_eq!(0, history("!(x)", 0));
+        assert_eq!(0, history("!\tx", 0));
+        assert_eq!(0, history("!-x", 0));
+        assert_eq!(1, history("a", 1));
+    }
+
+    #[test]
+    fn test_tokenize_history_expansion_quick_substitution() {
+        assert_eq!(8, history("^old^new", 0));
+        assert_eq!(9, history("^old^new^ x", 0));
+        assert_eq!(11, history("ls\n^old^new\n", 3));
+        assert_eq!(3, history("ls ^old^new", 3));
+        assert_eq!(0, history("^old", 0));
+    }
+}
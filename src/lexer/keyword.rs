@@ -0,0 +1,155 @@
+use crate::parser::RESERVED_WORDS;
+use crate::tokens::tokens::{Token, TokenKind};
+
+/// Reserved words after which the next word isn't a command : a name, a subject or a list of words
+const NOT_FOLLOWED_BY_COMMAND: [&str; 6] = ["case", "for", "select", "in", "function", "]]"];
+
+/// mark_keywords
+/// > [2.4 Reserved Words](https://pubs.opengroup.org/onlinepubs/9799919799/utilities/V3_chap02.html#tag_19_04)
+/// > Reserved words are words that have special meaning to the shell. [...] This recognition shall only occur
+/// > when none of the characters is quoted and when the word is used as:
+/// > - The first word of a command
+/// > - The first word following one of the reserved words other than case, for, or in
+/// > - The third word in a case command (only in is valid in this case)
+/// > - The third word in a for command (only in and do are valid in this case)
+///
+/// Turns the `Word` tokens of `tokens` that are reserved words into `Keyword` tokens. A reserved word is a word
+/// on its own, `if"x"` and `\if` aren't, and a case pattern is never one : `case $x in (done) ...`. Between
+/// `[[` and `]]` the words are operands, only the closing `]]` is reserved.
+pub(crate) fn mark_keywords(tokens: &mut [Token]) {
+    let mut command_start = true;
+    let mut conditional = false; // Between `[[` and `]]`
+    let mut third_word: Option<(&str, usize)> = None; // The `case`, `for`, `select` or `function` and the words after it
+    let mut index = 0;
+    while index < tokens.len() {
+        let end = index
+            + tokens[index..]
+                .iter()
+                .take_while(|token| token.kind.is_word())
+                .count();
+        if end == index {
+            match tokens[index].kind {
+                TokenKind::WhiteSpace | TokenKind::Comment | TokenKind::HereDocument => {}
+                TokenKind::Newline => command_start = !conditional,
+                _ if conditional => {}
+                kind => {
+                    // Redirections and the `(` and `|` between case patterns are followed by a word :
+                    command_start = !(kind.is_redirection()
+                        || kind == TokenKind::CasePatternStart
+                        || kind == TokenKind::CasePatternSeparator);
+                    third_word = None;
+                }
+            }
+            index += 1;
+            continue;
+        }
+
+        let token = &tokens[index];
+        let reserved = end == index + 1
+            && token.kind == TokenKind::Word
+            && RESERVED_WORDS.contains(&token.text.as_str());
+        let keyword = match third_word {
+            _ if !reserved => false,
+            _ if conditional => token.text == "]]",
+            Some(("case" | "for" | "select", 1)) if token.text == "in" => true,
+            Some(("for" | "select", 1)) if token.text == "do" => true,
+            _ => command_start && !is_case_pattern(tokens, end),
+        };
+        let introducer = third_word.map(|(reserved_word, _)| reserved_word);
+        third_word = match third_word {
+            Some((reserved_word, 0)) => Some((reserved_word, 1)),
+            _ => None,
+        };
+        // The body of `function name` follows the name :
+        command_start = third_word == Some(("function", 1));
+        if keyword {
+            match token.text.as_str() {
+                "case" => third_word = Some(("case", 0)),
+                "for" => third_word = Some(("for", 0)),
+                "select" => third_word = Some(("select", 0)),
+                "function" => third_word = Some(("function", 0)),
+                "[[" => conditional = true,
+                "]]" => conditional = false,
+                _ => {}
+            }
+            // The patterns of a case item start after `in`, where `esac` can end it right away :
+            command_start = !NOT_FOLLOWED_BY_COMMAND.contains(&token.text.as_str())
+                || (token.text == "in" && introducer == Some("case"));
+            tokens[index].kind = TokenKind::Keyword;
+        }
+        index = end;
+    }
+}
+
+/// Whether the word ending at `end` is followed by the `)` or `|` of a case pattern
+fn is_case_pattern(tokens: &[Token], end: usize) -> bool {
+    tokens[end..]
+        .iter()
+        .find(|token| token.kind != TokenKind::WhiteSpace)
+        .is_some_and(|token| {
+            matches!(
+                token.kind,
+                TokenKind::CasePatternEnd | TokenKind::CasePatternSeparator
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenize;
+    use crate::tokens::tokens::TokenKind;
+
+    /// The text of the `Keyword` tokens of `script`
+    fn keywords(script: &str) -> Vec<String> {
+        tokenize(script.to_string())
+            .unwrap()
+            .into_iter()
+            .filter(|token| token.kind == TokenKind::Keyword)
+            .map(|token| token.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_mark_keywords() {
+        assert_eq!(
+            vec!["if", "!", "then", "{", "}", "else", "fi"],
+            keywords("if ! a; then { b; } else echo fi then; fi")
+        );
+        assert_eq!(
+            vec!["while", "do", "done", "until", "time", "do", "done"],
+            keywords("while a\ndo b; done >out | until time -p c; do :; done")
+        );
+        assert_eq!(
+            vec!["for", "in", "do", "done", "select", "do", "done"],
+            keywords("for in in in do; do echo do; done; select x do a; done")
+        );
+        assert_eq!(
+            vec!["function", "{", "}", "{", "}", "coproc"],
+            keywords("function f { a; }; g() { b; }; coproc (c)")
+        );
+    }
+
+    #[test]
+    fn test_mark_keywords_not_reserved() {
+        // Quoted, escaped, part of a word or an argument :
+        assert!(keywords("'if' \"then\" \\fi if\"x\" x=done; echo if").is_empty());
+        // The operands of `[[ ]]`, only the closing `]]` is reserved :
+        assert_eq!(vec!["[[", "]]"], keywords("[[ ! a == if && ( b ) ]]"));
+        assert_eq!(vec!["[[", "]]"], keywords("[[ a\n]]"));
+        // The target of a redirection :
+        assert!(keywords("echo >if; <done cat").is_empty());
+    }
+
+    #[test]
+    fn test_mark_keywords_case() {
+        assert_eq!(
+            vec!["case", "in", "if", "then", "fi", "esac"],
+            keywords("case if in\n  done) if a; then b; fi;;\n  (esac | in) c;;\nesac")
+        );
+        assert_eq!(vec!["case", "in", "esac"], keywords("case x in esac"));
+        assert_eq!(
+            vec!["case", "in", "esac"],
+            keywords("case x\nin a|b) c;; esac")
+        );
+    }
+}
@@ -1,82 +1,100 @@
-use crate::tokens::tokens::Token;
+use crate::tokens::tokens::TokenKind;
 
 /// tokenize_metacharacter
 /// > [metacharacter](https://www.gnu.org/software/bash/manual/bash.html#Single-Quotes)
 /// > A character that, when unquoted, separates words. A metacharacter is a space, tab, newline,
 /// > **or one of the following characters: ‘|’, ‘&’, ‘;’, ‘(’, ‘)’, ‘<’, or ‘>’.**
-/// TO DO: This function should in the future also check for Redirection operators, ...
-pub fn tokenize_metacharacter(content: &str, start: usize) -> Option<(Token, usize)> {
-    let mut pointer: usize = start;
-    let char = content.chars().nth(pointer).unwrap();
-    // We loop here for double characters (in the future) :
-    loop {
-        pointer += 1;
-        if char == '|' {
-            return Some((Token::Pipe, pointer));
-        }
-        if char == '&' {
-            return Some((Token::Ampersand, pointer));
-        }
-        if char == ';' {
-            return Some((Token::Semicolon, pointer));
-        }
-        if char == '(' {
-            return Some((Token::LParenthesis, pointer));
-        }
-        if char == ')' {
-            return Some((Token::RParenthesis, pointer));
-        }
-        if char == '<' {
-            return Some((Token::LessAnd, pointer));
-        }
-        if char == '>' {
-            return Some((Token::GreatAnd, pointer));
-        }
-        break;
-    }
+///
+/// Operators of more than one character (`&&`, `>>`, ...) are handled by
+/// [tokenize_operator](crate::lexer::operator::tokenize_operator). `{` and `}` aren't metacharacters but
+/// reserved words (brace groups) or part of a word, see
+/// [tokenize_brace_expansion](crate::lexer::brace_expansion::tokenize_brace_expansion).
+pub fn tokenize_metacharacter(content: &[char], start: usize) -> Option<(TokenKind, usize)> {
+    let token = match content.get(start).copied()? {
+        '|' => TokenKind::Pipe,
+        '&' => TokenKind::Ampersand,
+        ';' => TokenKind::Semicolon,
+        '(' => TokenKind::LParenthesis,
+        ')' => TokenKind::RParenthesis,
+        '<' => TokenKind::LessAnd,
+        '>' => TokenKind::GreatAnd,
+        _ => return None, // Default is None, no token found
+    };
+    Some((token, start + 1))
+}
 
-    None // Default is None, no token found
+/// Whether `char` is one of the metacharacters matched by [tokenize_metacharacter]
+pub fn is_metacharacter(char: char) -> bool {
+    ['|', '&', ';', '(', ')', '<', '>'].contains(&char)
 }
+
 #[cfg(test)]
 mod tests {
+    use crate::lexer::chars;
     use crate::lexer::metacharacter::tokenize_metacharacter;
-    use crate::tokens::tokens::Token;
+    use crate::tokens::tokens::TokenKind;
 
     #[test]
     fn test_tokenize_metacharacter_basic() {
         // Test for each metacharacter
-        assert_eq!(tokenize_metacharacter("|", 0), Some((Token::Pipe, 1)));
-        assert_eq!(tokenize_metacharacter("&", 0), Some((Token::Ampersand, 1)));
-        assert_eq!(tokenize_metacharacter(";", 0), Some((Token::Semicolon, 1)));
         assert_eq!(
-            tokenize_metacharacter("(", 0),
-            Some((Token::LParenthesis, 1))
+            tokenize_metacharacter(&chars("|"), 0),
+            Some((TokenKind::Pipe, 1))
+        );
+        assert_eq!(
+            tokenize_metacharacter(&chars("&"), 0),
+            Some((TokenKind::Ampersand, 1))
         );
         assert_eq!(
-            tokenize_metacharacter(")", 0),
-            Some((Token::RParenthesis, 1))
+            tokenize_metacharacter(&chars(";"), 0),
+            Some((TokenKind::Semicolon, 1))
+        );
+        assert_eq!(
+            tokenize_metacharacter(&chars("("), 0),
+            Some((TokenKind::LParenthesis, 1))
+        );
+        assert_eq!(
+            tokenize_metacharacter(&chars(")"), 0),
+            Some((TokenKind::RParenthesis, 1))
+        );
+        assert_eq!(
+            tokenize_metacharacter(&chars("<"), 0),
+            Some((TokenKind::LessAnd, 1))
+        );
+        assert_eq!(
+            tokenize_metacharacter(&chars(">"), 0),
+            Some((TokenKind::GreatAnd, 1))
         );
-        assert_eq!(tokenize_metacharacter("<", 0), Some((Token::LessAnd, 1)));
-        assert_eq!(tokenize_metacharacter(">", 0), Some((Token::GreatAnd, 1)));
     }
 
     #[test]
     fn test_tokenize_metacharacter_no_match() {
         // Test for characters that are not metacharacters
-        assert_eq!(tokenize_metacharacter("a", 0), None);
-        assert_eq!(tokenize_metacharacter(" ", 0), None); // Space is not handled in the current implementation
-        assert_eq!(tokenize_metacharacter("abc", 0), None);
+        assert_eq!(tokenize_metacharacter(&chars("a"), 0), None);
+        assert_eq!(tokenize_metacharacter(&chars(" "), 0), None); // Space is not handled in the current implementation
+        assert_eq!(tokenize_metacharacter(&chars("abc"), 0), None);
+        assert_eq!(tokenize_metacharacter(&chars(""), 0), None);
+        assert_eq!(tokenize_metacharacter(&chars("|"), 3), None); // Out of bounds
     }
 
     #[test]
     fn test_tokenize_metacharacter_multiple_characters() {
         // Test for a string with multiple characters, only the first metacharacter should be tokenized
-        assert_eq!(tokenize_metacharacter("|&;", 0), Some((Token::Pipe, 1)));
-        assert_eq!(tokenize_metacharacter("&;", 1), Some((Token::Semicolon, 2)));
         assert_eq!(
-            tokenize_metacharacter("();", 1),
-            Some((Token::RParenthesis, 2))
+            tokenize_metacharacter(&chars("|&;"), 0),
+            Some((TokenKind::Pipe, 1))
+        );
+        assert_eq!(
+            tokenize_metacharacter(&chars("&;"), 1),
+            Some((TokenKind::Semicolon, 2))
+        );
+        assert_eq!(
+            tokenize_metacharacter(&chars("();"), 1),
+            Some((TokenKind::RParenthesis, 2))
+        );
+        assert_eq!(
+            tokenize_metacharacter(&chars("<>"), 0),
+            Some((TokenKind::LessAnd, 1))
         );
-        assert_eq!(tokenize_metacharacter("<>", 0), Some((Token::LessAnd, 1)));
     }
 }
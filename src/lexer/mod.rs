@@ -1,5 +1,28 @@
+pub mod arithmetic_expansion;
+pub mod brace_expansion;
+pub mod case_pattern;
+pub mod command_substitution;
+pub mod comment;
 pub mod dollar_single_quotes;
+pub mod double_quotes;
+pub mod here_document;
+pub mod history_expansion;
+pub mod keyword;
 pub mod metacharacter;
+pub mod nested;
 pub mod number;
+pub mod operator;
+pub mod parameter_expansion;
+pub mod pattern;
+pub mod process_substitution;
+pub mod regex;
 pub mod single_quotes;
+pub mod tilde_prefix;
 pub mod whitespace;
+pub mod word;
+
+/// The characters of `s`, what the lexers work on
+#[cfg(test)]
+pub(crate) fn chars(s: &str) -> Vec<char> {
+    s.chars().collect()
+}
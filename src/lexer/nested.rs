@@ -0,0 +1,195 @@
+use crate::dialect::Dialect;
+use crate::lexer::arithmetic_expansion::{tokenize_arithmetic_expansion, ArithmeticCommands};
+use crate::lexer::case_pattern::CasePatterns;
+use crate::lexer::command_substitution::tokenize_command_substitution;
+use crate::lexer::dollar_single_quotes::tokenize_dollar_single_quotes;
+use crate::lexer::double_quotes::tokenize_double_quotes;
+use crate::lexer::parameter_expansion::tokenize_parameter_expansion;
+use crate::lexer::single_quotes::tokenize_single_quotes;
+use crate::tokenize_next;
+use crate::tokens::tokens::TokenKind;
+use std::cell::Cell;
+
+/// How many quotes, expansions and substitutions deep the lexer goes before giving up, every level is a few
+/// frames of recursion. Bash scripts stay far below, a limit keeps input like 100000 `$(` from overflowing the
+/// stack.
+pub(crate) const MAX_DEPTH: usize = 256;
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Runs `tokenize` one level deeper, or fails with an error at `start` past [MAX_DEPTH].
+fn nested<T>(
+    start: usize,
+    tokenize: impl FnOnce() -> Result<T, Box<dyn std::error::Error>>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let depth = DEPTH.get();
+    if depth >= MAX_DEPTH {
+        return Err(format!(
+            "Nesting deeper than {} levels at index {}",
+            MAX_DEPTH, start
+        )
+        .into());
+    }
+    DEPTH.set(depth + 1);
+    let result = tokenize();
+    DEPTH.set(depth);
+    result
+}
+
+/// tokenize_expansion
+/// Skips over an arithmetic expansion, command substitution or parameter expansion starting at `start`.
+/// These are the constructs that remain special inside double quotes.
+pub fn tokenize_expansion(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let arithmetic_pointer = tokenize_arithmetic_expansion(content, start, dialect)?;
+    if arithmetic_pointer != start {
+        return Ok(arithmetic_pointer);
+    }
+    let command_pointer = tokenize_command_substitution(content, start, dialect)?;
+    if command_pointer != start {
+        return Ok(command_pointer);
+    }
+    tokenize_parameter_expansion(content, start, dialect)
+}
+
+/// tokenize_nested
+/// Skips over anything that can hide a closing character from an enclosing construct : quoted strings,
+/// backslash escapes and expansions. Returns `start` when there's nothing to skip, and an error when they're
+/// nested deeper than `MAX_DEPTH` levels.
+pub fn tokenize_nested(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    nested(start, || tokenize_nested_construct(content, start, dialect))
+}
+
+fn tokenize_nested_construct(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    match content.get(start).copied() {
+        Some('\'') => tokenize_single_quotes(content, start),
+        Some('"') => tokenize_double_quotes(content, start, dialect),
+        Some('\\') => Ok(start + 2), // The backslash always covers the next character
+        Some('$') => {
+            let dsq_pointer = tokenize_dollar_single_quotes(content, start, dialect)?;
+            if dsq_pointer != start {
+                return Ok(dsq_pointer);
+            }
+            tokenize_expansion(content, start, dialect)
+        }
+        Some('`') => tokenize_command_substitution(content, start, dialect),
+        _ => Ok(start),
+    }
+}
+
+/// tokenize_list
+/// Skips over the list of `$(list)` or `<(list)` from `start`, right after the opening parenthesis, and
+/// returns where the `)` that closes it is. The list is tokenized like a script, so the `)` of a case
+/// pattern (`$(case $x in a) echo a;; esac)`) or one in a comment doesn't close it. `None` when the input
+/// ends first.
+pub fn tokenize_list(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<Option<usize>, Box<dyn std::error::Error>> {
+    nested(start, || tokenize_list_items(content, start, dialect))
+}
+
+fn tokenize_list_items(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<Option<usize>, Box<dyn std::error::Error>> {
+    let length = content.len();
+    let mut case_patterns = CasePatterns::default();
+    let mut arithmetic_commands = ArithmeticCommands::default();
+    let mut depth = 0;
+    let mut pointer = start;
+    let mut previous = None;
+    while pointer < length {
+        let arithmetic_commands = case_patterns
+            .arithmetic_command_start()
+            .then_some(&mut arithmetic_commands);
+        let (kind, next_pointer) =
+            tokenize_next(content, pointer, previous, arithmetic_commands, dialect)?;
+        let text: String = content[pointer..next_pointer].iter().collect();
+        let kind = case_patterns.next(kind, &text);
+        match kind {
+            TokenKind::LParenthesis => depth += 1,
+            TokenKind::RParenthesis if depth == 0 => return Ok(Some(pointer)),
+            TokenKind::RParenthesis => depth -= 1,
+            _ => {}
+        }
+        (pointer, previous) = (next_pointer, Some(kind));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::lexer::chars;
+    use crate::lexer::nested::{tokenize_expansion, tokenize_nested};
+
+    #[test]
+    pub fn test_tokenize_expansion() {
+        assert_eq!(
+            5,
+            tokenize_expansion(&chars("$HOME/bin"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            8,
+            tokenize_expansion(&chars("$(ls -l)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            8,
+            tokenize_expansion(&chars("$((1+2))"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            6,
+            tokenize_expansion(&chars("`date`"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_expansion(&chars("'quoted'"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_expansion(&chars("$'quoted'"), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_nested() {
+        assert_eq!(
+            3,
+            tokenize_nested(&chars("')'"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            3,
+            tokenize_nested(&chars("\")\""), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            4,
+            tokenize_nested(&chars("$')'"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            2,
+            tokenize_nested(&chars("\\)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            4,
+            tokenize_nested(&chars("$(a)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(0, tokenize_nested(&chars(")"), 0, &Dialect::BASH).unwrap());
+        assert_eq!(0, tokenize_nested(&chars(""), 0, &Dialect::BASH).unwrap());
+    }
+}
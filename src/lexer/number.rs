@@ -1,34 +1,39 @@
-pub fn tokenize_number(content: &String, start: usize) -> usize {
+pub fn tokenize_number(content: &[char], start: usize) -> usize {
     let mut pointer: usize = start;
-    let mut char = content.chars().nth(pointer).unwrap();
-    while char.is_numeric() {
-        pointer += 1;
-        if pointer >= content.len() {
+    while let Some(char) = content.get(pointer).copied() {
+        if !char.is_numeric() {
             break;
         }
-
-        char = content.chars().nth(pointer).unwrap();
+        pointer += 1;
     }
     pointer
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::lexer::chars;
     use crate::lexer::number::tokenize_number;
 
     #[test]
     pub fn test_tokenize_number_basic() {
-        assert_eq!(2, tokenize_number(&String::from("NotANumber"), 2));
-        assert_eq!(0, tokenize_number(&String::from("NotANumber"), 0));
-        assert_eq!(9, tokenize_number(&String::from("NotANumber"), 9)); // Last character
+        assert_eq!(2, tokenize_number(&chars("NotANumber"), 2));
+        assert_eq!(0, tokenize_number(&chars("NotANumber"), 0));
+        assert_eq!(9, tokenize_number(&chars("NotANumber"), 9)); // Last character
 
-        assert_eq!(1, tokenize_number(&String::from("8"), 0));
-        assert_eq!(1, tokenize_number(&String::from("8-"), 0));
-        assert_eq!(1, tokenize_number(&String::from("8o"), 0));
-        assert_eq!(1, tokenize_number(&String::from("8\n9"), 0));
-        assert_eq!(1, tokenize_number(&String::from("8 9"), 0));
-        assert_eq!(1, tokenize_number(&String::from("8.9"), 0));
-        assert_eq!(3, tokenize_number(&String::from("801"), 0));
-        assert_eq!(3, tokenize_number(&String::from("801 - 902"), 0));
+        assert_eq!(1, tokenize_number(&chars("8"), 0));
+        assert_eq!(1, tokenize_number(&chars("8-"), 0));
+        assert_eq!(1, tokenize_number(&chars("8o"), 0));
+        assert_eq!(1, tokenize_number(&chars("8\n9"), 0));
+        assert_eq!(1, tokenize_number(&chars("8 9"), 0));
+        assert_eq!(1, tokenize_number(&chars("8.9"), 0));
+        assert_eq!(3, tokenize_number(&chars("801"), 0));
+        assert_eq!(3, tokenize_number(&chars("801 - 902"), 0));
+    }
+
+    #[test]
+    pub fn test_tokenize_number_out_of_bounds() {
+        assert_eq!(0, tokenize_number(&chars(""), 0));
+        assert_eq!(5, tokenize_number(&chars("123"), 5));
+        assert_eq!(3, tokenize_number(&chars("é12"), 1)); // Multi-byte characters before the number
     }
 }
@@ -0,0 +1,202 @@
+use crate::dialect::{BashVersion, Dialect};
+use crate::tokens::tokens::TokenKind;
+
+/// tokenize_operator
+/// > [2.10.1 Shell Grammar Lexical Conventions](https://pubs.opengroup.org/onlinepubs/9799919799/utilities/V3_chap02.html#tag_19_10_01)
+/// > `&&` `||` `;;` `<<` `>>` `<&` `>&` `<>` `<<-` `>|`
+///
+/// The operators made up of more than one character, the single character ones are handled by
+/// [tokenize_metacharacter](crate::lexer::metacharacter::tokenize_metacharacter). This has to run first.
+/// Bash adds `<<<`, `&>`, `&>>`, `|&` and `;;&`, which depend on the `dialect` like the `;&` of IEEE 1003.1-2024.
+pub fn tokenize_operator(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Option<(TokenKind, usize)> {
+    let mut chars = content.iter().skip(start).copied();
+    let operator = match (chars.next()?, chars.next(), chars.next()) {
+        ('<', Some('<'), Some('<')) if dialect.here_strings => {
+            return Some((TokenKind::TripleLess, start + 3))
+        }
+        ('&', Some('>'), Some('>')) if dialect.redirect_output_and_error => {
+            return Some((TokenKind::AmpersandDoubleGreat, start + 3))
+        }
+        (';', Some(';'), Some('&')) if dialect.case_continue => {
+            return Some((TokenKind::DoubleSemicolonAmpersand, start + 3))
+        }
+        ('<', Some('<'), Some('-')) => return Some((TokenKind::DoubleLessDash, start + 3)),
+        ('&', Some('&'), _) => TokenKind::AndIf,
+        ('&', Some('>'), _) if dialect.redirect_output_and_error => TokenKind::AmpersandGreat,
+        ('|', Some('|'), _) => TokenKind::OrIf,
+        ('|', Some('&'), _) if dialect.pipe_output_and_error => TokenKind::PipeAmpersand,
+        (';', Some(';'), _) => TokenKind::DoubleSemicolon,
+        (';', Some('&'), _) if dialect.case_fallthrough => TokenKind::SemicolonAmpersand,
+        ('<', Some('<'), _) => TokenKind::DoubleLess,
+        ('>', Some('>'), _) => TokenKind::DoubleGreat,
+        ('<', Some('&'), _) => TokenKind::LessAmpersand,
+        ('>', Some('&'), _) => TokenKind::GreatAmpersand,
+        ('<', Some('>'), _) => TokenKind::LessGreat,
+        ('>', Some('|'), _) => TokenKind::Clobber,
+        _ => return None,
+    };
+    Some((operator, start + 2))
+}
+
+/// Err when the operator `kind` found at `index` is newer than [Dialect::bash_version] :
+/// `&>>`, `|&`, `;&` and `;;&` came with bash 4.0.
+pub fn require_operator_version(
+    kind: TokenKind,
+    index: usize,
+    dialect: &Dialect,
+) -> Result<(), String> {
+    let operator = match kind {
+        TokenKind::AmpersandDoubleGreat => "&>>",
+        TokenKind::PipeAmpersand => "|&",
+        TokenKind::SemicolonAmpersand => ";&",
+        TokenKind::DoubleSemicolonAmpersand => ";;&",
+        _ => return Ok(()),
+    };
+    dialect.require_bash(BashVersion::new(4, 0), operator, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::{BashVersion, Dialect};
+    use crate::lexer::chars;
+    use crate::lexer::operator::{require_operator_version, tokenize_operator};
+    use crate::tokens::tokens::TokenKind;
+
+    #[test]
+    fn test_tokenize_operator_basic() {
+        assert_eq!(
+            tokenize_operator(&chars("&&"), 0, &Dialect::BASH),
+            Some((TokenKind::AndIf, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("||"), 0, &Dialect::BASH),
+            Some((TokenKind::OrIf, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars(";;"), 0, &Dialect::BASH),
+            Some((TokenKind::DoubleSemicolon, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("<<"), 0, &Dialect::BASH),
+            Some((TokenKind::DoubleLess, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("<<-"), 0, &Dialect::BASH),
+            Some((TokenKind::DoubleLessDash, 3))
+        );
+        assert_eq!(
+            tokenize_operator(&chars(">>"), 0, &Dialect::BASH),
+            Some((TokenKind::DoubleGreat, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("<&"), 0, &Dialect::BASH),
+            Some((TokenKind::LessAmpersand, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars(">&"), 0, &Dialect::BASH),
+            Some((TokenKind::GreatAmpersand, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("<>"), 0, &Dialect::BASH),
+            Some((TokenKind::LessGreat, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars(">|"), 0, &Dialect::BASH),
+            Some((TokenKind::Clobber, 2))
+        );
+    }
+
+    #[test]
+    fn test_tokenize_operator_in_context() {
+        assert_eq!(
+            tokenize_operator(&chars("a && b"), 2, &Dialect::BASH),
+            Some((TokenKind::AndIf, 4))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("2>&1"), 1, &Dialect::BASH),
+            Some((TokenKind::GreatAmpersand, 3))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("cat <<-EOF"), 4, &Dialect::BASH),
+            Some((TokenKind::DoubleLessDash, 7))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("|||"), 0, &Dialect::BASH),
+            Some((TokenKind::OrIf, 2))
+        );
+    }
+
+    #[test]
+    fn test_tokenize_operator_no_match() {
+        assert_eq!(tokenize_operator(&chars("|"), 0, &Dialect::BASH), None);
+        assert_eq!(tokenize_operator(&chars("&;"), 0, &Dialect::BASH), None);
+        assert_eq!(tokenize_operator(&chars("a&&"), 0, &Dialect::BASH), None);
+        assert_eq!(tokenize_operator(&chars(""), 0, &Dialect::BASH), None);
+        assert_eq!(tokenize_operator(&chars("&&"), 5, &Dialect::BASH), None);
+    }
+
+    #[test]
+    fn test_tokenize_operator_dialects() {
+        assert_eq!(
+            tokenize_operator(&chars("<<<"), 0, &Dialect::BASH),
+            Some((TokenKind::TripleLess, 3))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("&>>"), 0, &Dialect::BASH),
+            Some((TokenKind::AmpersandDoubleGreat, 3))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("&>"), 0, &Dialect::BASH),
+            Some((TokenKind::AmpersandGreat, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("|&"), 0, &Dialect::BASH),
+            Some((TokenKind::PipeAmpersand, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars("<<<"), 0, &Dialect::DASH),
+            Some((TokenKind::DoubleLess, 2))
+        );
+        assert_eq!(tokenize_operator(&chars("&>"), 0, &Dialect::POSIX), None);
+        assert_eq!(tokenize_operator(&chars("|&"), 0, &Dialect::POSIX), None);
+
+        assert_eq!(
+            tokenize_operator(&chars(";;&"), 0, &Dialect::BASH),
+            Some((TokenKind::DoubleSemicolonAmpersand, 3))
+        );
+        assert_eq!(
+            tokenize_operator(&chars(";&"), 0, &Dialect::BASH),
+            Some((TokenKind::SemicolonAmpersand, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars(";&"), 0, &Dialect::POSIX),
+            Some((TokenKind::SemicolonAmpersand, 2))
+        );
+        assert_eq!(
+            tokenize_operator(&chars(";;&"), 0, &Dialect::POSIX),
+            Some((TokenKind::DoubleSemicolon, 2))
+        );
+        assert_eq!(tokenize_operator(&chars(";&"), 0, &Dialect::DASH), None);
+    }
+
+    #[test]
+    fn test_require_operator_version() {
+        let bash_3 = Dialect {
+            bash_version: Some(BashVersion::new(3, 2)),
+            ..Dialect::BASH
+        };
+        assert_eq!(
+            Err(String::from(
+                "`|&` requires bash 4.0 (targeting 3.2) at index 2"
+            )),
+            require_operator_version(TokenKind::PipeAmpersand, 2, &bash_3)
+        );
+        assert!(require_operator_version(TokenKind::DoubleSemicolonAmpersand, 0, &bash_3).is_err());
+        assert!(require_operator_version(TokenKind::AndIf, 0, &bash_3).is_ok());
+        assert!(require_operator_version(TokenKind::PipeAmpersand, 0, &Dialect::BASH).is_ok());
+    }
+}
@@ -0,0 +1,262 @@
+use crate::dialect::{BashVersion, Dialect};
+use crate::lexer::nested::tokenize_nested;
+
+/// tokenize_parameter_expansion
+/// > [3.5.3 Shell Parameter Expansion](https://www.gnu.org/software/bash/manual/bash.html#Shell-Parameter-Expansion)
+/// > The ‘$’ character introduces parameter expansion, command substitution, or arithmetic expansion.
+/// > The parameter name or symbol to be expanded may be enclosed in braces, which are optional but serve to
+/// > protect the variable to be expanded from characters immediately following it.
+///
+/// Without braces this is a name (`$HOME`), a single digit (`$1`) or a special parameter (`$?`).
+pub fn tokenize_parameter_expansion(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut prefix = content.iter().skip(start).copied();
+    if prefix.next() != Some('$') {
+        return Ok(start);
+    }
+    let pointer = start + 1; // Skipped `$`
+    match prefix.next() {
+        Some('{') => tokenize_braced_parameter(content, start, dialect),
+        Some(char) if char.is_ascii_alphabetic() || char == '_' => {
+            let name_length = prefix
+                .take_while(|char| char.is_ascii_alphanumeric() || *char == '_')
+                .count();
+            Ok(pointer + 1 + name_length)
+        }
+        Some(char) if char.is_ascii_digit() || is_special_parameter(char) => Ok(pointer + 1),
+        _ => Ok(start), // A lone `$` is just a literal dollar sign
+    }
+}
+
+/// > [3.4.2 Special Parameters](https://www.gnu.org/software/bash/manual/bash.html#Special-Parameters)
+pub fn is_special_parameter(char: char) -> bool {
+    ['*', '@', '#', '?', '-', '$', '!'].contains(&char)
+}
+
+/// `${...}` can contain nested expansions and quotes (`${x:-"}"}`), braces have to be balanced.
+fn tokenize_braced_parameter(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    if dialect.bash_version.is_some() {
+        require_bash_version(content, start, dialect)?;
+    }
+    let mut pointer = start + 2; // Skipped `${`
+    let mut depth = 0;
+    loop {
+        match content.get(pointer).copied() {
+            Some('{') => depth += 1,
+            Some('}') if depth == 0 => return Ok(pointer + 1),
+            Some('}') => depth -= 1,
+            Some(_) => {
+                let nested_pointer = tokenize_nested(content, pointer, dialect)?;
+                if nested_pointer != pointer {
+                    pointer = nested_pointer;
+                    continue;
+                }
+            }
+            None => {
+                return Err(format!("Unterminated parameter expansion at index {}", start).into());
+            }
+        }
+        pointer += 1;
+    }
+}
+
+/// The operator right after the parameter decides which bash can run a `${...}` :
+/// `${var^^}` and `${var,,}` came with bash 4.0, `${var@Q}` with 4.4 and `${var@U}` with 5.1.
+fn require_bash_version(content: &[char], start: usize, dialect: &Dialect) -> Result<(), String> {
+    let chars = content.get(start + 2..).unwrap_or_default();
+    let mut offset = usize::from(matches!(chars.first(), Some('!' | '#'))); // ${!ref} and ${#var}
+    let name_length = chars[offset.min(chars.len())..]
+        .iter()
+        .take_while(|char| char.is_ascii_alphanumeric() || **char == '_')
+        .count();
+    offset += match name_length {
+        0 => usize::from(
+            chars
+                .get(offset)
+                .is_some_and(|char| is_special_parameter(*char)),
+        ),
+        _ => name_length,
+    };
+    if chars.get(offset) == Some(&'[') {
+        // ${array[@]^^}
+        match chars[offset..].iter().position(|char| *char == ']') {
+            Some(end) => offset += end + 1,
+            None => return Ok(()),
+        }
+    }
+    let index = start + 2 + offset;
+    match (chars.get(offset), chars.get(offset + 1)) {
+        (Some(operator @ ('^' | ',')), _) => {
+            dialect.require_bash(BashVersion::new(4, 0), &operator.to_string(), index)
+        }
+        (Some('@'), Some(operator)) => {
+            let required = match operator {
+                'Q' | 'E' | 'P' | 'A' | 'a' => BashVersion::new(4, 4),
+                'U' | 'u' | 'L' | 'K' => BashVersion::new(5, 1),
+                'k' => BashVersion::new(5, 2),
+                _ => return Ok(()),
+            };
+            dialect.require_bash(required, &format!("@{}", operator), index)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::{BashVersion, Dialect};
+    use crate::lexer::chars;
+    use crate::lexer::parameter_expansion::tokenize_parameter_expansion;
+
+    #[test]
+    pub fn test_tokenize_parameter_expansion_names() {
+        assert_eq!(
+            0,
+            tokenize_parameter_expansion(&chars("HOME"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            5,
+            tokenize_parameter_expansion(&chars("$HOME"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            5,
+            tokenize_parameter_expansion(&chars("$HOME/bin"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            9,
+            tokenize_parameter_expansion(&chars("$_my_var1-x"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            9,
+            tokenize_parameter_expansion(&chars("echo $abc"), 5, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_parameter_expansion_special() {
+        assert_eq!(
+            2,
+            tokenize_parameter_expansion(&chars("$1"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            2,
+            tokenize_parameter_expansion(&chars("$10"), 0, &Dialect::BASH).unwrap()
+        ); // `$10` is `$1` followed by `0`
+        assert_eq!(
+            2,
+            tokenize_parameter_expansion(&chars("$?"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            2,
+            tokenize_parameter_expansion(&chars("$@"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            2,
+            tokenize_parameter_expansion(&chars("$$"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_parameter_expansion(&chars("$"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_parameter_expansion(&chars("$ "), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_parameter_expansion(&chars("$/"), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_parameter_expansion_braces() {
+        assert_eq!(
+            7,
+            tokenize_parameter_expansion(&chars("${HOME}"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            13,
+            tokenize_parameter_expansion(&chars("${x:-default}"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            9,
+            tokenize_parameter_expansion(&chars("${x:-\"}\"}"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            10,
+            tokenize_parameter_expansion(&chars("${x:-${y}}ab"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            "Unterminated parameter expansion at index 0",
+            tokenize_parameter_expansion(&chars("${HOME"), 0, &Dialect::BASH)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_parameter_expansion_bash_version() {
+        let bash_4_2 = Dialect {
+            bash_version: Some(BashVersion::new(4, 2)),
+            ..Dialect::BASH
+        };
+        let bash_3_2 = Dialect {
+            bash_version: Some(BashVersion::new(3, 2)),
+            ..Dialect::BASH
+        };
+        let error = |content: &str, dialect: &Dialect| {
+            tokenize_parameter_expansion(&chars(content), 0, dialect)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            "`@Q` requires bash 4.4 (targeting 4.2) at index 3",
+            error("${x@Q}", &bash_4_2)
+        );
+        assert_eq!(
+            "`@U` requires bash 5.1 (targeting 4.2) at index 10",
+            error("${array[@]@U}", &bash_4_2)
+        );
+        assert_eq!(
+            "`^` requires bash 4.0 (targeting 3.2) at index 6",
+            error("${name^^}", &bash_3_2)
+        );
+        assert_eq!(
+            "`,` requires bash 4.0 (targeting 3.2) at index 3",
+            error("${@,}", &bash_3_2)
+        );
+        assert_eq!(
+            "`@a` requires bash 4.4 (targeting 4.2) at index 8",
+            error("${x:-${y@a}}", &bash_4_2)
+        );
+        assert_eq!(
+            6,
+            tokenize_parameter_expansion(&chars("${x@Q}"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            9,
+            tokenize_parameter_expansion(&chars("${name^^}"), 0, &bash_4_2).unwrap()
+        );
+        for content in [
+            "${x}",
+            "${#x}",
+            "${!x}",
+            "${x:-a^b}",
+            "${x#@Q}",
+            "${@}",
+            "${x[0]}",
+        ] {
+            assert_eq!(
+                content.len(),
+                tokenize_parameter_expansion(&chars(content), 0, &bash_3_2).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,733 @@
+use crate::dialect::Dialect;
+use crate::lexer::dollar_single_quotes::{
+    decode_dollar_single_quotes, tokenize_dollar_single_quotes,
+};
+use crate::lexer::nested::MAX_DEPTH;
+use crate::lexer::word::tokenize_word;
+use std::collections::HashMap;
+
+/// tokenize_pattern
+/// > [3.5.8.1 Pattern Matching](https://www.gnu.org/software/bash/manual/bash.html#Pattern-Matching)
+/// > Any character that appears in a pattern, other than the special pattern characters described below,
+/// > matches itself. [...] The special pattern characters must be quoted if they are to be matched literally.
+///
+/// A word (see [tokenize_word]) with an unquoted `*`, `?` or bracket expression `[...]` in it, which bash
+/// replaces by the file names it matches. With `dialect.extglob` that includes the extended patterns
+/// `?(...)`, `*(...)`, `+(...)`, `@(...)` and `!(...)`. Returns `start` for a word without any.
+pub fn tokenize_pattern(content: &[char], start: usize, dialect: &Dialect) -> usize {
+    let word_pointer = tokenize_word(content, start, dialect);
+    let word: String = content[start..word_pointer].iter().collect();
+    match Pattern::parse_with_dialect(&word, dialect).is_literal() {
+        true => start,
+        false => word_pointer,
+    }
+}
+
+/// Pattern
+/// The structure of a pattern, built from the source text of a word by [Pattern::parse]. Quoted characters
+/// are always part of a [PatternElement::Literal].
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pattern {
+    pub elements: Vec<PatternElement>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PatternElement {
+    Literal(String),
+    AnyString, // *
+    AnyChar,   // ?
+    Bracket(BracketExpression),
+    Extended(ExtendedPattern),
+}
+
+/// > [3.5.8.1 Pattern Matching](https://www.gnu.org/software/bash/manual/bash.html#Pattern-Matching)
+/// > If the extglob shell option is enabled using the shopt builtin, the shell recognizes several extended
+/// > pattern matching operators. In the following description, a pattern-list is a list of one or more
+/// > patterns separated by a ‘|’.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtendedPattern {
+    pub operator: ExtendedOperator,
+    pub alternatives: Vec<Pattern>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExtendedOperator {
+    ZeroOrOne,  // ?(pattern-list)
+    ZeroOrMore, // *(pattern-list)
+    OneOrMore,  // +(pattern-list)
+    One,        // @(pattern-list)
+    Not,        // !(pattern-list), anything except one of the patterns
+}
+
+/// `[abc]`, `[!a-z]` or `[^[:digit:]]`
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BracketExpression {
+    pub negated: bool,
+    pub items: Vec<BracketItem>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BracketItem {
+    Char(char), // Also `[=c=]` and `[.c.]`, there's no locale to tell equivalent characters apart
+    Range(char, char),
+    Class(CharacterClass),
+}
+
+/// `[:name:]` inside a bracket expression
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CharacterClass {
+    Alnum,
+    Alpha,
+    Ascii,
+    Blank,
+    Cntrl,
+    Digit,
+    Graph,
+    Lower,
+    Print,
+    Punct,
+    Space,
+    Upper,
+    Word,
+    Xdigit,
+}
+
+impl Pattern {
+    /// Parses the source text of a word : quotes and backslashes are removed, and the characters they quoted
+    /// match literally. Expansions like `$x` aren't expanded and match their own text.
+    pub fn parse(word: &str) -> Pattern {
+        Pattern::parse_with_dialect(word, &Dialect::BASH)
+    }
+
+    /// parse_with_dialect
+    /// Like [Pattern::parse], with `dialect.extglob` for the extended patterns : `!(*.txt)`
+    pub fn parse_with_dialect(word: &str, dialect: &Dialect) -> Pattern {
+        parse_elements(&unquote(word), dialect.extglob, 0)
+    }
+
+    /// Whether the pattern only matches its own text, i.e. it has no unquoted special pattern characters
+    pub fn is_literal(&self) -> bool {
+        self.elements
+            .iter()
+            .all(|element| matches!(element, PatternElement::Literal(_)))
+    }
+
+    /// matches
+    /// > [3.5.8.1 Pattern Matching](https://www.gnu.org/software/bash/manual/bash.html#Pattern-Matching)
+    ///
+    /// Matches `text` as a whole, the way `case` and `[[ == ]]` do : `*` and `?` also match `/` and a
+    /// leading `.`.
+    pub fn matches(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        Matcher::new(&self.elements, &text, text.len(), false).matches(0, 0)
+    }
+
+    /// matches_path
+    /// > When a pattern is used for filename expansion, the character ‘.’ at the start of a filename or
+    /// > immediately following a slash must be matched explicitly, unless the shell option dotglob is set.
+    /// > In order to match the filenames ‘.’ and ‘..’, the pattern must begin with ‘.’
+    ///
+    /// Like [Pattern::matches], but `*`, `?` and bracket expressions never match a `/`, nor a `.` that starts
+    /// a file name.
+    pub fn matches_path(&self, path: &str) -> bool {
+        let path: Vec<char> = path.chars().collect();
+        Matcher::new(&self.elements, &path, path.len(), true).matches(0, 0)
+    }
+}
+
+impl BracketExpression {
+    pub fn matches(&self, char: char) -> bool {
+        let found = self.items.iter().any(|item| match item {
+            BracketItem::Char(item) => *item == char,
+            BracketItem::Range(first, last) => (*first..=*last).contains(&char),
+            BracketItem::Class(class) => class.matches(char),
+        });
+        found != self.negated
+    }
+}
+
+impl CharacterClass {
+    /// The class called `name` in `[:name:]`
+    pub fn from_name(name: &str) -> Option<CharacterClass> {
+        let class = match name {
+            "alnum" => CharacterClass::Alnum,
+            "alpha" => CharacterClass::Alpha,
+            "ascii" => CharacterClass::Ascii,
+            "blank" => CharacterClass::Blank,
+            "cntrl" => CharacterClass::Cntrl,
+            "digit" => CharacterClass::Digit,
+            "graph" => CharacterClass::Graph,
+            "lower" => CharacterClass::Lower,
+            "print" => CharacterClass::Print,
+            "punct" => CharacterClass::Punct,
+            "space" => CharacterClass::Space,
+            "upper" => CharacterClass::Upper,
+            "word" => CharacterClass::Word,
+            "xdigit" => CharacterClass::Xdigit,
+            _ => return None,
+        };
+        Some(class)
+    }
+
+    pub fn matches(&self, char: char) -> bool {
+        match self {
+            CharacterClass::Alnum => char.is_alphanumeric(),
+            CharacterClass::Alpha => char.is_alphabetic(),
+            CharacterClass::Ascii => char.is_ascii(),
+            CharacterClass::Blank => char == ' ' || char == '\t',
+            CharacterClass::Cntrl => char.is_control(),
+            CharacterClass::Digit => char.is_ascii_digit(),
+            CharacterClass::Graph => !char.is_control() && !char.is_whitespace(),
+            CharacterClass::Lower => char.is_lowercase(),
+            CharacterClass::Print => !char.is_control(),
+            CharacterClass::Punct => char.is_ascii_punctuation(),
+            CharacterClass::Space => char.is_whitespace(),
+            CharacterClass::Upper => char.is_uppercase(),
+            CharacterClass::Word => char.is_alphanumeric() || char == '_',
+            CharacterClass::Xdigit => char.is_ascii_hexdigit(),
+        }
+    }
+}
+
+/// Whether `elements[element..]` matches `text[pointer..limit]`, remembering the answers so a pattern like
+/// `*a*a*a*b` doesn't take exponential time. The alternatives of an extended pattern are matched against
+/// parts of the same text, so the `.` and `/` rules of [Pattern::matches_path] see the whole path.
+struct Matcher<'a> {
+    elements: &'a [PatternElement],
+    text: &'a [char],
+    limit: usize,
+    path: bool,
+    memo: HashMap<(usize, usize), bool>,
+    repeats: HashMap<(usize, usize), bool>,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(
+        elements: &'a [PatternElement],
+        text: &'a [char],
+        limit: usize,
+        path: bool,
+    ) -> Matcher<'a> {
+        Matcher {
+            elements,
+            text,
+            limit,
+            path,
+            memo: HashMap::new(),
+            repeats: HashMap::new(),
+        }
+    }
+
+    fn matches(&mut self, element: usize, pointer: usize) -> bool {
+        if let Some(matched) = self.memo.get(&(element, pointer)) {
+            return *matched;
+        }
+        let elements = self.elements;
+        let matched = match elements.get(element) {
+            None => pointer == self.limit,
+            Some(PatternElement::Literal(literal)) => {
+                let length = literal.chars().count();
+                self.limit >= pointer + length
+                    && literal
+                        .chars()
+                        .eq(self.text[pointer..pointer + length].iter().copied())
+                    && self.matches(element + 1, pointer + length)
+            }
+            Some(PatternElement::AnyString) => {
+                self.matches(element + 1, pointer)
+                    || (self.is_wildcard_match(pointer) && self.matches(element, pointer + 1))
+            }
+            Some(PatternElement::AnyChar) => {
+                self.is_wildcard_match(pointer) && self.matches(element + 1, pointer + 1)
+            }
+            Some(PatternElement::Bracket(bracket)) => {
+                self.is_wildcard_match(pointer)
+                    && bracket.matches(self.text[pointer])
+                    && self.matches(element + 1, pointer + 1)
+            }
+            Some(PatternElement::Extended(extended)) => {
+                let alternatives = &extended.alternatives;
+                match extended.operator {
+                    ExtendedOperator::One => (pointer..=self.limit).any(|end| {
+                        self.alternatives_match(alternatives, pointer, end)
+                            && self.matches(element + 1, end)
+                    }),
+                    ExtendedOperator::ZeroOrOne => {
+                        self.matches(element + 1, pointer)
+                            || (pointer..=self.limit).any(|end| {
+                                self.alternatives_match(alternatives, pointer, end)
+                                    && self.matches(element + 1, end)
+                            })
+                    }
+                    ExtendedOperator::ZeroOrMore => {
+                        self.matches(element + 1, pointer) || self.repeats(element, pointer)
+                    }
+                    ExtendedOperator::OneOrMore => self.repeats(element, pointer),
+                    ExtendedOperator::Not => (pointer..=self.limit).any(|end| {
+                        (pointer..end).all(|pointer| self.is_wildcard_match(pointer))
+                            && !self.alternatives_match(alternatives, pointer, end)
+                            && self.matches(element + 1, end)
+                    }),
+                }
+            }
+        };
+        self.memo.insert((element, pointer), matched);
+        matched
+    }
+
+    /// Whether the extended pattern at `element` matches one or more times from `pointer`, followed by the
+    /// rest of the pattern.
+    fn repeats(&mut self, element: usize, pointer: usize) -> bool {
+        if let Some(matched) = self.repeats.get(&(element, pointer)) {
+            return *matched;
+        }
+        let Some(PatternElement::Extended(extended)) = self.elements.get(element) else {
+            return false;
+        };
+        let matched = (pointer..=self.limit).any(|end| {
+            self.alternatives_match(&extended.alternatives, pointer, end)
+                && (self.matches(element + 1, end) || (end > pointer && self.repeats(element, end)))
+        });
+        self.repeats.insert((element, pointer), matched);
+        matched
+    }
+
+    /// Whether one of `alternatives` matches `text[start..end]`
+    fn alternatives_match(&self, alternatives: &[Pattern], start: usize, end: usize) -> bool {
+        alternatives.iter().any(|alternative| {
+            Matcher::new(&alternative.elements, self.text, end, self.path).matches(0, start)
+        })
+    }
+
+    /// Whether the character at `pointer` can be matched by something other than a literal
+    fn is_wildcard_match(&self, pointer: usize) -> bool {
+        if pointer >= self.limit {
+            return false;
+        }
+        match self.text.get(pointer) {
+            None => false,
+            Some(_) if !self.path => true,
+            Some('/') => false,
+            Some('.') => pointer != 0 && self.text[pointer - 1] != '/',
+            Some(_) => true,
+        }
+    }
+}
+
+/// Quote removal, keeping track of which characters were quoted : `'a*'\?"b"` is `a*?b`, all quoted.
+fn unquote(word: &str) -> Vec<(char, bool)> {
+    let chars: Vec<char> = word.chars().collect();
+    let mut unquoted = Vec::new();
+    let mut pointer = 0;
+    while let Some(&char) = chars.get(pointer) {
+        match char {
+            '\\' => match chars.get(pointer + 1) {
+                Some('\n') => pointer += 2, // Line continuation
+                Some(escaped) => {
+                    unquoted.push((*escaped, true));
+                    pointer += 2;
+                }
+                None => {
+                    unquoted.push(('\\', true));
+                    pointer += 1;
+                }
+            },
+            '\'' => {
+                let end = (pointer + 1..chars.len())
+                    .find(|end| chars[*end] == '\'')
+                    .unwrap_or(chars.len());
+                unquoted.extend(chars[pointer + 1..end].iter().map(|char| (*char, true)));
+                pointer = end + 1;
+            }
+            '"' => {
+                pointer += 1;
+                while let Some(&char) = chars.get(pointer) {
+                    match (char, chars.get(pointer + 1)) {
+                        ('"', _) => break,
+                        ('\\', Some('\n')) => pointer += 1,
+                        ('\\', Some(escaped @ ('$' | '`' | '"' | '\\'))) => {
+                            unquoted.push((*escaped, true));
+                            pointer += 1;
+                        }
+                        (char, _) => unquoted.push((char, true)),
+                    }
+                    pointer += 1;
+                }
+                pointer += 1;
+            }
+            '$' if chars.get(pointer + 1) == Some(&'\'') => {
+                let end = tokenize_dollar_single_quotes(&chars, pointer, &Dialect::BASH)
+                    .unwrap_or(chars.len())
+                    .min(chars.len());
+                let quoted: String = chars[pointer..end].iter().collect();
+                let decoded = decode_dollar_single_quotes(&quoted);
+                unquoted.extend(
+                    String::from_utf8_lossy(&decoded)
+                        .chars()
+                        .map(|char| (char, true)),
+                );
+                pointer = end;
+            }
+            '$' if chars.get(pointer + 1) == Some(&'"') => pointer += 1, // $"..." is a double-quoted string
+            char => {
+                unquoted.push((char, false));
+                pointer += 1;
+            }
+        }
+    }
+    unquoted
+}
+
+/// The elements of a pattern from its unquoted characters, `depth` extended patterns deep. Past [MAX_DEPTH] the
+/// operator and `(` match themselves, like when they're unterminated.
+fn parse_elements(chars: &[(char, bool)], extglob: bool, depth: usize) -> Pattern {
+    let mut elements: Vec<PatternElement> = Vec::new();
+    let mut pointer = 0;
+    while let Some(&(char, quoted)) = chars.get(pointer) {
+        pointer += 1;
+        let is_extended = matches!(
+            (char, quoted, chars.get(pointer)),
+            ('?' | '*' | '+' | '@' | '!', false, Some(('(', false)))
+        );
+        let extended = match extglob && is_extended && depth < MAX_DEPTH {
+            true => parse_extended(chars, pointer, char, depth),
+            false => None, // Without extglob, or unterminated, the operator and `(` match themselves
+        };
+        if let Some((extended, extended_pointer)) = extended {
+            elements.push(PatternElement::Extended(extended));
+            pointer = extended_pointer;
+            continue;
+        }
+        let element = match (char, quoted) {
+            ('*', false) if elements.last() == Some(&PatternElement::AnyString) => continue,
+            ('*', false) => PatternElement::AnyString,
+            ('?', false) => PatternElement::AnyChar,
+            ('[', false) => match parse_bracket(chars, pointer) {
+                Some((bracket, bracket_pointer)) => {
+                    pointer = bracket_pointer;
+                    PatternElement::Bracket(bracket)
+                }
+                None => PatternElement::Literal(String::from('[')),
+            },
+            _ => PatternElement::Literal(String::from(char)),
+        };
+        match (elements.last_mut(), element) {
+            (Some(PatternElement::Literal(literal)), PatternElement::Literal(next)) => {
+                literal.push_str(&next)
+            }
+            (_, element) => elements.push(element),
+        }
+    }
+    Pattern { elements }
+}
+
+/// The pattern-list of an extended pattern, `start` points at its `(`. Returns the pointer after its `)`,
+/// `None` when it isn't terminated and the operator and `(` match themselves.
+fn parse_extended(
+    chars: &[(char, bool)],
+    start: usize,
+    operator: char,
+    depth: usize,
+) -> Option<(ExtendedPattern, usize)> {
+    let operator = match operator {
+        '?' => ExtendedOperator::ZeroOrOne,
+        '*' => ExtendedOperator::ZeroOrMore,
+        '+' => ExtendedOperator::OneOrMore,
+        '@' => ExtendedOperator::One,
+        _ => ExtendedOperator::Not,
+    };
+    let mut alternatives = Vec::new();
+    let mut alternative_start = start + 1;
+    let mut parentheses = 0;
+    for pointer in start + 1..chars.len() {
+        match chars[pointer] {
+            ('(', false) => parentheses += 1,
+            (')', false) if parentheses > 0 => parentheses -= 1,
+            (')' | '|', false) if parentheses == 0 => {
+                alternatives.push(parse_elements(
+                    &chars[alternative_start..pointer],
+                    true,
+                    depth + 1,
+                ));
+                alternative_start = pointer + 1;
+                if chars[pointer].0 == ')' {
+                    let extended = ExtendedPattern {
+                        operator,
+                        alternatives,
+                    };
+                    return Some((extended, pointer + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The bracket expression after the `[` at `start - 1`, returns the pointer after its `]`. `None` when it
+/// isn't terminated, the `[` then matches itself.
+fn parse_bracket(chars: &[(char, bool)], start: usize) -> Option<(BracketExpression, usize)> {
+    let mut pointer = start;
+    let negated = matches!(chars.get(pointer), Some(('!' | '^', false)));
+    if negated {
+        pointer += 1;
+    }
+    let first = pointer; // A `]` right after `[` or `[!` is a member
+    let mut items = Vec::new();
+    loop {
+        let &(char, quoted) = chars.get(pointer)?;
+        if char == ']' && !quoted && pointer != first {
+            return Some((BracketExpression { negated, items }, pointer + 1));
+        }
+
+        // [:class:], [=c=] and [.c.] :
+        if let (('[', false), Some(&(delimiter @ (':' | '=' | '.'), false))) =
+            ((char, quoted), chars.get(pointer + 1))
+        {
+            let name_start = pointer + 2;
+            let end = (name_start..chars.len().saturating_sub(1))
+                .find(|end| chars[*end] == (delimiter, false) && chars[end + 1] == (']', false))?;
+            let name: String = chars[name_start..end]
+                .iter()
+                .map(|(char, _)| char)
+                .collect();
+            let item = match delimiter {
+                ':' => BracketItem::Class(CharacterClass::from_name(&name)?),
+                _ => {
+                    let mut name_chars = name.chars();
+                    match (name_chars.next(), name_chars.next()) {
+                        (Some(char), None) => BracketItem::Char(char),
+                        _ => return None, // Multi-character collating elements need a locale
+                    }
+                }
+            };
+            items.push(item);
+            pointer = end + 2;
+            continue;
+        }
+
+        // a-z, but a `-` before the closing `]` is a member :
+        match (chars.get(pointer + 1), chars.get(pointer + 2)) {
+            (Some(('-', false)), Some(&(last, last_quoted))) if last != ']' || last_quoted => {
+                items.push(BracketItem::Range(char, last));
+                pointer += 3;
+            }
+            _ => {
+                items.push(BracketItem::Char(char));
+                pointer += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::lexer::chars;
+    use crate::lexer::pattern::{
+        tokenize_pattern, BracketExpression, BracketItem, CharacterClass, ExtendedOperator,
+        ExtendedPattern, Pattern, PatternElement,
+    };
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Pattern::parse(pattern).matches(text)
+    }
+
+    #[test]
+    fn test_tokenize_pattern() {
+        assert_eq!(5, tokenize_pattern(&chars("*.txt"), 0, &Dialect::BASH));
+        assert_eq!(
+            9,
+            tokenize_pattern(&chars("ls /etc/* x"), 3, &Dialect::BASH)
+        );
+        assert_eq!(8, tokenize_pattern(&chars("file[12] x"), 0, &Dialect::BASH));
+        assert_eq!(1, tokenize_pattern(&chars("?"), 0, &Dialect::BASH));
+        assert_eq!(0, tokenize_pattern(&chars("file.txt"), 0, &Dialect::BASH));
+        assert_eq!(0, tokenize_pattern(&chars("\\*.txt"), 0, &Dialect::BASH));
+        assert_eq!(0, tokenize_pattern(&chars("[ -f x ]"), 0, &Dialect::BASH));
+        assert_eq!(0, tokenize_pattern(&chars("[["), 0, &Dialect::BASH));
+        assert_eq!(0, tokenize_pattern(&chars("a'*'"), 0, &Dialect::BASH));
+        assert_eq!(0, tokenize_pattern(&chars(""), 0, &Dialect::BASH));
+
+        let extglob = Dialect {
+            extglob: true,
+            ..Dialect::BASH
+        };
+        assert_eq!(8, tokenize_pattern(&chars("!(*.txt) x"), 0, &extglob));
+        assert_eq!(6, tokenize_pattern(&chars("@(a|b)"), 0, &extglob));
+        assert_eq!(0, tokenize_pattern(&chars("@(a|b)"), 0, &Dialect::BASH));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            vec![
+                PatternElement::Literal(String::from("/etc/")),
+                PatternElement::AnyString,
+                PatternElement::Literal(String::from(".con")),
+                PatternElement::AnyChar,
+            ],
+            Pattern::parse("/etc/**.con?").elements
+        );
+        assert_eq!(
+            vec![
+                PatternElement::Literal(String::from("a*?b[c]$x")),
+                PatternElement::AnyString,
+            ],
+            Pattern::parse("'a*'\\?\"b[c]\"$x*").elements
+        );
+        assert_eq!(
+            vec![PatternElement::Bracket(BracketExpression {
+                negated: true,
+                items: vec![
+                    BracketItem::Char(']'),
+                    BracketItem::Range('a', 'z'),
+                    BracketItem::Class(CharacterClass::Digit),
+                    BracketItem::Char('x'),
+                    BracketItem::Char('-'),
+                ]
+            })],
+            Pattern::parse("[!]a-z[:digit:][=x=]-]").elements
+        );
+        assert!(Pattern::parse("$'\\x2a'").is_literal());
+        assert!(Pattern::parse("[").is_literal());
+        assert!(Pattern::parse("[]").is_literal());
+        assert!(!Pattern::parse("[[:alpha:]]").is_literal());
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("*.txt", "notes.txt"));
+        assert!(matches("*.txt", ".txt"));
+        assert!(!matches("*.txt", "notes.txt.bak"));
+        assert!(matches("a?c", "abc"));
+        assert!(!matches("a?c", "ac"));
+        assert!(matches("*", ""));
+        assert!(matches("", ""));
+        assert!(!matches("", "a"));
+        assert!(matches("/etc/*", "/etc/ssh/sshd_config"));
+        assert!(matches("*a*a*a*a*a*b", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaab"));
+        assert!(!matches("*a*a*a*a*a*b", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"));
+        assert!(matches("h\u{e9}?lo", "h\u{e9}llo"));
+    }
+
+    #[test]
+    fn test_matches_brackets() {
+        assert!(matches("file[12]", "file1"));
+        assert!(!matches("file[12]", "file3"));
+        assert!(matches("[!a]", "b"));
+        assert!(matches("[^a]", "b"));
+        assert!(!matches("[!a]", "a"));
+        assert!(matches("[a-c]x", "bx"));
+        assert!(!matches("[c-a]", "b"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("[a-]", "-"));
+        assert!(matches("[[:alpha:]][[:digit:]]", "x1"));
+        assert!(!matches("[[:upper:]]", "x"));
+        assert!(matches("[[:space:][:punct:]]", "!"));
+        assert!(matches("[", "["));
+        assert!(matches("a[", "a["));
+    }
+
+    #[test]
+    fn test_matches_quoted() {
+        assert!(matches("'*'", "*"));
+        assert!(!matches("'*'", "a"));
+        assert!(matches("\"*\"*", "*abc"));
+        assert!(!matches("\"*\"*", "abc"));
+        assert!(matches("\\[a]", "[a]"));
+        assert!(!matches("\\[a]", "a"));
+        assert!(matches("[\"!\"a]", "!"));
+        assert!(matches("$'\\t'*", "\tx"));
+    }
+
+    #[test]
+    fn test_parse_extended() {
+        let extglob = Dialect {
+            extglob: true,
+            ..Dialect::BASH
+        };
+        assert_eq!(
+            vec![
+                PatternElement::Extended(ExtendedPattern {
+                    operator: ExtendedOperator::Not,
+                    alternatives: vec![
+                        Pattern::parse("*.txt"),
+                        Pattern::parse_with_dialect("@(a|b)", &extglob),
+                    ]
+                }),
+                PatternElement::Literal(String::from("x")),
+            ],
+            Pattern::parse_with_dialect("!(*.txt|@(a|b))x", &extglob).elements
+        );
+        assert_eq!(
+            vec![PatternElement::Extended(ExtendedPattern {
+                operator: ExtendedOperator::ZeroOrMore,
+                alternatives: vec![Pattern::parse(""), Pattern::parse("'|'")]
+            })],
+            Pattern::parse_with_dialect("*(|'|')", &extglob).elements
+        );
+        assert!(Pattern::parse("@(a|b)").is_literal());
+        assert!(Pattern::parse_with_dialect("@(a|b", &extglob).is_literal());
+        assert!(Pattern::parse_with_dialect("'@'(a)", &extglob).is_literal());
+        assert!(!Pattern::parse_with_dialect("@(a)", &extglob).is_literal());
+    }
+
+    #[test]
+    fn test_matches_extended() {
+        let extglob = Dialect {
+            extglob: true,
+            ..Dialect::BASH
+        };
+        let matches = |pattern: &str, text: &str| {
+            Pattern::parse_with_dialect(pattern, &extglob).matches(text)
+        };
+        assert!(matches("@(a|bc)", "bc"));
+        assert!(!matches("@(a|bc)", "abc"));
+        assert!(matches("?(a)b", "b"));
+        assert!(matches("?(a)b", "ab"));
+        assert!(!matches("?(a)b", "aab"));
+        assert!(matches("*(ab|c)", ""));
+        assert!(matches("*(ab|c)", "abcab"));
+        assert!(!matches("*(ab|c)", "abca"));
+        assert!(matches("+(ab|c)d", "ccabd"));
+        assert!(!matches("+(ab|c)d", "d"));
+        assert!(matches("!(*.txt)", "notes.md"));
+        assert!(!matches("!(*.txt)", "notes.txt"));
+        assert!(matches("!(a)", ""));
+        assert!(matches("x!(a|b)y", "xcy"));
+        assert!(!matches("x!(a|b)y", "xby"));
+        assert!(matches("@(+([0-9])|[a-z])", "123"));
+        assert!(matches("+(a|aa|aaa)b", "aaaaaaaaaaaaaaaaaaaaaaaaab"));
+        assert!(!matches("+(a|aa|aaa)b", "aaaaaaaaaaaaaaaaaaaaaaaaa"));
+
+        let path = |pattern: &str, path: &str| {
+            Pattern::parse_with_dialect(pattern, &extglob).matches_path(path)
+        };
+        assert!(path("/etc/!(passwd)", "/etc/group"));
+        assert!(!path("/etc/!(passwd)", "/etc/ssh/config"));
+        assert!(!path("/etc/!(passwd)", "/etc/.hidden"));
+        assert!(path("@(a|b)/*", "a/x"));
+    }
+
+    #[test]
+    fn test_matches_path() {
+        let pattern = Pattern::parse("/etc/*");
+        assert!(pattern.matches_path("/etc/passwd"));
+        assert!(!pattern.matches_path("/etc/ssh/sshd_config"));
+        assert!(!pattern.matches_path("/etc/.hidden"));
+        assert!(Pattern::parse("/etc/.*").matches_path("/etc/.hidden"));
+        assert!(Pattern::parse("*/*").matches_path("a/b"));
+        assert!(!Pattern::parse("a?b").matches_path("a/b"));
+        assert!(!Pattern::parse("a[/]b").matches_path("a/b"));
+        assert!(!Pattern::parse("?profile").matches_path(".profile"));
+        assert!(Pattern::parse("a.*").matches_path("a.b"));
+    }
+}
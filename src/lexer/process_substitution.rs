@@ -0,0 +1,75 @@
+use crate::dialect::Dialect;
+use crate::lexer::nested::tokenize_list;
+
+/// tokenize_process_substitution
+/// > [3.5.6 Process Substitution](https://www.gnu.org/software/bash/manual/bash.html#Process-Substitution)
+/// > Process substitution allows a process’s input or output to be referred to using a filename.
+/// > It takes the form of `<(list)` or `>(list)`
+///
+/// Like `$(list)` we look for the closing parenthesis, see [tokenize_list]. Without
+/// `dialect.process_substitution` these are a redirection followed by a subshell.
+pub fn tokenize_process_substitution(
+    content: &[char],
+    start: usize,
+    dialect: &Dialect,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut prefix = content.iter().skip(start).copied();
+    if !dialect.process_substitution
+        || !matches!(prefix.next(), Some('<' | '>'))
+        || prefix.next() != Some('(')
+    {
+        return Ok(start);
+    }
+    match tokenize_list(content, start + 2, dialect)? {
+        Some(end) => Ok(end + 1),
+        None => Err(format!("Unterminated process substitution at index {}", start).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::lexer::chars;
+    use crate::lexer::process_substitution::tokenize_process_substitution;
+
+    #[test]
+    pub fn test_tokenize_process_substitution_basic() {
+        assert_eq!(
+            5,
+            tokenize_process_substitution(&chars("<(ls)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            17,
+            tokenize_process_substitution(&chars("diff <(ls) >(cat)"), 11, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_process_substitution(&chars("$(ls)"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            0,
+            tokenize_process_substitution(&chars("< (ls)"), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_process_substitution_nested() {
+        assert_eq!(
+            16,
+            tokenize_process_substitution(&chars("<(echo ')' $(a))"), 0, &Dialect::BASH).unwrap()
+        );
+        assert_eq!(
+            8,
+            tokenize_process_substitution(&chars("<((a) b)x"), 0, &Dialect::BASH).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_tokenize_process_substitution_dialects() {
+        assert_eq!(
+            0,
+            tokenize_process_substitution(&chars("<(ls)"), 0, &Dialect::POSIX).unwrap()
+        );
+        assert!(tokenize_process_substitution(&chars("<(ls"), 0, &Dialect::BASH).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::dialect::Dialect;
+use crate::lexer::dollar_single_quotes::tokenize_dollar_single_quotes;
+use crate::lexer::double_quotes::{tokenize_dollar_double_quotes, tokenize_double_quotes};
+use crate::lexer::nested::tokenize_expansion;
+use crate::lexer::single_quotes::tokenize_single_quotes;
+use crate::tokens::span::Span;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RegexTokenKind {
+    Literal,     // A character that matches itself, escaped ones included : `a`, `\.`
+    Quoted,      // A quoted string, matched literally : `'a.b'`, `"$x"`, `$'\t'`
+    Expansion,   // An unquoted expansion, its value is part of the regular expression : `$re`
+    AnyChar,     // .
+    Start,       // ^
+    End,         // $
+    Bracket,     // [a-z], [^[:space:]]
+    GroupStart,  // (
+    GroupEnd,    // )
+    Alternation, // |
+    Quantifier,  // *, +, ?, {2}, {2,}, {2,5}
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegexToken {
+    pub kind: RegexTokenKind,
+    pub text: String,
+    pub span: Span,
+}
+
+/// tokenize_regex
+/// > [3.2.5.2 Conditional Constructs](https://www.gnu.org/software/bash/manual/bash.html#Conditional-Constructs)
+/// > An additional binary operator, ‘=~’, is available [...] the string to the right of the operator is
+/// > considered a POSIX extended regular expression pattern [...] Any part of the pattern may be quoted to
+/// > force the quoted portion to be matched literally.
+///
+/// Splits the right-hand side of `=~` into the elements of the extended regular expression, `offset` is
+/// where `word` is located and is added to the spans. Expansions are kept whole, their value is only known
+/// when the condition runs.
+pub fn tokenize_regex(word: &str, offset: usize) -> Vec<RegexToken> {
+    let chars: Vec<char> = word.chars().collect();
+    let offsets: Vec<usize> = word
+        .char_indices()
+        .map(|(position, _)| position)
+        .chain(std::iter::once(word.len()))
+        .collect();
+    let dialect = &Dialect::BASH;
+    let mut tokens = Vec::new();
+    let mut pointer = 0;
+    while pointer < chars.len() {
+        let quoted = [
+            tokenize_single_quotes(&chars, pointer),
+            tokenize_dollar_single_quotes(&chars, pointer, dialect),
+            tokenize_dollar_double_quotes(&chars, pointer, dialect),
+            tokenize_double_quotes(&chars, pointer, dialect),
+        ]
+        .into_iter()
+        .find_map(|result| result.ok().filter(|end| *end != pointer));
+        let (kind, end) = match quoted {
+            Some(end) => (RegexTokenKind::Quoted, end),
+            None => match chars[pointer] {
+                '$' | '`' => match tokenize_expansion(&chars, pointer, dialect) {
+                    Ok(end) if end != pointer => (RegexTokenKind::Expansion, end),
+                    _ if chars[pointer] == '$' => (RegexTokenKind::End, pointer + 1),
+                    _ => (RegexTokenKind::Literal, pointer + 1),
+                },
+                '\\' => (RegexTokenKind::Literal, (pointer + 2).min(chars.len())),
+                '.' => (RegexTokenKind::AnyChar, pointer + 1),
+                '^' => (RegexTokenKind::Start, pointer + 1),
+                '(' => (RegexTokenKind::GroupStart, pointer + 1),
+                ')' => (RegexTokenKind::GroupEnd, pointer + 1),
+                '|' => (RegexTokenKind::Alternation, pointer + 1),
+                '*' | '+' | '?' => (RegexTokenKind::Quantifier, pointer + 1),
+                '{' => match interval_end(&chars, pointer) {
+                    Some(end) => (RegexTokenKind::Quantifier, end),
+                    None => (RegexTokenKind::Literal, pointer + 1),
+                },
+                '[' => match bracket_end(&chars, pointer) {
+                    Some(end) => (RegexTokenKind::Bracket, end),
+                    None => (RegexTokenKind::Literal, pointer + 1),
+                },
+                _ => (RegexTokenKind::Literal, pointer + 1),
+            },
+        };
+        let span = Span::new(offset + offsets[pointer], offset + offsets[end]);
+        tokens.push(RegexToken {
+            kind,
+            text: word[offsets[pointer]..offsets[end]].to_string(),
+            span,
+        });
+        pointer = end;
+    }
+    tokens
+}
+
+/// The end of a bounded repetition `{n}`, `{n,}` or `{n,m}`, a `{` that doesn't start one is a literal
+fn interval_end(chars: &[char], start: usize) -> Option<usize> {
+    let close = start + chars[start..].iter().position(|char| *char == '}')?;
+    let inner: String = chars[start + 1..close].iter().collect();
+    let (minimum, maximum) = inner.split_once(',').unwrap_or((&inner, "0"));
+    let is_number = |text: &str| text.chars().all(|char| char.is_ascii_digit());
+    match !minimum.is_empty() && is_number(minimum) && is_number(maximum) {
+        true => Some(close + 1),
+        false => None,
+    }
+}
+
+/// The end of a bracket expression, a `]` right after the `[` or `[^` is part of the list and character
+/// classes like `[:alpha:]` can contain a `]`
+fn bracket_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut pointer = start + 1;
+    if chars.get(pointer) == Some(&'^') {
+        pointer += 1;
+    }
+    if chars.get(pointer) == Some(&']') {
+        pointer += 1;
+    }
+    while let Some(char) = chars.get(pointer) {
+        match (char, chars.get(pointer + 1)) {
+            (']', _) => return Some(pointer + 1),
+            ('[', Some(delimiter @ (':' | '=' | '.'))) => {
+                let close = (pointer + 2..chars.len().saturating_sub(1))
+                    .find(|close| chars[*close] == *delimiter && chars[close + 1] == ']')?;
+                pointer = close + 2;
+            }
+            _ => pointer += 1,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::regex::{tokenize_regex, RegexTokenKind};
+    use crate::tokens::span::Span;
+
+    fn kinds(word: &str) -> Vec<(RegexTokenKind, String)> {
+        tokenize_regex(word, 0)
+            .into_iter()
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_regex() {
+        use RegexTokenKind::*;
+        assert_eq!(
+            vec![
+                (Start, String::from("^")),
+                (GroupStart, String::from("(")),
+                (Literal, String::from("a")),
+                (Alternation, String::from("|")),
+                (Literal, String::from("b")),
+                (GroupEnd, String::from(")")),
+                (Quantifier, String::from("+")),
+                (AnyChar, String::from(".")),
+                (Quantifier, String::from("*")),
+                (Literal, String::from("\\.")),
+                (Bracket, String::from("[0-9]")),
+                (Quantifier, String::from("{2,3}")),
+                (End, String::from("$")),
+            ],
+            kinds("^(a|b)+.*\\.[0-9]{2,3}$")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_regex_brackets() {
+        use RegexTokenKind::*;
+        assert_eq!(vec![(Bracket, String::from("[]a]"))], kinds("[]a]"));
+        assert_eq!(vec![(Bracket, String::from("[^]a]"))], kinds("[^]a]"));
+        assert_eq!(
+            vec![(Bracket, String::from("[[:space:]x]"))],
+            kinds("[[:space:]x]")
+        );
+        assert_eq!(
+            vec![(Literal, String::from("[")), (Literal, String::from("a"))],
+            kinds("[a")
+        );
+        assert_eq!(
+            vec![
+                (Literal, String::from("{")),
+                (Literal, String::from("a")),
+                (Literal, String::from("}")),
+            ],
+            kinds("{a}")
+        );
+        assert_eq!(vec![(Quantifier, String::from("{3}"))], kinds("{3}"));
+    }
+
+    #[test]
+    fn test_tokenize_regex_quotes_and_expansions() {
+        use RegexTokenKind::*;
+        assert_eq!(
+            vec![
+                (Quoted, String::from("'a.b'")),
+                (Quoted, String::from("\"$x\"")),
+                (Expansion, String::from("$re")),
+                (Expansion, String::from("${suffix}")),
+                (Quoted, String::from("$'\\t'")),
+                (Expansion, String::from("$(id)")),
+                (End, String::from("$")),
+            ],
+            kinds("'a.b'\"$x\"$re${suffix}$'\\t'$(id)$")
+        );
+        let tokens = tokenize_regex("é.$x", 10);
+        assert_eq!(Span::new(12, 13), tokens[1].span);
+        assert_eq!(Span::new(13, 15), tokens[2].span);
+    }
+}
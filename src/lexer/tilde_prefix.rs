@@ -0,0 +1,130 @@
+use crate::dialect::Dialect;
+use crate::lexer::metacharacter::is_metacharacter;
+
+/// tokenize_tilde_prefix
+/// > [3.5.2 Tilde Expansion](https://www.gnu.org/software/bash/manual/bash.html#Tilde-Expansion)
+/// > If a word begins with an unquoted tilde character (‘~’), all of the characters up to the first unquoted
+/// > slash (or all characters, if there is no unquoted slash) are considered a tilde-prefix. If none of the
+/// > characters in the tilde-prefix are quoted, the characters in the tilde-prefix following the tilde are
+/// > treated as a possible login name.
+/// > [...] Each variable assignment is checked for unquoted tilde-prefixes immediately following a ‘:’ or the
+/// > first ‘=’.
+///
+/// `~`, `~user`, `~+`, `~-` and `~N` (`~+N`, `~-N`), the rest of the word is a word of its own. We look back
+/// to see where the `~` is : a tilde in the middle of a word (`a~`) or after a quote (`"a"~`) is a literal.
+pub fn tokenize_tilde_prefix(content: &[char], start: usize, _dialect: &Dialect) -> usize {
+    if content.get(start) != Some(&'~') {
+        return start;
+    }
+    let in_assignment = match start.checked_sub(1).map(|previous| content[previous]) {
+        None => false,
+        Some('=' | ':') if is_assignment(&content[word_start(content, start)..start]) => true,
+        // `a\ ~` : The escaped space is part of the word
+        Some(previous)
+            if is_word_boundary(previous) && (start < 2 || content[start - 2] != '\\') =>
+        {
+            false
+        }
+        Some(_) => return start,
+    };
+    let mut pointer = start + 1;
+    while let Some(&char) = content.get(pointer) {
+        if char == '/' || (char == ':' && in_assignment) || is_word_boundary(char) {
+            break;
+        }
+        pointer += 1;
+    }
+    let prefix: String = content[start + 1..pointer].iter().collect();
+    match is_tilde_prefix(&prefix) {
+        true => pointer,
+        false => start,
+    }
+}
+
+/// Whether `prefix`, what comes after the `~`, is empty, a login name, a directory stack entry (`+N`, `-N`,
+/// `N`) or `+`/`-` for `$PWD`/`$OLDPWD`. Anything quoted or expanded makes it a literal.
+fn is_tilde_prefix(prefix: &str) -> bool {
+    let number = prefix.strip_prefix(['+', '-']).unwrap_or(prefix);
+    number.is_empty()
+        || number.chars().all(|char| char.is_ascii_digit())
+        || prefix
+            .chars()
+            .all(|char| char.is_alphanumeric() || ['_', '.', '-'].contains(&char))
+}
+
+fn is_word_boundary(char: char) -> bool {
+    char.is_ascii_whitespace() || is_metacharacter(char)
+}
+
+/// Where the word that `end` is in starts, only looking at unquoted boundaries
+fn word_start(chars: &[char], end: usize) -> usize {
+    (0..end)
+        .rev()
+        .find(|pointer| is_word_boundary(chars[*pointer]))
+        .map_or(0, |boundary| boundary + 1)
+}
+
+/// Whether the word so far is `NAME=`, `NAME+=` or `NAME[subscript]=`, possibly followed by more of the value
+fn is_assignment(word: &[char]) -> bool {
+    let Some(equals) = word.iter().position(|char| *char == '=') else {
+        return false;
+    };
+    let name: String = word[..equals].iter().collect();
+    let name = name.strip_suffix('+').unwrap_or(&name);
+    let name = match name.find('[') {
+        Some(bracket) if name.ends_with(']') => &name[..bracket],
+        _ => name,
+    };
+    name.starts_with(|char: char| char.is_ascii_alphabetic() || char == '_')
+        && name
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::lexer::chars;
+    use crate::lexer::tilde_prefix::tokenize_tilde_prefix;
+
+    fn tilde(content: &str, start: usize) -> usize {
+        tokenize_tilde_prefix(&chars(content), start, &Dialect::BASH)
+    }
+
+    #[test]
+    fn test_tokenize_tilde_prefix() {
+        assert_eq!(1, tilde("~", 0));
+        assert_eq!(1, tilde("~/bin", 0));
+        assert_eq!(9, tilde("ls ~alice/x", 3));
+        assert_eq!(5, tilde("cd ~+", 3));
+        assert_eq!(5, tilde("cd ~-", 3));
+        assert_eq!(6, tilde("cd ~-2", 3));
+        assert_eq!(5, tilde("cd ~3/x", 3));
+        assert_eq!(6, tilde("a;~bob|b", 2));
+        assert_eq!(10, tilde("~first.l-n", 0));
+    }
+
+    #[test]
+    fn test_tokenize_tilde_prefix_assignments() {
+        assert_eq!(6, tilde("PATH=~/bin", 5));
+        assert_eq!(10, tilde("PATH=/a:~b:~/c", 8));
+        assert_eq!(12, tilde("PATH=/a:~b:~/c", 11));
+        assert_eq!(4, tilde("a+=~", 3));
+        assert_eq!(7, tilde("a[1]=~x", 5));
+        assert_eq!(6, tilde("x a=~b", 4));
+        assert_eq!(6, tilde("echo=~", 5));
+    }
+
+    #[test]
+    fn test_tokenize_tilde_prefix_no_match() {
+        assert_eq!(1, tilde("a~", 1));
+        assert_eq!(3, tilde("\"a\"~", 3));
+        assert_eq!(0, tilde("~'root'", 0));
+        assert_eq!(0, tilde("~$USER", 0));
+        assert_eq!(0, tilde("~*", 0));
+        assert_eq!(0, tilde("x", 0));
+        assert_eq!(6, tilde("--opt=~", 6));
+        assert_eq!(2, tilde("a:~", 2));
+        assert_eq!(2, tilde("\\ ~", 2));
+    }
+}
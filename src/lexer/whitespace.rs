@@ -1,33 +1,66 @@
-pub fn tokenize_whitespace(content: &String, start: usize) -> usize {
+/// Newlines are left out, they separate commands and get their own token, see [tokenize_newline]
+pub fn tokenize_whitespace(content: &[char], start: usize) -> usize {
     let mut pointer: usize = start;
-    let mut char = content.chars().nth(pointer).unwrap();
-    while char.is_ascii_whitespace() {
-        pointer += 1;
-        if pointer >= content.len() {
+    while let Some(char) = content.get(pointer).copied() {
+        if !char.is_ascii_whitespace() || char == '\n' {
             break;
         }
-
-        char = content.chars().nth(pointer).unwrap();
+        pointer += 1;
     }
     pointer
 }
 
+/// tokenize_newline
+/// > [control operator] A token that performs a control function. It is a newline or one of the following: ...
+///
+/// Every newline is a token of its own, here-documents start right after the first one.
+pub fn tokenize_newline(content: &[char], start: usize) -> usize {
+    match content.get(start).copied() {
+        Some('\n') => start + 1,
+        _ => start,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::lexer::whitespace::tokenize_whitespace;
+    use crate::lexer::chars;
+    use crate::lexer::whitespace::{tokenize_newline, tokenize_whitespace};
 
     #[test]
     pub fn test_tokenize_whitespace_basic() {
-        assert_eq!(2, tokenize_whitespace(&String::from("NoWhitespace"), 2));
-        assert_eq!(0, tokenize_whitespace(&String::from("NoWhitespace"), 0));
-        assert_eq!(11, tokenize_whitespace(&String::from("NoWhitespace"), 11)); // Last character
+        assert_eq!(2, tokenize_whitespace(&chars("NoWhitespace"), 2));
+        assert_eq!(0, tokenize_whitespace(&chars("NoWhitespace"), 0));
+        assert_eq!(11, tokenize_whitespace(&chars("NoWhitespace"), 11)); // Last character
+
+        assert_eq!(1, tokenize_whitespace(&chars(" "), 0));
+        assert_eq!(1, tokenize_whitespace(&chars(" |"), 0));
+        assert_eq!(1, tokenize_whitespace(&chars("\t"), 0));
+        assert_eq!(0, tokenize_whitespace(&chars("-"), 0));
+        assert_eq!(2, tokenize_whitespace(&chars("  "), 0));
+        assert_eq!(4, tokenize_whitespace(&chars("    "), 0));
+        assert_eq!(4, tokenize_whitespace(&chars("    |"), 0));
+    }
 
-        assert_eq!(1, tokenize_whitespace(&String::from(" "), 0));
-        assert_eq!(1, tokenize_whitespace(&String::from(" |"), 0));
-        assert_eq!(1, tokenize_whitespace(&String::from("\t"), 0));
-        assert_eq!(0, tokenize_whitespace(&String::from("-"), 0));
-        assert_eq!(2, tokenize_whitespace(&String::from("  "), 0));
-        assert_eq!(4, tokenize_whitespace(&String::from("    "), 0));
-        assert_eq!(4, tokenize_whitespace(&String::from("    |"), 0));
+    #[test]
+    pub fn test_tokenize_whitespace_out_of_bounds() {
+        assert_eq!(0, tokenize_whitespace(&chars(""), 0));
+        assert_eq!(5, tokenize_whitespace(&chars("abc"), 5));
+        assert_eq!(2, tokenize_whitespace(&chars("é "), 1)); // Multi-byte characters before the whitespace
+        assert_eq!(3, tokenize_whitespace(&chars("é  "), 1));
+    }
+
+    #[test]
+    pub fn test_tokenize_whitespace_stops_at_newline() {
+        assert_eq!(1, tokenize_whitespace(&chars(" \n "), 0));
+        assert_eq!(0, tokenize_whitespace(&chars("\n"), 0));
+        assert_eq!(2, tokenize_whitespace(&chars("\t\r\n"), 0));
+    }
+
+    #[test]
+    pub fn test_tokenize_newline() {
+        assert_eq!(1, tokenize_newline(&chars("\n\n"), 0));
+        assert_eq!(2, tokenize_newline(&chars("\n\n"), 1));
+        assert_eq!(0, tokenize_newline(&chars(" \n"), 0));
+        assert_eq!(0, tokenize_newline(&chars(""), 0));
     }
 }
@@ -0,0 +1,182 @@
+use crate::dialect::Dialect;
+use crate::lexer::brace_expansion::tokenize_brace_expansion;
+use crate::lexer::history_expansion::tokenize_history_expansion;
+use crate::lexer::metacharacter::is_metacharacter;
+use crate::lexer::nested::tokenize_nested;
+use crate::lexer::parameter_expansion::is_special_parameter;
+use crate::lexer::tilde_prefix::tokenize_tilde_prefix;
+
+/// tokenize_word
+/// > [word](https://www.gnu.org/software/bash/manual/bash.html#index-word)
+/// > A sequence of characters treated as a unit by the shell. Words may not include unquoted metacharacters.
+///
+/// Quoted parts and expansions are tokenized on their own, so we stop at whitespace, metacharacters, quotes,
+/// brace expansions, tilde prefixes in assignments and `$` followed by anything that makes it an expansion. A backslash escapes the
+/// character after it.
+/// With `extglob` the parentheses of a pattern like `@(a|b)` are part of the word.
+pub fn tokenize_word(content: &[char], start: usize, dialect: &Dialect) -> usize {
+    let mut pointer: usize = start;
+    let mut chars = content.iter().skip(start).copied().peekable();
+    while let Some(char) = chars.next() {
+        if char.is_ascii_whitespace() || is_metacharacter(char) || ['\'', '"', '`'].contains(&char)
+        {
+            break;
+        }
+        if char == '$'
+            && pointer != start
+            && chars
+                .peek()
+                .is_some_and(|next| is_dollar_prefix(*next, dialect))
+        {
+            break; // Start of an expansion or a dollar-single-quote
+        }
+        if char == '{'
+            && pointer != start
+            && tokenize_brace_expansion(content, pointer, dialect) != pointer
+        {
+            break;
+        }
+        if char == '~'
+            && pointer != start
+            && tokenize_tilde_prefix(content, pointer, dialect) != pointer
+        {
+            break; // `PATH=a:~/bin`
+        }
+        if char == '!'
+            && pointer != start
+            && tokenize_history_expansion(content, pointer, dialect) != pointer
+        {
+            break; // `a!!`
+        }
+        if dialect.extglob
+            && ['?', '*', '+', '@', '!'].contains(&char)
+            && chars.peek() == Some(&'(')
+        {
+            if let Some(extglob_pointer) = tokenize_extglob(content, pointer + 1, dialect) {
+                pointer = extglob_pointer;
+                chars = content.iter().skip(pointer).copied().peekable();
+                continue;
+            }
+        }
+        if char == '\\' && chars.next().is_some() {
+            pointer += 1; // The escaped character is part of the word
+        }
+        pointer += 1;
+    }
+    pointer
+}
+
+/// Whether a `$` followed by `char` starts an expansion or a dollar-quoted string
+fn is_dollar_prefix(char: char, dialect: &Dialect) -> bool {
+    char.is_ascii_alphanumeric()
+        || ['_', '(', '{'].contains(&char)
+        || (char == '\'' && dialect.dollar_single_quotes)
+        || (char == '"' && dialect.dollar_double_quotes)
+        || is_special_parameter(char)
+}
+
+/// The pattern list of an extended pattern, `start` points at its `(`. Returns `None` when it's unterminated,
+/// the `(` is then a metacharacter like any other.
+fn tokenize_extglob(content: &[char], start: usize, dialect: &Dialect) -> Option<usize> {
+    let mut pointer = start + 1; // Skipped `(`
+    let mut depth = 0;
+    loop {
+        match content.get(pointer).copied()? {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(pointer + 1),
+            ')' => depth -= 1,
+            _ => {
+                let nested_pointer = tokenize_nested(content, pointer, dialect).ok()?;
+                if nested_pointer != pointer {
+                    pointer = nested_pointer;
+                    continue;
+                }
+            }
+        }
+        pointer += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::lexer::chars;
+    use crate::lexer::word::tokenize_word;
+
+    #[test]
+    pub fn test_tokenize_word_basic() {
+        assert_eq!(4, tokenize_word(&chars("echo"), 0, &Dialect::BASH));
+        assert_eq!(4, tokenize_word(&chars("echo hello"), 0, &Dialect::BASH));
+        assert_eq!(10, tokenize_word(&chars("echo hello"), 5, &Dialect::BASH));
+        assert_eq!(4, tokenize_word(&chars("echo|cat"), 0, &Dialect::BASH));
+        assert_eq!(4, tokenize_word(&chars("echo;"), 0, &Dialect::BASH));
+        assert_eq!(2, tokenize_word(&chars("ab'cd'"), 0, &Dialect::BASH));
+        assert_eq!(2, tokenize_word(&chars("ab$'cd'"), 0, &Dialect::BASH));
+        assert_eq!(4, tokenize_word(&chars("$USR"), 0, &Dialect::BASH));
+        assert_eq!(1, tokenize_word(&chars("$"), 0, &Dialect::BASH));
+        assert_eq!(4, tokenize_word(&chars("a$/b"), 0, &Dialect::BASH));
+    }
+
+    #[test]
+    pub fn test_tokenize_word_stops_at_quotes_and_expansions() {
+        assert_eq!(2, tokenize_word(&chars("ab\"cd\""), 0, &Dialect::BASH));
+        assert_eq!(2, tokenize_word(&chars("ab`cd`"), 0, &Dialect::BASH));
+        assert_eq!(2, tokenize_word(&chars("ab$cd"), 0, &Dialect::BASH));
+        assert_eq!(2, tokenize_word(&chars("ab${cd}"), 0, &Dialect::BASH));
+        assert_eq!(2, tokenize_word(&chars("ab$(cd)"), 0, &Dialect::BASH));
+        assert_eq!(2, tokenize_word(&chars("ab$?"), 0, &Dialect::BASH));
+    }
+
+    #[test]
+    pub fn test_tokenize_word_escapes() {
+        assert_eq!(4, tokenize_word(&chars("a\\ b c"), 0, &Dialect::BASH));
+        assert_eq!(4, tokenize_word(&chars("a\\;b;"), 0, &Dialect::BASH));
+        assert_eq!(3, tokenize_word(&chars("\\'a'"), 0, &Dialect::BASH));
+        assert_eq!(6, tokenize_word(&chars("\\$HOME"), 0, &Dialect::BASH));
+        assert_eq!(2, tokenize_word(&chars("a\\"), 0, &Dialect::BASH));
+    }
+
+    #[test]
+    pub fn test_tokenize_word_no_match() {
+        assert_eq!(0, tokenize_word(&chars(" echo"), 0, &Dialect::BASH));
+        assert_eq!(0, tokenize_word(&chars("|"), 0, &Dialect::BASH));
+        assert_eq!(0, tokenize_word(&chars("'quoted'"), 0, &Dialect::BASH));
+        assert_eq!(0, tokenize_word(&chars(""), 0, &Dialect::BASH));
+        assert_eq!(3, tokenize_word(&chars("abc"), 3, &Dialect::BASH));
+    }
+
+    #[test]
+    pub fn test_tokenize_word_multibyte() {
+        assert_eq!(4, tokenize_word(&chars("éçho"), 0, &Dialect::BASH));
+        assert_eq!(6, tokenize_word(&chars("a ü\u{a0}bc d"), 2, &Dialect::BASH));
+        // Non-ASCII whitespace is part of the word
+    }
+
+    #[test]
+    pub fn test_tokenize_word_dialects() {
+        let extglob = Dialect {
+            extglob: true,
+            ..Dialect::BASH
+        };
+        assert_eq!(2, tokenize_word(&chars("ab$'cd'"), 0, &Dialect::BASH));
+        assert_eq!(2, tokenize_word(&chars("ab$\"cd\""), 0, &Dialect::BASH));
+        assert_eq!(3, tokenize_word(&chars("ab$'cd'"), 0, &Dialect::DASH));
+        assert_eq!(2, tokenize_word(&chars("ab$'cd'"), 0, &Dialect::POSIX));
+        assert_eq!(3, tokenize_word(&chars("ab$\"cd\""), 0, &Dialect::POSIX));
+
+        assert_eq!(1, tokenize_word(&chars("@(a|b)"), 0, &Dialect::BASH));
+        assert_eq!(6, tokenize_word(&chars("@(a|b)"), 0, &extglob));
+        assert_eq!(14, tokenize_word(&chars("x!(*.txt|')')y z"), 0, &extglob));
+        assert_eq!(12, tokenize_word(&chars("+(a|@(b|c))d"), 0, &extglob));
+        assert_eq!(1, tokenize_word(&chars("*(a"), 0, &extglob));
+
+        assert_eq!(3, tokenize_word(&chars("pre{a,b}"), 0, &Dialect::BASH));
+        assert_eq!(8, tokenize_word(&chars("pre{a,b}"), 0, &Dialect::DASH));
+        assert_eq!(5, tokenize_word(&chars("a{b}c"), 0, &Dialect::BASH));
+        assert_eq!(1, tokenize_word(&chars("{"), 0, &Dialect::BASH));
+
+        assert_eq!(5, tokenize_word(&chars("PATH=~/bin"), 0, &Dialect::BASH));
+        assert_eq!(8, tokenize_word(&chars("PATH=/a:~b"), 0, &Dialect::BASH));
+        assert_eq!(6, tokenize_word(&chars("a~b=~c"), 0, &Dialect::BASH));
+    }
+}
@@ -1,67 +1,1009 @@
+use crate::dialect::Dialect;
+use crate::lexer::arithmetic_expansion::{tokenize_arithmetic_expansion, ArithmeticCommands};
+use crate::lexer::brace_expansion::tokenize_brace_expansion;
+use crate::lexer::case_pattern::CasePatterns;
+use crate::lexer::command_substitution::tokenize_command_substitution;
+use crate::lexer::comment::tokenize_comment;
 use crate::lexer::dollar_single_quotes::tokenize_dollar_single_quotes;
+use crate::lexer::double_quotes::{tokenize_dollar_double_quotes, tokenize_double_quotes};
+use crate::lexer::here_document::{here_document_delimiter, tokenize_here_document};
+use crate::lexer::history_expansion::tokenize_history_expansion;
+use crate::lexer::keyword::mark_keywords;
 use crate::lexer::metacharacter::tokenize_metacharacter;
 use crate::lexer::number::tokenize_number;
+use crate::lexer::operator::{require_operator_version, tokenize_operator};
+use crate::lexer::parameter_expansion::tokenize_parameter_expansion;
+use crate::lexer::pattern::tokenize_pattern;
+use crate::lexer::process_substitution::tokenize_process_substitution;
 use crate::lexer::single_quotes::tokenize_single_quotes;
-use crate::lexer::whitespace::tokenize_whitespace;
-use crate::tokens::tokens::Token;
+use crate::lexer::tilde_prefix::tokenize_tilde_prefix;
+use crate::lexer::whitespace::{tokenize_newline, tokenize_whitespace};
+use crate::lexer::word::tokenize_word;
+use crate::tokens::span::Span;
+use crate::tokens::tokens::{Token, TokenKind};
 use std::error::Error;
 
+pub mod alias;
+pub mod argv;
+pub mod arithmetic;
+pub mod dialect;
+pub mod expand;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod injection;
 pub mod lexer;
+pub mod parser;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod quote;
 pub mod tokens;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+/// tokenize
+/// Every iteration either moves the pointer forward or returns, so this is guaranteed to terminate
+/// without panicking on any input. The tokens cover the input without gaps, see [render].
+///
+/// Here-documents and case patterns are the only tokens that depend on what came before : the body of every
+/// `<<` starts after the next newline, in the order the redirections appeared, and the `(`, `|` and `)`
+/// around the patterns of a `case` item aren't parentheses and pipes, see [CasePatterns].
 pub fn tokenize(s: String) -> Result<Vec<Token>, Box<dyn Error>> {
+    tokenize_with_dialect(s, &Dialect::BASH)
+}
+
+/// tokenize_with_dialect
+/// Like [tokenize], for a script written for another shell than bash : `tokenize_with_dialect(s, &Dialect::DASH)`
+pub fn tokenize_with_dialect(s: String, dialect: &Dialect) -> Result<Vec<Token>, Box<dyn Error>> {
+    // Lexers work with character indexes into a slice collected once, spans use byte offsets :
+    let chars: Vec<char> = s.chars().collect();
+    let offsets: Vec<usize> = s
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(s.len()))
+        .collect();
+    let byte_offset = |pointer: usize| offsets.get(pointer).copied().unwrap_or(s.len());
+
     let mut result: Vec<Token> = Vec::new();
-    let mut pointer = 0;
-    while pointer < s.chars().count() {
-        // It's important to follow a certain order, whitespace first, keyword before number, ...
-        let whitespace_pointer = tokenize_whitespace(&s, pointer);
-        if whitespace_pointer != pointer {
-            // Whitespace detected
-            result.push(Token::WhiteSpace);
-            pointer = whitespace_pointer;
-            continue;
+    let mut lexer = Lexer::new(chars, dialect);
+    while let Some((kind, text)) = lexer.next()? {
+        let end = byte_offset(lexer.pointer);
+        let span = Span::new(end - text.len(), end);
+        result.push(Token {
+            kind,
+            text,
+            span,
+            alias: None,
+        });
+    }
+    mark_keywords(&mut result);
+
+    Ok(result)
+}
+
+/// The state of [tokenize_with_dialect] between two tokens. [tokenize_with_aliases](alias::tokenize_with_aliases)
+/// drives it too, and splices the value of an alias into `chars` before reading it.
+pub(crate) struct Lexer<'a> {
+    pub chars: Vec<char>,
+    pub pointer: usize,
+    pub previous: Option<TokenKind>, // The kind of the last token, `None` at the start of the input
+    dialect: &'a Dialect,
+    // The delimiter word being read after a `<<`, and the here-documents waiting for a newline :
+    delimiter: Option<(String, bool)>,
+    here_documents: Vec<(String, bool)>,
+    case_patterns: CasePatterns,
+    arithmetic_commands: ArithmeticCommands,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(chars: Vec<char>, dialect: &'a Dialect) -> Lexer<'a> {
+        Lexer {
+            chars,
+            pointer: 0,
+            previous: None,
+            dialect,
+            delimiter: None,
+            here_documents: Vec::new(),
+            case_patterns: CasePatterns::default(),
+            arithmetic_commands: ArithmeticCommands::default(),
         }
+    }
+
+    /// Reads the token at the pointer, returns its kind and its text, `None` at the end of the input
+    pub fn next(&mut self) -> Result<Option<(TokenKind, String)>, Box<dyn Error>> {
+        let start = self.pointer;
+        let (kind, next_pointer) = loop {
+            if self.pointer >= self.chars.len() {
+                return Ok(None);
+            }
+            match self.here_documents.first() {
+                Some((word, strip_tabs)) if self.previous == Some(TokenKind::Newline) => {
+                    let here_document_pointer =
+                        tokenize_here_document(&self.chars, self.pointer, word, *strip_tabs);
+                    self.here_documents.remove(0);
+                    if here_document_pointer != self.pointer {
+                        break (TokenKind::HereDocument, here_document_pointer);
+                    } // Otherwise an empty line as delimiter
+                }
+                _ => {
+                    let arithmetic_commands = self
+                        .case_patterns
+                        .arithmetic_command_start()
+                        .then_some(&mut self.arithmetic_commands);
+                    break tokenize_next(
+                        &self.chars,
+                        self.pointer,
+                        self.previous,
+                        arithmetic_commands,
+                        self.dialect,
+                    )?;
+                }
+            }
+        };
+        let text: String = self.chars[start..next_pointer].iter().collect();
+        let kind = self.case_patterns.next(kind, &text);
 
-        let number_pointer = tokenize_number(&s, pointer);
-        if number_pointer != pointer {
-            // Number detected
-            result.push(Token::Number);
-            pointer = number_pointer;
-            continue;
+        self.delimiter = match (self.delimiter.take(), kind) {
+            (None, TokenKind::DoubleLess) => Some((String::new(), false)),
+            (None, TokenKind::DoubleLessDash) => Some((String::new(), true)),
+            (Some(pending), TokenKind::WhiteSpace) if pending.0.is_empty() => Some(pending),
+            (Some((mut word, strip_tabs)), kind) if kind.is_word() => {
+                word.push_str(&text);
+                Some((word, strip_tabs))
+            }
+            (Some((word, strip_tabs)), _) => {
+                if !word.is_empty() {
+                    self.here_documents
+                        .push((here_document_delimiter(&word).0, strip_tabs));
+                }
+                None
+            }
+            (None, _) => None,
+        };
+        self.previous = Some(kind);
+        self.pointer = next_pointer;
+        Ok(Some((kind, text)))
+    }
+
+    /// The end of the word starting at the pointer when it's a single `Word` token, like the name of an alias
+    pub fn word_end(&self) -> Result<Option<usize>, Box<dyn Error>> {
+        let here_document =
+            self.previous == Some(TokenKind::Newline) && !self.here_documents.is_empty();
+        if self.pointer >= self.chars.len()
+            || here_document
+            || self.previous.is_some_and(|kind| kind.is_word())
+        {
+            return Ok(None);
+        }
+        let (kind, end) =
+            tokenize_next(&self.chars, self.pointer, self.previous, None, self.dialect)?;
+        if kind != TokenKind::Word {
+            return Ok(None);
         }
+        if end < self.chars.len() {
+            let (next_kind, _) = tokenize_next(&self.chars, end, Some(kind), None, self.dialect)?;
+            if next_kind.is_word() {
+                return Ok(None);
+            }
+        }
+        Ok(Some(end))
+    }
+
+    /// Replaces the characters from the pointer to `end` with `value`, the next token starts with `value`
+    pub fn splice(&mut self, end: usize, value: &[char]) {
+        self.chars.splice(self.pointer..end, value.iter().copied());
+        // The memo holds indexes past the pointer :
+        self.arithmetic_commands = ArithmeticCommands::default();
+    }
+}
+
+/// render
+/// Concatenates the text of `tokens`, for the tokens returned by [tokenize] this reproduces the input
+/// byte for byte.
+pub fn render(tokens: &[Token]) -> String {
+    tokens.iter().map(|token| token.text.as_str()).collect()
+}
 
-        // Single-Quote strings :
-        let single_quotes_result = tokenize_single_quotes(&s, pointer);
-        if single_quotes_result.is_err() {
-            return Err(single_quotes_result.unwrap_err());
+/// Tokenizes the token starting at `pointer`, the returned pointer is always past `pointer`. `previous` is the
+/// kind of the token before it, `None` at the start of the input. `arithmetic_commands` is only passed where
+/// a `((` starts an arithmetic command, see [CasePatterns::arithmetic_command_start].
+pub(crate) fn tokenize_next(
+    s: &[char],
+    pointer: usize,
+    previous: Option<TokenKind>,
+    arithmetic_commands: Option<&mut ArithmeticCommands>,
+    dialect: &Dialect,
+) -> Result<(TokenKind, usize), Box<dyn Error>> {
+    // It's important to follow a certain order, whitespace first, keyword before number, ...
+    let whitespace_pointer = tokenize_whitespace(s, pointer);
+    if whitespace_pointer != pointer {
+        // Whitespace detected
+        return Ok((TokenKind::WhiteSpace, whitespace_pointer));
+    }
+
+    let newline_pointer = tokenize_newline(s, pointer);
+    if newline_pointer != pointer {
+        return Ok((TokenKind::Newline, newline_pointer));
+    }
+
+    let comment_pointer = tokenize_comment(s, pointer, previous);
+    if comment_pointer != pointer {
+        return Ok((TokenKind::Comment, comment_pointer));
+    }
+
+    // `!!` and `^old^new` are replaced before the line is tokenized, anywhere but in single quotes, after a
+    // backslash and in comments. Double quotes keep them inside, like other expansions :
+    let history_pointer = tokenize_history_expansion(s, pointer, dialect);
+    if history_pointer != pointer {
+        return Ok((TokenKind::HistoryExpansion, history_pointer));
+    }
+
+    let number_pointer = tokenize_number(s, pointer);
+    if number_pointer != pointer && tokenize_word(s, pointer, dialect) <= number_pointer {
+        // Number detected, `1file` on the other hand is a word
+        return Ok((TokenKind::Number, number_pointer));
+    }
+
+    // Single-Quote strings :
+    let single_quotes_pointer = tokenize_single_quotes(s, pointer)?;
+    if single_quotes_pointer != pointer {
+        return Ok((TokenKind::SingleQuote, single_quotes_pointer));
+    }
+
+    // Dollar-Single-Quote strings :
+    let dsq_pointer = tokenize_dollar_single_quotes(s, pointer, dialect)?;
+    if dsq_pointer != pointer {
+        return Ok((TokenKind::DollarSingleQuote, dsq_pointer));
+    }
+
+    // Dollar-Double-Quote strings :
+    let ddq_pointer = tokenize_dollar_double_quotes(s, pointer, dialect)?;
+    if ddq_pointer != pointer {
+        return Ok((TokenKind::DollarDoubleQuote, ddq_pointer));
+    }
+
+    // Double-Quote strings :
+    let double_quotes_pointer = tokenize_double_quotes(s, pointer, dialect)?;
+    if double_quotes_pointer != pointer {
+        return Ok((TokenKind::DoubleQuote, double_quotes_pointer));
+    }
+
+    // Expansions, arithmetic first as `$((` could also start a command substitution :
+    let arithmetic_pointer = tokenize_arithmetic_expansion(s, pointer, dialect)?;
+    if arithmetic_pointer != pointer {
+        return Ok((TokenKind::ArithmeticExpansion, arithmetic_pointer));
+    }
+    let command_pointer = tokenize_command_substitution(s, pointer, dialect)?;
+    if command_pointer != pointer {
+        return Ok((TokenKind::CommandSubstitution, command_pointer));
+    }
+    let parameter_pointer = tokenize_parameter_expansion(s, pointer, dialect)?;
+    if parameter_pointer != pointer {
+        return Ok((TokenKind::ParameterExpansion, parameter_pointer));
+    }
+
+    // Process substitution before `<` and `>` :
+    let process_pointer = tokenize_process_substitution(s, pointer, dialect)?;
+    if process_pointer != pointer {
+        return Ok((TokenKind::ProcessSubstitution, process_pointer));
+    }
+
+    // `((` where a command starts, before it's read as the `(` of a subshell :
+    if let Some(arithmetic_commands) = arithmetic_commands {
+        let arithmetic_pointer = arithmetic_commands.tokenize(s, pointer, dialect)?;
+        if arithmetic_pointer != pointer {
+            return Ok((TokenKind::ArithmeticCommand, arithmetic_pointer));
         }
-        let single_quotes_pointer = single_quotes_result?;
-        if single_quotes_pointer != pointer {
-            result.push(Token::SingleQuote);
-            pointer = single_quotes_pointer;
-            continue;
+    }
+
+    // Operators made of several metacharacters first : `&&`, `>>`, ...
+    if let Some((operator_token, operator_pointer)) = tokenize_operator(s, pointer, dialect) {
+        require_operator_version(operator_token, pointer, dialect)?;
+        return Ok((operator_token, operator_pointer));
+    }
+
+    // Check for metacharacters ‘|’, ‘&’, ‘;’, ‘(’, ‘)’, ‘<’, or ‘>’
+    if let Some((metacharacters_token, metacharacters_pointer)) = tokenize_metacharacter(s, pointer)
+    {
+        return Ok((metacharacters_token, metacharacters_pointer));
+    }
+
+    // `~` and `~user` at the start of a word or after `=`/`:` in an assignment, before the word they prefix :
+    let tilde_pointer = tokenize_tilde_prefix(s, pointer, dialect);
+    if tilde_pointer != pointer {
+        return Ok((TokenKind::TildePrefix, tilde_pointer));
+    }
+
+    // Brace expansions are words that `tokenize_word` stops at, `{a,b}` but not the brace group `{ a; }` :
+    let brace_pointer = tokenize_brace_expansion(s, pointer, dialect);
+    if brace_pointer != pointer {
+        return Ok((TokenKind::BraceExpansion, brace_pointer));
+    }
+
+    // Words that bash replaces by the file names they match :
+    let pattern_pointer = tokenize_pattern(s, pointer, dialect);
+    if pattern_pointer != pointer {
+        return Ok((TokenKind::Pattern, pattern_pointer));
+    }
+
+    // Anything else is (part of) a word :
+    let word_pointer = tokenize_word(s, pointer, dialect);
+    if word_pointer != pointer {
+        return Ok((TokenKind::Word, word_pointer));
+    }
+
+    // No lexer made progress, bail out instead of looping forever :
+    Err(format!("Unexpected character at index {}", pointer).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::{BashVersion, Dialect};
+    use crate::tokens::span::Span;
+    use crate::tokens::tokens::{Token, TokenKind};
+    use crate::{render, tokenize, tokenize_with_dialect};
+    #[cfg(not(target_arch = "wasm32"))]
+    use proptest::prelude::*;
+
+    fn kinds(s: &str) -> Vec<TokenKind> {
+        tokenize(s.to_string())
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_simple_command() {
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::SingleQuote,
+                TokenKind::Semicolon,
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::Number,
+                TokenKind::Pipe,
+                TokenKind::Word,
+            ],
+            kinds("echo 'hi';exit 1|cat")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_numbers() {
+        assert_eq!(vec![TokenKind::Number], kinds("123"));
+        assert_eq!(
+            vec![TokenKind::Number, TokenKind::GreatAnd, TokenKind::Word],
+            kinds("2>log")
+        );
+        assert_eq!(vec![TokenKind::Word], kinds("1file"));
+        assert_eq!(vec![TokenKind::Word], kinds("1.5"));
+    }
+
+    #[test]
+    fn test_tokenize_dollar_single_quotes_terminates() {
+        assert_eq!(vec![TokenKind::DollarSingleQuote], kinds("$'x'"));
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::DollarSingleQuote
+            ],
+            kinds("echo $'a\\nb'")
+        );
+        assert_eq!(vec![TokenKind::Word], kinds("$"));
+    }
+
+    #[test]
+    fn test_tokenize_errors() {
+        assert!(tokenize(String::from("echo 'unterminated")).is_err());
+        assert!(tokenize(String::from("echo $'\\")).is_err());
+        assert!(tokenize(String::from("é$'ü")).is_err());
+    }
+
+    #[test]
+    fn test_tokenize_nesting_limit() {
+        let nested = |open: &str, close: &str, count: usize| {
+            tokenize(format!("{}x{}", open.repeat(count), close.repeat(count)))
+                .map_err(|error| error.to_string())
+        };
+        assert_eq!(1, nested("$(", ")", 200).unwrap().len());
+        assert_eq!(1, nested("${x:-", "}", 200).unwrap().len());
+        assert_eq!(
+            "Nesting deeper than 256 levels at index 514",
+            nested("$(", ")", 100000).unwrap_err()
+        );
+        assert_eq!(
+            "Nesting deeper than 256 levels at index 1282",
+            nested("${x:-", "}", 100000).unwrap_err()
+        );
+        assert_eq!(
+            "Nesting deeper than 256 levels at index 771",
+            nested("$((", "))", 100000).unwrap_err()
+        );
+        assert_eq!(
+            "Nesting deeper than 256 levels at index 771",
+            nested("\"$(", ")\"", 100000).unwrap_err()
+        );
+        let extglob = Dialect {
+            extglob: true,
+            ..Dialect::BASH
+        };
+        let patterns = format!("{}x{}", "@(".repeat(100000), ")".repeat(100000));
+        assert_eq!(1, tokenize_with_dialect(patterns, &extglob).unwrap().len());
+    }
+
+    #[test]
+    fn test_tokenize_quotes_and_expansions() {
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::DoubleQuote,
+                TokenKind::WhiteSpace,
+                TokenKind::CommandSubstitution,
+                TokenKind::ParameterExpansion,
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::ArithmeticExpansion,
+                TokenKind::WhiteSpace,
+                TokenKind::Word,
+            ],
+            kinds(r#"echo "a $b" $(date)$HOME/bin $((1 + 2)) a\ b"#)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_spans_and_text() {
+        assert_eq!(
+            vec![
+                Token::new(TokenKind::Word, "echo", Span::new(0, 4)),
+                Token::new(TokenKind::WhiteSpace, "  ", Span::new(4, 6)),
+                Token::new(TokenKind::SingleQuote, "'hé'", Span::new(6, 11)),
+                Token::new(TokenKind::Pipe, "|", Span::new(11, 12)),
+                Token::new(TokenKind::Word, "cat", Span::new(12, 15)),
+            ],
+            tokenize(String::from("echo  'hé'|cat")).unwrap()
+        );
+        assert_eq!(Vec::<Token>::new(), tokenize(String::new()).unwrap());
+    }
+
+    #[test]
+    fn test_tokenize_comments() {
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::Comment,
+                TokenKind::Newline,
+                TokenKind::Word,
+                TokenKind::Semicolon,
+                TokenKind::Comment,
+            ],
+            kinds("echo # 'not a quote\nls#x;#y")
+        );
+        assert_eq!(
+            vec![TokenKind::SingleQuote, TokenKind::Word],
+            kinds("'a'#b")
+        );
+        assert_eq!(
+            vec![TokenKind::CommandSubstitution, TokenKind::Word],
+            kinds("$(ls)#x")
+        );
+        assert_eq!(
+            vec![TokenKind::ParameterExpansion, TokenKind::Word],
+            kinds("${x}#y")
+        );
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::CommandSubstitution,
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::Comment,
+            ],
+            kinds("echo $(echo \"a\")#x #y")
+        );
+        assert_eq!(
+            vec![
+                TokenKind::LParenthesis,
+                TokenKind::RParenthesis,
+                TokenKind::Comment
+            ],
+            kinds("()#x")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_operators_and_newlines() {
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::AndIf,
+                TokenKind::Word,
+                TokenKind::OrIf,
+                TokenKind::Word,
+                TokenKind::Newline,
+                TokenKind::Newline,
+                TokenKind::Number,
+                TokenKind::GreatAmpersand,
+                TokenKind::Number,
+                TokenKind::DoubleGreat,
+                TokenKind::Word,
+                TokenKind::DoubleSemicolon,
+            ],
+            kinds("a&&b||c\n\n2>&1>>x;;")
+        );
+        assert_eq!(
+            vec![
+                TokenKind::WhiteSpace,
+                TokenKind::Newline,
+                TokenKind::WhiteSpace
+            ],
+            kinds(" \n\t")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_here_documents() {
+        let tokens = tokenize(String::from("cat <<EOF; ls\nit's $x\nEOF\necho")).unwrap();
+        let here_document = tokens
+            .iter()
+            .find(|token| token.kind == TokenKind::HereDocument)
+            .unwrap();
+        assert_eq!("it's $x\nEOF", here_document.text);
+        assert_eq!(TokenKind::Word, tokens.last().unwrap().kind);
+
+        // Bodies follow each other in the order of the redirections :
+        let tokens = tokenize(String::from("cat <<A <<-'B'\n1\nA\n\t2\n\tB\n")).unwrap();
+        let bodies: Vec<&str> = tokens
+            .iter()
+            .filter(|token| token.kind == TokenKind::HereDocument)
+            .map(|token| token.text.as_str())
+            .collect();
+        assert_eq!(vec!["1\nA", "\t2\n\tB"], bodies);
+
+        // Delimited by the end of the input :
+        assert_eq!(
+            TokenKind::HereDocument,
+            kinds("cat <<EOF\n'unterminated").pop().unwrap()
+        );
+        assert_eq!(vec![TokenKind::Word, TokenKind::DoubleLess], kinds("a<<"));
+    }
+
+    #[test]
+    fn test_tokenize_arithmetic_commands() {
+        use TokenKind::*;
+        assert_eq!(vec![ArithmeticCommand], kinds("((x << 2 # 1))"));
+        assert_eq!(
+            vec![
+                Keyword,
+                WhiteSpace,
+                ArithmeticCommand,
+                Semicolon,
+                WhiteSpace,
+                Keyword
+            ],
+            kinds("for ((i=0;i<3;i++)); do")
+        );
+        assert_eq!(
+            vec![Keyword, ArithmeticCommand, WhiteSpace, Keyword],
+            kinds("for((;;)) do")
+        );
+        assert_eq!(
+            vec![
+                Word,
+                Semicolon,
+                WhiteSpace,
+                ArithmeticCommand,
+                AndIf,
+                ArithmeticCommand
+            ],
+            kinds("a; ((x))&&((y))")
+        );
+        // Only where a command starts :
+        assert_eq!(
+            vec![
+                Word,
+                LParenthesis,
+                LParenthesis,
+                Word,
+                RParenthesis,
+                RParenthesis
+            ],
+            kinds("a((x))")
+        );
+        assert_eq!(CasePatternStart, kinds("case x in ((x)) a;; esac")[6]);
+        assert_eq!(LParenthesis, kinds("[[ ((a)) ]]")[2]);
+        // A `((` without a matching `))` is a subshell in a subshell :
+        assert_eq!(
+            vec![
+                LParenthesis,
+                LParenthesis,
+                Word,
+                RParenthesis,
+                WhiteSpace,
+                RParenthesis
+            ],
+            kinds("((ls) )")
+        );
+        // Inside a command substitution, where `<<` isn't a here-document either :
+        assert_eq!(vec![CommandSubstitution], kinds("$( ((x << 1)) )"));
+        let dash = tokenize_with_dialect(String::from("((x))"), &Dialect::DASH).unwrap();
+        assert_eq!(TokenKind::LParenthesis, dash[0].kind);
+    }
+
+    #[test]
+    fn test_tokenize_dialects() {
+        let dash = |s: &str| -> Vec<TokenKind> {
+            tokenize_with_dialect(s.to_string(), &Dialect::DASH)
+                .unwrap()
+                .into_iter()
+                .map(|token| token.kind)
+                .collect()
+        };
+        assert_eq!(
+            vec![
+                TokenKind::DollarSingleQuote,
+                TokenKind::DollarDoubleQuote,
+                TokenKind::ProcessSubstitution,
+                TokenKind::TripleLess,
+                TokenKind::Word,
+                TokenKind::AmpersandGreat,
+                TokenKind::Word,
+                TokenKind::PipeAmpersand,
+            ],
+            kinds("$'a'$\"b\"<(c)<<<d&>e|&")
+        );
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::SingleQuote,
+                TokenKind::Word,
+                TokenKind::DoubleQuote,
+                TokenKind::LessAnd,
+                TokenKind::LParenthesis,
+                TokenKind::Word,
+                TokenKind::RParenthesis,
+                TokenKind::DoubleLess,
+                TokenKind::LessAnd,
+                TokenKind::Word,
+                TokenKind::Ampersand,
+                TokenKind::GreatAnd,
+                TokenKind::Word,
+                TokenKind::Pipe,
+                TokenKind::Ampersand,
+            ],
+            dash("$'a'$\"b\"<(c)<<<d&>e|&")
+        );
+        // `<<<` doesn't start a here-document, so the next line is tokenized as usual :
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::TripleLess,
+                TokenKind::Word,
+                TokenKind::Newline,
+                TokenKind::Word
+            ],
+            kinds("a<<<b\nc")
+        );
+
+        let presets = |s: &str, dialect: &Dialect| -> Vec<TokenKind> {
+            tokenize_with_dialect(s.to_string(), dialect)
+                .unwrap()
+                .into_iter()
+                .map(|token| token.kind)
+                .collect()
+        };
+        // `|&` starts a co-process in ksh93 :
+        assert_eq!(
+            vec![TokenKind::Pattern, TokenKind::Pipe, TokenKind::Ampersand],
+            presets("@(a|b)|&", &Dialect::KSH93)
+        );
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::AmpersandGreat,
+                TokenKind::Word,
+                TokenKind::PipeAmpersand,
+                TokenKind::Word,
+                TokenKind::DoubleQuote,
+            ],
+            presets("a&>b|&$\"c\"", &Dialect::ZSH)
+        );
+        assert_eq!(
+            vec![
+                TokenKind::DollarSingleQuote,
+                TokenKind::TripleLess,
+                TokenKind::Word,
+                TokenKind::AmpersandGreat,
+                TokenKind::Word,
+            ],
+            presets("$'a'<<<b{c,d}&>e", &Dialect::BUSYBOX_ASH)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_brace_expansions() {
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::Word,
+                TokenKind::BraceExpansion,
+                TokenKind::Word,
+                TokenKind::BraceExpansion,
+            ],
+            kinds("echo pre{a,b{c,d}}suf{1..10..2}")
+        );
+        // A brace group is a `{` keyword, and `{a}` isn't correctly-formed :
+        assert_eq!(
+            vec![
+                TokenKind::Keyword,
+                TokenKind::WhiteSpace,
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::Word,
+                TokenKind::Semicolon,
+                TokenKind::WhiteSpace,
+                TokenKind::Keyword,
+            ],
+            kinds("{ echo {a}; }")
+        );
+        let dash = tokenize_with_dialect(String::from("{a,b}"), &Dialect::DASH).unwrap();
+        assert_eq!(TokenKind::Word, dash[0].kind);
+    }
+
+    #[test]
+    fn test_tokenize_tilde_prefixes() {
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::TildePrefix,
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::TildePrefix,
+                TokenKind::WhiteSpace,
+                TokenKind::Word,
+                TokenKind::SingleQuote,
+            ],
+            kinds("ls ~alice/notes ~+ ~'root'")
+        );
+        let tokens = tokenize(String::from("PATH=~/bin:/usr/bin:~- a=b~")).unwrap();
+        assert_eq!(
+            vec![
+                (TokenKind::Word, "PATH="),
+                (TokenKind::TildePrefix, "~"),
+                (TokenKind::Word, "/bin:/usr/bin:"),
+                (TokenKind::TildePrefix, "~-"),
+                (TokenKind::WhiteSpace, " "),
+                (TokenKind::Word, "a=b~"),
+            ],
+            tokens
+                .iter()
+                .map(|token| (token.kind, token.text.as_str()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![TokenKind::TildePrefix, TokenKind::Pattern],
+            kinds("~/*.txt")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_patterns() {
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::Pattern,
+                TokenKind::WhiteSpace,
+                TokenKind::DoubleQuote,
+                TokenKind::Pattern,
+                TokenKind::WhiteSpace,
+                TokenKind::Word,
+            ],
+            kinds("ls /etc/*.conf \"$dir\"/file[0-9]? \\*")
+        );
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::Word
+            ],
+            kinds("[ x ]")
+        );
+
+        let extglob = Dialect {
+            extglob: true,
+            ..Dialect::BASH
+        };
+        let tokens = tokenize_with_dialect(String::from("ls !(*.txt|@(a|b))"), &extglob).unwrap();
+        assert_eq!(TokenKind::Pattern, tokens[2].kind);
+        assert_eq!("!(*.txt|@(a|b))", tokens[2].text);
+        assert_eq!(
+            vec![
+                TokenKind::Word,
+                TokenKind::WhiteSpace,
+                TokenKind::Word,
+                TokenKind::LParenthesis,
+                TokenKind::Pattern,
+                TokenKind::RParenthesis
+            ],
+            kinds("ls !(*.txt)")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_bash_version() {
+        let bash_3_2 = Dialect {
+            bash_version: Some(BashVersion::new(3, 2)),
+            ..Dialect::BASH
+        };
+        let error = |s: &str| {
+            tokenize_with_dialect(s.to_string(), &bash_3_2)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            "`|&` requires bash 4.0 (targeting 3.2) at index 2",
+            error("a |& b")
+        );
+        assert_eq!(
+            "`;;&` requires bash 4.0 (targeting 3.2) at index 17",
+            error("case a in a) echo;;& esac")
+        );
+        assert_eq!(
+            "`&>>` requires bash 4.0 (targeting 3.2) at index 11",
+            error("echo \"$(ls &>>log)\"")
+        );
+        assert_eq!(
+            "`|&` requires bash 4.0 (targeting 3.2) at index 10",
+            error("diff <(ls |& sort) b")
+        );
+        assert_eq!(
+            "`@Q` requires bash 4.4 (targeting 3.2) at index 9",
+            error("echo \"${x@Q}\"")
+        );
+        assert!(tokenize_with_dialect(
+            String::from("a | b && c; case a in a) b;; esac"),
+            &bash_3_2
+        )
+        .is_ok());
+        assert!(tokenize(String::from("a |& b ${x@Q}")).is_ok());
+    }
+
+    #[test]
+    fn test_tokenize_history_expansion() {
+        let interactive = Dialect {
+            history_expansion: true,
+            ..Dialect::BASH
+        };
+        let tokens = |s: &str| {
+            tokenize_with_dialect(s.to_string(), &interactive)
+                .unwrap()
+                .into_iter()
+                .map(|token| (token.kind, token.text))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![
+                (TokenKind::Word, String::from("sudo")),
+                (TokenKind::WhiteSpace, String::from(" ")),
+                (TokenKind::HistoryExpansion, String::from("!!")),
+            ],
+            tokens("sudo !!")
+        );
+        assert_eq!(
+            vec![
+                (TokenKind::Word, String::from("echo")),
+                (TokenKind::WhiteSpace, String::from(" ")),
+                (TokenKind::Word, String::from("a")),
+                (TokenKind::HistoryExpansion, String::from("!$")),
+                (TokenKind::Word, String::from("b")),
+            ],
+            tokens("echo a!$b")
+        );
+        assert_eq!(
+            vec![(TokenKind::HistoryExpansion, String::from("^old^new^"))],
+            tokens("^old^new^")
+        );
+        assert_eq!(
+            vec![(TokenKind::HistoryExpansion, String::from("!-2:s/a/b/"))],
+            tokens("!-2:s/a/b/")
+        );
+        assert_eq!(
+            vec![
+                (TokenKind::Word, String::from("echo")),
+                (TokenKind::WhiteSpace, String::from(" ")),
+                (TokenKind::HistoryExpansion, String::from("!:1-3")),
+            ],
+            tokens("echo !:1-3")
+        );
+        // Double quotes hold them like expansions, a quote in the expansion doesn't end the string :
+        assert_eq!(
+            vec![(TokenKind::DoubleQuote, String::from("\"!!:s/a/\"/ x\""))],
+            tokens("\"!!:s/a/\"/ x\"")
+        );
+        // Single-quoted, escaped, commented out, or followed by a blank :
+        assert_eq!(TokenKind::SingleQuote, tokens("'!!'")[0].0);
+        assert_eq!(TokenKind::Word, tokens("\\!!")[0].0);
+        assert_eq!(TokenKind::Comment, tokens("# !!")[0].0);
+        assert!(!kinds("! ls; a!=b").contains(&TokenKind::HistoryExpansion));
+        // Scripts don't expand them :
+        assert!(!kinds("sudo !!; echo !$").contains(&TokenKind::HistoryExpansion));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_schema() {
+        use crate::parser::{parse, ParseError};
+
+        let tokens = tokenize(String::from("ls | wc")).unwrap();
+        assert_eq!(
+            r#"{"kind":"Word","text":"ls","span":{"start":0,"end":2},"alias":null}"#,
+            serde_json::to_string(&tokens[0]).unwrap()
+        );
+        let json = serde_json::to_string(&tokens).unwrap();
+        assert_eq!(tokens, serde_json::from_str::<Vec<Token>>(&json).unwrap());
+
+        let list = parse(&tokens).unwrap();
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(list, serde_json::from_str(&json).unwrap());
+
+        let error = parse(&tokenize(String::from("ls |")).unwrap()).unwrap_err();
+        assert_eq!(
+            r#"{"message":"Expected a command, found the end of input at index 4","span":{"start":4,"end":4}}"#,
+            serde_json::to_string(&error).unwrap()
+        );
+        let _: ParseError = serde_json::from_str(&serde_json::to_string(&error).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_render() {
+        let script = "#!/bin/bash\n\tif [ -f x ]; then  echo \"$a\" $'b' 2>&1 # done\nfi\n";
+        assert_eq!(script, render(&tokenize(script.to_string()).unwrap()));
+        assert_eq!("", render(&[]));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    proptest! {
+        #[test]
+        fn test_tokenize_never_panics(s in "\\PC*") {
+            let _ = tokenize(s);
         }
 
-        // Dollar-Single-Quote strings :
-        let dsq_result = tokenize_dollar_single_quotes(&s, pointer);
-        if dsq_result.is_err() {
-            return Err(dsq_result.unwrap_err());
+        #[test]
+        fn test_tokenize_never_panics_on_shell_like_input(
+            s in "([ \t\n'\"$\\\\|&;()<>`{}\\[\\]#=~!*?a-z0-9]|é|\u{a0})*"
+        ) {
+            let _ = tokenize(s);
         }
-        let dsq_pointer = dsq_result?;
-        if dsq_pointer != pointer {
-            result.push(Token::DollarSingleQuote);
-            pointer = single_quotes_pointer;
-            continue;
+
+        #[test]
+        fn test_spans_cover_the_input(s in "([ \t\n'\"$\\\\|;()`{}#a-z0-9]|é)*") {
+            if let Ok(tokens) = tokenize(s.clone()) {
+                let mut end = 0;
+                for token in tokens {
+                    prop_assert_eq!(end, token.span.start);
+                    prop_assert!(token.span.start < token.span.end);
+                    prop_assert_eq!(&s[token.span.start..token.span.end], token.text);
+                    end = token.span.end;
+                }
+                prop_assert_eq!(end, s.len());
+            }
         }
 
-        // Check for metacharacters ‘|’, ‘&’, ‘;’, ‘(’, ‘)’, ‘<’, or ‘>’
-        let metacharacters_token_opt = tokenize_metacharacter(&s, pointer);
-        if let Some((metacharacters_token, metacharacters_pointer)) = metacharacters_token_opt {
-            result.push(metacharacters_token);
-            pointer = metacharacters_pointer;
-            continue;
+        #[test]
+        fn test_render_round_trips(s in "\\PC*|([ \t\n'\"$\\\\|&;()<>`{}#=a-z0-9]|é)*") {
+            if let Ok(tokens) = tokenize(s.clone()) {
+                prop_assert_eq!(s, render(&tokens));
+            }
         }
     }
-
-    Ok(result)
 }
@@ -0,0 +1,374 @@
+use crate::arithmetic::Expression;
+use crate::lexer::regex::RegexToken;
+use crate::tokens::span::Span;
+
+/// A word as it's written in the source, adjacent quotes and expansions included : `a"b c"$d` is a literal, a
+/// double-quoted string and a parameter expansion.
+pub use crate::tokens::word::Word;
+
+/// A script or the body of a compound command : and-or lists separated by `;`, `&` or newlines.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct List {
+    pub items: Vec<ListItem>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListItem {
+    pub and_or: AndOr,
+    pub separator: Separator,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Separator {
+    Sequential,   // `;`, a newline or nothing
+    Asynchronous, // &
+}
+
+/// Pipelines separated by `&&` and `||` : `make && make install || exit 1`
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AndOr {
+    pub first: Pipeline,
+    pub rest: Vec<(AndOrOperator, Pipeline)>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AndOrOperator {
+    And, // &&
+    Or,  // ||
+}
+
+/// Commands separated by `|`, optionally preceded by `time` and `!` : `time -p ! grep -q x file | wc -l`
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pipeline {
+    pub timed: Option<Timed>,
+    pub negated: bool,
+    pub commands: Vec<Command>,
+    pub operators: Vec<PipeOperator>, // The operator after every command but the last
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PipeOperator {
+    Pipe,               // |
+    PipeOutputAndError, // |&, short for `2>&1 |`
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Timed {
+    Default, // time
+    Posix,   // time -p
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Command {
+    Simple(SimpleCommand),
+    Compound(CompoundCommand),
+    FunctionDefinition(FunctionDefinition),
+    Coprocess(Coprocess),
+}
+
+/// Variable assignments, words and redirections in the order they're written, split by their role :
+/// `A=1 ls -l >out`
+///
+/// Like bash does, the arguments of declaration utilities stay words, including array assignments :
+/// `declare -A m=([k]=v)` has the word `m=([k]=v)`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimpleCommand {
+    pub assignments: Vec<Assignment>,
+    pub words: Vec<Word>,
+    pub redirections: Vec<Redirection>,
+    pub span: Span,
+}
+
+/// `name=value`, `name+=value`, `name[subscript]=value` or an array `name=(a [k]=v)`
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Assignment {
+    pub name: String,
+    pub subscript: Option<String>,
+    pub append: bool,
+    pub value: AssignmentValue,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AssignmentValue {
+    Scalar(Word),
+    Array(Vec<ArrayElement>),
+}
+
+/// An element of an array literal, `[key]=value` or just `value`
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArrayElement {
+    pub key: Option<Word>,
+    pub value: Word,
+}
+
+/// A compound command with the redirections that follow it : `{ ls; } >out`
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompoundCommand {
+    pub kind: CompoundKind,
+    pub redirections: Vec<Redirection>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompoundKind {
+    BraceGroup(List), // { list; }
+    Subshell(List),   // ( list )
+    If {
+        condition: List,
+        then_body: List,
+        elifs: Vec<(List, List)>,
+        else_body: Option<List>,
+    },
+    While {
+        condition: List,
+        body: List,
+    },
+    Until {
+        condition: List,
+        body: List,
+    },
+    For {
+        variable: Word,
+        words: Option<Vec<Word>>, // `None` without `in`, which loops over the positional parameters
+        body: List,
+    },
+    Case {
+        word: Word,
+        items: Vec<CaseItem>,
+    },
+    Select {
+        variable: Word,
+        words: Option<Vec<Word>>,
+        body: List,
+    },
+    Conditional(ConditionalExpression), // [[ expression ]]
+    Arithmetic(Expression),             // (( expression ))
+    ArithmeticFor {
+        init: Option<Expression>,      // `None` when it's left out
+        condition: Option<Expression>, // `None` when it's left out, which loops forever
+        update: Option<Expression>,
+        body: List,
+    },
+}
+
+/// The expression of a `[[ ]]` command or of the `test` and `[` builtins, `&&` (`-a`) binds tighter than
+/// `||` (`-o`)
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConditionalExpression {
+    Unary {
+        operator: UnaryTest,
+        operand: Word,
+    },
+    Binary {
+        left: Word,
+        operator: BinaryTest,
+        right: Word, // A pattern for `==` and `!=` in `[[ ]]`
+    },
+    Match {
+        left: Word,
+        right: Word,
+        regex: Vec<RegexToken>, // The elements of `right`, an extended regular expression
+    },
+    Not(Box<ConditionalExpression>),
+    And(Box<ConditionalExpression>, Box<ConditionalExpression>),
+    Or(Box<ConditionalExpression>, Box<ConditionalExpression>),
+    Group(Box<ConditionalExpression>),
+    Word(Word), // True if the word isn't empty
+}
+
+/// > [6.4 Bash Conditional Expressions](https://www.gnu.org/software/bash/manual/bash.html#Bash-Conditional-Expressions)
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnaryTest {
+    FileExists,        // -a, -e
+    BlockSpecial,      // -b
+    CharacterSpecial,  // -c
+    Directory,         // -d
+    RegularFile,       // -f
+    SetGroupId,        // -g
+    SymbolicLink,      // -h, -L
+    Sticky,            // -k
+    NamedPipe,         // -p
+    Readable,          // -r
+    NonEmptyFile,      // -s
+    Terminal,          // -t fd
+    SetUserId,         // -u
+    Writable,          // -w
+    Executable,        // -x
+    OwnedByGroup,      // -G
+    ModifiedSinceRead, // -N
+    OwnedByUser,       // -O
+    Socket,            // -S
+    EmptyString,       // -z
+    NonEmptyString,    // -n
+    OptionEnabled,     // -o optname
+    VariableSet,       // -v varname
+    NameReference,     // -R varname
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BinaryTest {
+    Equal,               // =, ==
+    NotEqual,            // !=
+    Less,                // <, sorts before
+    Greater,             // >, sorts after
+    IntegerEqual,        // -eq
+    IntegerNotEqual,     // -ne
+    IntegerLess,         // -lt
+    IntegerLessEqual,    // -le
+    IntegerGreater,      // -gt
+    IntegerGreaterEqual, // -ge
+    NewerThan,           // -nt
+    OlderThan,           // -ot
+    SameFile,            // -ef
+}
+
+impl UnaryTest {
+    pub fn from_operator(operator: &str) -> Option<UnaryTest> {
+        Some(match operator {
+            "-a" | "-e" => UnaryTest::FileExists,
+            "-b" => UnaryTest::BlockSpecial,
+            "-c" => UnaryTest::CharacterSpecial,
+            "-d" => UnaryTest::Directory,
+            "-f" => UnaryTest::RegularFile,
+            "-g" => UnaryTest::SetGroupId,
+            "-h" | "-L" => UnaryTest::SymbolicLink,
+            "-k" => UnaryTest::Sticky,
+            "-p" => UnaryTest::NamedPipe,
+            "-r" => UnaryTest::Readable,
+            "-s" => UnaryTest::NonEmptyFile,
+            "-t" => UnaryTest::Terminal,
+            "-u" => UnaryTest::SetUserId,
+            "-w" => UnaryTest::Writable,
+            "-x" => UnaryTest::Executable,
+            "-G" => UnaryTest::OwnedByGroup,
+            "-N" => UnaryTest::ModifiedSinceRead,
+            "-O" => UnaryTest::OwnedByUser,
+            "-S" => UnaryTest::Socket,
+            "-z" => UnaryTest::EmptyString,
+            "-n" => UnaryTest::NonEmptyString,
+            "-o" => UnaryTest::OptionEnabled,
+            "-v" => UnaryTest::VariableSet,
+            "-R" => UnaryTest::NameReference,
+            _ => return None,
+        })
+    }
+}
+
+impl BinaryTest {
+    /// The test for `operator`, `=~` isn't one of them as its right-hand side is a [ConditionalExpression::Match]
+    pub fn from_operator(operator: &str) -> Option<BinaryTest> {
+        Some(match operator {
+            "=" | "==" => BinaryTest::Equal,
+            "!=" => BinaryTest::NotEqual,
+            "<" => BinaryTest::Less,
+            ">" => BinaryTest::Greater,
+            "-eq" => BinaryTest::IntegerEqual,
+            "-ne" => BinaryTest::IntegerNotEqual,
+            "-lt" => BinaryTest::IntegerLess,
+            "-le" => BinaryTest::IntegerLessEqual,
+            "-gt" => BinaryTest::IntegerGreater,
+            "-ge" => BinaryTest::IntegerGreaterEqual,
+            "-nt" => BinaryTest::NewerThan,
+            "-ot" => BinaryTest::OlderThan,
+            "-ef" => BinaryTest::SameFile,
+            _ => return None,
+        })
+    }
+}
+
+/// `pattern | pattern) list ;;`
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CaseItem {
+    pub patterns: Vec<Word>,
+    pub body: Option<List>,
+    pub terminator: Option<CaseTerminator>, // `None` for a last item without one
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CaseTerminator {
+    Break,       // ;;
+    FallThrough, // ;& runs the next item's list
+    Continue,    // ;;& tests the next item's patterns
+}
+
+/// `name() compound-command`, or with the bash keyword `function name [()] compound-command`
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionDefinition {
+    pub name: Word,
+    pub body: CompoundCommand,
+    pub keyword: bool,
+    pub span: Span,
+}
+
+/// `coproc [NAME] command`, a name can only be given to a compound command
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coprocess {
+    pub name: Option<Word>,
+    pub body: Box<Command>,
+    pub span: Span,
+}
+
+/// `[n]operator word`, for here-documents the word is the delimiter.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Redirection {
+    pub fd: Option<u32>,
+    pub operator: RedirectionOperator,
+    pub target: Word,
+    pub here_document: Option<HereDocument>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RedirectionOperator {
+    Input,                 // <
+    Output,                // >
+    Append,                // >>
+    HereDocument,          // <<
+    HereDocumentStripTabs, // <<-
+    DuplicateInput,        // <&
+    DuplicateOutput,       // >&
+    ReadWrite,             // <>
+    Clobber,               // >|
+    HereString,            // <<<
+    OutputAndError,        // &>
+    AppendOutputAndError,  // &>>
+}
+
+/// The body of a here-document without the delimiter line, leading tabs are already stripped for `<<-`.
+/// When part of the delimiter is quoted the body isn't expanded.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HereDocument {
+    pub body: String,
+    pub quoted: bool,
+    pub span: Span,
+}
@@ -0,0 +1,409 @@
+use crate::expand::expand;
+use crate::parser::ast::{BinaryTest, ConditionalExpression, UnaryTest, Word};
+use crate::parser::{ParseError, MAX_DEPTH};
+use crate::tokens::span::Span;
+use std::collections::HashMap;
+
+/// parse_test
+/// > [4.1 Bourne Shell Builtins](https://www.gnu.org/software/bash/manual/bash.html#Bourne-Shell-Builtins)
+/// > `test expr` / `[ expr ]`
+/// > Evaluate a conditional expression expr and return a status of 0 (true) or 1 (false). [...] Expressions
+/// > may be combined using the following operators, listed in decreasing order of precedence. The
+/// > evaluation depends on the number of arguments.
+///
+/// Builds the expression of a `test` or `[` command from its words, the command name included : the words of
+/// a [SimpleCommand](crate::parser::ast::SimpleCommand). Unlike `[[ ]]`, the shell has already expanded and
+/// unquoted the arguments by the time `test` sees them, so `"-f"` and `\(` are an operator and a parenthesis.
+/// Arguments whose value depends on an expansion are never operators.
+pub fn parse_test(words: &[Word]) -> Result<ConditionalExpression, ParseError> {
+    let Some((name, arguments)) = words.split_first() else {
+        return Err(ParseError {
+            message: String::from("Expected `test` or `[`, found the end of input at index 0"),
+            span: Span::new(0, 0),
+        });
+    };
+    let end = words.last().map_or(0, |word| word.span.end);
+    let arguments = match value(name).as_deref() {
+        Some("test") => arguments,
+        Some("[") => match arguments.split_last() {
+            Some((last, rest)) if value(last).as_deref() == Some("]") => rest,
+            _ => {
+                return Err(ParseError {
+                    message: format!("Expected `]`, found the end of input at index {}", end),
+                    span: Span::new(end, end),
+                })
+            }
+        },
+        _ => {
+            return Err(ParseError {
+                message: format!(
+                    "Expected `test` or `[`, found `{}` at index {}",
+                    name.text(),
+                    name.span.start
+                ),
+                span: name.span,
+            })
+        }
+    };
+    let test = Test {
+        arguments,
+        values: arguments.iter().map(value).collect(),
+        end: arguments.last().map_or(name.span.end, |word| word.span.end),
+    };
+    test.parse_arguments(0, arguments.len())
+}
+
+/// The value of an argument without quotes, `None` when it depends on an expansion
+fn value(word: &Word) -> Option<String> {
+    if word.has_expansions() {
+        return None;
+    }
+    match expand(word, &HashMap::new()).ok()?.as_slice() {
+        [value] => Some(value.clone()),
+        _ => None,
+    }
+}
+
+struct Test<'a> {
+    arguments: &'a [Word],
+    values: Vec<Option<String>>,
+    end: usize,
+}
+
+impl Test<'_> {
+    fn is(&self, index: usize, text: &str) -> bool {
+        self.values
+            .get(index)
+            .is_some_and(|value| value.as_deref() == Some(text))
+    }
+
+    fn unary(&self, index: usize) -> Option<UnaryTest> {
+        UnaryTest::from_operator(self.values.get(index)?.as_deref()?)
+    }
+
+    fn binary(&self, index: usize) -> Option<BinaryTest> {
+        BinaryTest::from_operator(self.values.get(index)?.as_deref()?)
+    }
+
+    /// > 0 arguments : The expression is false.
+    /// > 1 argument : The expression is true if, and only if, the argument is not null.
+    /// > 2 arguments : If the first argument is ‘!’, the expression is true if and only if the second argument
+    /// > is null. If the first argument is one of the unary conditional operators, the expression is true if the
+    /// > unary test is true.
+    /// > 3 arguments : If the second argument is one of the binary conditional operators, the result of the
+    /// > expression is the result of the binary test using the first and third arguments as operands. The ‘-a’
+    /// > and ‘-o’ operators are considered binary operators when there are three arguments. If the first
+    /// > argument is ‘!’, the value is the negation of the two-argument test using the second and third
+    /// > arguments. If the first argument is exactly ‘(’ and the third argument is exactly ‘)’, the result is
+    /// > the one-argument test of the second argument.
+    /// > 4 arguments : If the first argument is ‘!’, the result is the negation of the three-argument expression
+    /// > composed of the remaining arguments. If the first argument is exactly ‘(’ and the fourth argument is
+    /// > exactly ‘)’, the result is the two-argument test of the second and third arguments.
+    /// > 5 or more arguments : The expression is parsed and evaluated according to precedence.
+    fn parse_arguments(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Result<ConditionalExpression, ParseError> {
+        let word = |index: usize| self.arguments[index].clone();
+        let (unary, binary) = (self.unary(start), self.binary(start + 1));
+        match (end - start, unary, binary) {
+            (0, ..) => Ok(ConditionalExpression::Word(Word {
+                parts: Vec::new(),
+                span: Span::new(self.end, self.end),
+            })),
+            (1, ..) => Ok(ConditionalExpression::Word(word(start))),
+            (3, ..) if self.is(start + 1, "-a") || self.is(start + 1, "-o") => {
+                let (left, right) = (
+                    Box::new(ConditionalExpression::Word(word(start))),
+                    Box::new(ConditionalExpression::Word(word(start + 2))),
+                );
+                match self.is(start + 1, "-a") {
+                    true => Ok(ConditionalExpression::And(left, right)),
+                    false => Ok(ConditionalExpression::Or(left, right)),
+                }
+            }
+            (3, _, Some(operator)) => Ok(ConditionalExpression::Binary {
+                left: word(start),
+                operator,
+                right: word(start + 2),
+            }),
+            (2..=4, ..) if self.is(start, "!") => {
+                let expression = self.parse_arguments(start + 1, end)?;
+                Ok(ConditionalExpression::Not(Box::new(expression)))
+            }
+            (2, Some(operator), _) => Ok(ConditionalExpression::Unary {
+                operator,
+                operand: word(start + 1),
+            }),
+            (3 | 4, ..) if self.is(start, "(") && self.is(end - 1, ")") => {
+                let expression = self.parse_arguments(start + 1, end - 1)?;
+                Ok(ConditionalExpression::Group(Box::new(expression)))
+            }
+            _ => {
+                let mut position = start;
+                let expression = self.parse_or(&mut position, end, 0)?;
+                match position < end {
+                    true => Err(self.unexpected(position)),
+                    false => Ok(expression),
+                }
+            }
+        }
+    }
+
+    /// or : and ('-o' and)*, `depth` counts the enclosing `!` and `(`
+    fn parse_or(
+        &self,
+        position: &mut usize,
+        end: usize,
+        depth: usize,
+    ) -> Result<ConditionalExpression, ParseError> {
+        let mut expression = self.parse_and(position, end, depth)?;
+        while *position < end && self.is(*position, "-o") {
+            *position += 1;
+            let right = self.parse_and(position, end, depth)?;
+            expression = ConditionalExpression::Or(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    /// and : not ('-a' not)*
+    fn parse_and(
+        &self,
+        position: &mut usize,
+        end: usize,
+        depth: usize,
+    ) -> Result<ConditionalExpression, ParseError> {
+        let mut expression = self.parse_not(position, end, depth)?;
+        while *position < end && self.is(*position, "-a") {
+            *position += 1;
+            let right = self.parse_not(position, end, depth)?;
+            expression = ConditionalExpression::And(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    /// not : '!' not | '(' or ')' | word binary word | unary word | word
+    fn parse_not(
+        &self,
+        position: &mut usize,
+        end: usize,
+        depth: usize,
+    ) -> Result<ConditionalExpression, ParseError> {
+        if *position >= end {
+            return Err(self.expected("an argument", *position));
+        }
+        let start = *position;
+        if depth == MAX_DEPTH {
+            let span = self.arguments[start].span;
+            return Err(ParseError {
+                message: format!(
+                    "Nesting deeper than {} levels at index {}",
+                    MAX_DEPTH, span.start
+                ),
+                span,
+            });
+        }
+        if self.is(start, "!") {
+            *position += 1;
+            let expression = self.parse_not(position, end, depth + 1)?;
+            return Ok(ConditionalExpression::Not(Box::new(expression)));
+        }
+        if self.is(start, "(") {
+            *position += 1;
+            let expression = self.parse_or(position, end, depth + 1)?;
+            if *position >= end || !self.is(*position, ")") {
+                return Err(self.expected("`)`", *position));
+            }
+            *position += 1;
+            return Ok(ConditionalExpression::Group(Box::new(expression)));
+        }
+        let word = |index: usize| self.arguments[index].clone();
+        if let Some(operator) = self.binary(start + 1).filter(|_| start + 2 < end) {
+            *position += 3;
+            return Ok(ConditionalExpression::Binary {
+                left: word(start),
+                operator,
+                right: word(start + 2),
+            });
+        }
+        if let Some(operator) = self.unary(start).filter(|_| start + 1 < end) {
+            *position += 2;
+            return Ok(ConditionalExpression::Unary {
+                operator,
+                operand: word(start + 1),
+            });
+        }
+        *position += 1;
+        Ok(ConditionalExpression::Word(word(start)))
+    }
+
+    fn expected(&self, expected: &str, position: usize) -> ParseError {
+        match self.arguments.get(position) {
+            Some(argument) => ParseError {
+                message: format!(
+                    "Expected {}, found `{}` at index {}",
+                    expected,
+                    argument.text(),
+                    argument.span.start
+                ),
+                span: argument.span,
+            },
+            None => ParseError {
+                message: format!(
+                    "Expected {}, found the end of input at index {}",
+                    expected, self.end
+                ),
+                span: Span::new(self.end, self.end),
+            },
+        }
+    }
+
+    fn unexpected(&self, position: usize) -> ParseError {
+        let argument = &self.arguments[position];
+        ParseError {
+            message: format!(
+                "Unexpected `{}` at index {}",
+                argument.text(),
+                argument.span.start
+            ),
+            span: argument.span,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::ast::{BinaryTest, Command, ConditionalExpression, UnaryTest};
+    use crate::parser::conditional::parse_test;
+    use crate::parser::{parse, ParseError};
+    use crate::tokenize;
+
+    fn test(command: &str) -> Result<ConditionalExpression, ParseError> {
+        let list = parse(&tokenize(command.to_string()).unwrap()).unwrap();
+        let Command::Simple(command) = &list.items[0].and_or.first.commands[0] else {
+            panic!("Expected a simple command");
+        };
+        parse_test(&command.words)
+    }
+
+    /// The expression with every word replaced by its text, to compare trees without spans
+    fn describe(expression: &ConditionalExpression) -> String {
+        match expression {
+            ConditionalExpression::Unary { operator, operand } => {
+                format!("{:?}({})", operator, operand.text())
+            }
+            ConditionalExpression::Binary {
+                left,
+                operator,
+                right,
+            } => format!("{:?}({}, {})", operator, left.text(), right.text()),
+            ConditionalExpression::Match { left, right, .. } => {
+                format!("Match({}, {})", left.text(), right.text())
+            }
+            ConditionalExpression::Not(expression) => format!("!{}", describe(expression)),
+            ConditionalExpression::And(left, right) => {
+                format!("({} && {})", describe(left), describe(right))
+            }
+            ConditionalExpression::Or(left, right) => {
+                format!("({} || {})", describe(left), describe(right))
+            }
+            ConditionalExpression::Group(expression) => format!("[{}]", describe(expression)),
+            ConditionalExpression::Word(word) => format!("`{}`", word.text()),
+        }
+    }
+
+    fn tree(command: &str) -> String {
+        describe(&test(command).unwrap())
+    }
+
+    #[test]
+    fn test_parse_test_argument_counts() {
+        assert_eq!("``", tree("test"));
+        assert_eq!("``", tree("[ ]"));
+        assert_eq!("`-n`", tree("[ -n ]"));
+        assert_eq!("NonEmptyString(\"$x\")", tree("[ -n \"$x\" ]"));
+        assert_eq!("!`$x`", tree("test ! $x"));
+        assert_eq!("Equal(\"$a\", b)", tree("[ \"$a\" = b ]"));
+        assert_eq!("Equal(!, x)", tree("[ ! = x ]"));
+        assert_eq!("(`a` && `b`)", tree("test a -a b"));
+        assert_eq!("(`a` || `-f`)", tree("test a -o -f"));
+        assert_eq!("!RegularFile(x)", tree("[ ! -f x ]"));
+        assert_eq!("[`-d`]", tree("test \\( -d \\)"));
+        assert_eq!("!IntegerLess(1, 2)", tree("test ! 1 -lt 2"));
+        assert_eq!("[SymbolicLink(x)]", tree("[ '(' -L x ')' ]"));
+    }
+
+    #[test]
+    fn test_parse_test_precedence() {
+        assert_eq!(
+            "((Directory(a) && !Writable(b)) || IntegerGreaterEqual($n, 3))",
+            tree("[ -d a -a ! -w b -o $n -ge 3 ]")
+        );
+        assert_eq!(
+            "([(`a` || `b`)] && Less(x, y))",
+            tree("test \\( a -o b \\) -a x \"<\" y")
+        );
+        assert_eq!("(FileExists(a) && `-e`)", tree("test -e a -a -e"));
+        // An argument that depends on an expansion is an operand, whatever its value
+        assert_eq!("Equal($op, =)", tree("test $op = ="));
+        let ConditionalExpression::Or(left, right) = test("test -t 1 -o -S \"$x\"").unwrap() else {
+            panic!("Expected `-o`");
+        };
+        assert!(matches!(
+            *left,
+            ConditionalExpression::Unary {
+                operator: UnaryTest::Terminal,
+                ..
+            }
+        ));
+        assert!(matches!(
+            *right,
+            ConditionalExpression::Unary {
+                operator: UnaryTest::Socket,
+                ..
+            }
+        ));
+        assert!(matches!(
+            test("[ a -nt b -a c -ef d ]").unwrap(),
+            ConditionalExpression::And(left, _) if matches!(
+                *left,
+                ConditionalExpression::Binary { operator: BinaryTest::NewerThan, .. }
+            )
+        ));
+    }
+
+    #[test]
+    fn test_parse_test_errors() {
+        assert_eq!(
+            "Expected `]`, found the end of input at index 9",
+            test("[ -f x -a").unwrap_err().message
+        );
+        assert_eq!(
+            "Expected `test` or `[`, found `ls` at index 0",
+            test("ls -f x").unwrap_err().message
+        );
+        assert_eq!(
+            "Expected `)`, found the end of input at index 19",
+            test("test \\( a -a b -o c").unwrap_err().message
+        );
+        assert_eq!(
+            "Unexpected `b` at index 7",
+            test("test a b").unwrap_err().message
+        );
+        assert_eq!(
+            "Expected an argument, found the end of input at index 13",
+            test("[ a -a b -o ! ]").unwrap_err().message
+        );
+        assert_eq!(
+            "Expected `test` or `[`, found the end of input at index 0",
+            parse_test(&[]).unwrap_err().message
+        );
+        assert_eq!(
+            "Nesting deeper than 128 levels at index 261",
+            test(&format!("test {}a -a b", "! ".repeat(5000)))
+                .unwrap_err()
+                .message
+        );
+    }
+}
@@ -0,0 +1,1997 @@
+use crate::arithmetic::{parse_at, Expression};
+use crate::dialect::{BashVersion, Dialect};
+use crate::lexer::here_document::here_document_delimiter;
+use crate::lexer::nested::tokenize_nested;
+use crate::lexer::regex::tokenize_regex;
+use crate::parser::ast::{
+    AndOr, AndOrOperator, ArrayElement, Assignment, AssignmentValue, BinaryTest, CaseItem,
+    CaseTerminator, Command, CompoundCommand, CompoundKind, ConditionalExpression, Coprocess,
+    FunctionDefinition, HereDocument, List, ListItem, PipeOperator, Pipeline, Redirection,
+    RedirectionOperator, Separator, SimpleCommand, Timed, UnaryTest, Word,
+};
+use crate::tokens::span::Span;
+use crate::tokens::tokens::{Token, TokenKind};
+use crate::tokens::word::{WordPart, WordPartKind};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+pub mod ast;
+pub mod conditional;
+
+/// A syntax error, `span` points at the token that didn't fit the grammar. When the input ended too early
+/// it's the empty span at the end of the input.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// parse
+/// > [2.10 Shell Grammar](https://pubs.opengroup.org/onlinepubs/9799919799/utilities/V3_chap02.html#tag_19_10)
+/// > The following grammar defines the Shell Command Language.
+///
+/// Builds the syntax tree of the script `tokens` were tokenized from, see [tokenize](crate::tokenize).
+/// Adjacent word tokens (`a"b"$c`) form a single [Word], reserved words are only recognized where a command
+/// can start so `echo if` is a simple command. Whitespace and comments are skipped and here-document bodies are
+/// attached to their redirection. Commands nested more than 128 levels deep are a syntax error. The expressions
+/// of `((...))` and `for ((...))` are parsed with [arithmetic](crate::arithmetic), their errors are syntax errors.
+pub fn parse(tokens: &[Token]) -> Result<List, ParseError> {
+    parse_with_dialect(tokens, &Dialect::BASH)
+}
+
+/// parse_with_dialect
+/// Like [parse], for tokens from [tokenize_with_dialect](crate::tokenize_with_dialect). `[[` is only a
+/// compound command when `dialect.conditional_command` is on, otherwise it's a command name like `[`.
+pub fn parse_with_dialect(tokens: &[Token], dialect: &Dialect) -> Result<List, ParseError> {
+    let mut parser = Parser::new(tokens, *dialect);
+    let list = parser.parse_list(&[])?;
+    match parser.peek() {
+        Some(symbol) => Err(unexpected(symbol)),
+        None => Ok(list),
+    }
+}
+
+/// Reserved words that end a list, so they can't start a command
+const TERMINATORS: [&str; 9] = [
+    "}", "then", "elif", "else", "fi", "do", "done", "esac", "]]",
+];
+
+/// Reserved words that start a compound command, besides `(`
+const COMPOUND_COMMANDS: [&str; 8] = ["{", "if", "while", "until", "for", "case", "select", "[["];
+
+/// > [3.2.1 Reserved Words](https://www.gnu.org/software/bash/manual/bash.html#Reserved-Words)
+/// > Reserved words are words that have special meaning to the shell. They are used to begin and end the
+/// > shell’s compound commands.
+///
+/// They're only reserved when unquoted and the first word of a command, `in` and `do` after `for NAME` and `case WORD`.
+pub(crate) const RESERVED_WORDS: [&str; 22] = [
+    "if", "then", "elif", "else", "fi", "time", "for", "in", "until", "while", "do", "done",
+    "case", "esac", "coproc", "select", "function", "{", "}", "[[", "]]", "!",
+];
+
+/// How deep lists, compound commands and conditional expressions can nest, past this `parse` returns an error
+/// instead of running out of stack
+const MAX_DEPTH: usize = 128;
+
+/// Builtins that take assignments as arguments, which can be array literals : `local a=(1 2)`
+const DECLARATION_UTILITIES: [&str; 5] = ["declare", "typeset", "local", "export", "readonly"];
+
+/// What the parser works with : tokens with the words grouped and whitespace left out.
+#[derive(Debug, Clone)]
+enum Symbol {
+    Word(Word),
+    Operator(TokenKind, Token),
+    Newline(Span),
+}
+
+impl Symbol {
+    fn span(&self) -> Span {
+        match self {
+            Symbol::Word(word) => word.span,
+            Symbol::Operator(_, token) => token.span,
+            Symbol::Newline(span) => *span,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Symbol::Word(word) => format!("`{}`", word.text()),
+            Symbol::Operator(_, token) => format!("`{}`", token.text),
+            Symbol::Newline(_) => String::from("a newline"),
+        }
+    }
+}
+
+fn unexpected(symbol: &Symbol) -> ParseError {
+    ParseError {
+        message: format!(
+            "Unexpected {} at index {}",
+            symbol.describe(),
+            symbol.span().start
+        ),
+        span: symbol.span(),
+    }
+}
+
+struct Parser<'a> {
+    dialect: Dialect,
+    tokens: &'a [Token],
+    symbols: Vec<Symbol>,
+    position: usize,
+    here_documents: VecDeque<&'a Token>,
+    previous_end: usize,
+    end: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(tokens: &'a [Token], dialect: Dialect) -> Parser<'a> {
+        let mut symbols = Vec::new();
+        let mut word: Option<Word> = None;
+        for token in tokens {
+            if let Some(part) = WordPart::from_token(token, &dialect) {
+                let word = word.get_or_insert_with(|| Word {
+                    parts: Vec::new(),
+                    span: Span::new(token.span.start, token.span.start),
+                });
+                word.parts.push(part);
+                word.span.end = token.span.end;
+                continue;
+            }
+            if let Some(word) = word.take() {
+                symbols.push(Symbol::Word(word));
+            }
+            match token.kind {
+                TokenKind::WhiteSpace | TokenKind::Comment | TokenKind::HereDocument => {}
+                TokenKind::Newline => symbols.push(Symbol::Newline(token.span)),
+                kind => symbols.push(Symbol::Operator(kind, token.clone())),
+            }
+        }
+        symbols.extend(word.map(Symbol::Word));
+        // A line continuation between words isn't a word itself :
+        symbols.retain(|symbol| match symbol {
+            Symbol::Word(word) => !word.text().split("\\\n").all(str::is_empty),
+            _ => true,
+        });
+
+        Parser {
+            dialect,
+            tokens,
+            symbols,
+            position: 0,
+            here_documents: tokens
+                .iter()
+                .filter(|token| token.kind == TokenKind::HereDocument)
+                .collect(),
+            previous_end: 0,
+            end: tokens.last().map_or(0, |token| token.span.end),
+            depth: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&Symbol> {
+        self.symbols.get(self.position)
+    }
+
+    fn peek_word(&self) -> Option<&Word> {
+        match self.peek() {
+            Some(Symbol::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    fn peek_operator(&self) -> Option<TokenKind> {
+        match self.peek() {
+            Some(Symbol::Operator(kind, _)) => Some(*kind),
+            _ => None,
+        }
+    }
+
+    fn peek_reserved(&self, reserved: &str) -> bool {
+        self.peek_word()
+            .is_some_and(|word| is_reserved(word, reserved))
+    }
+
+    fn advance(&mut self) -> Option<Symbol> {
+        let symbol = self.symbols.get(self.position).cloned();
+        if let Some(symbol) = &symbol {
+            self.previous_end = symbol.span().end;
+            self.position += 1;
+        }
+        symbol
+    }
+
+    /// The tokens of `span` as one word, whitespace and operators are literal parts of it : `a=(1 "$x")`
+    fn source_word(&self, span: Span) -> Word {
+        let parts = self
+            .tokens
+            .iter()
+            .filter(|token| span.contains(&token.span))
+            .map(|token| {
+                WordPart::from_token(token, &self.dialect).unwrap_or_else(|| WordPart {
+                    kind: WordPartKind::Literal,
+                    text: token.text.clone(),
+                    span: token.span,
+                })
+            })
+            .collect();
+        Word { parts, span }
+    }
+
+    /// Whether the symbol `offset` symbols ahead starts a compound command
+    fn at_compound_command(&self, offset: usize) -> bool {
+        match self.symbols.get(self.position + offset) {
+            Some(Symbol::Operator(TokenKind::LParenthesis | TokenKind::ArithmeticCommand, _)) => {
+                true
+            }
+            Some(Symbol::Word(word)) => COMPOUND_COMMANDS.iter().any(|reserved| {
+                is_reserved(word, reserved)
+                    && (*reserved != "[[" || self.dialect.conditional_command)
+            }),
+            _ => false,
+        }
+    }
+
+    /// Whether the symbol `offset` symbols ahead is a `(` directly after the current word : `a=(`
+    fn at_adjacent_parenthesis(&self, offset: usize) -> bool {
+        match (
+            self.symbols.get(self.position + offset - 1),
+            self.symbols.get(self.position + offset),
+        ) {
+            (Some(previous), Some(Symbol::Operator(TokenKind::LParenthesis, parenthesis))) => {
+                previous.span().end == parenthesis.span.start
+            }
+            _ => false,
+        }
+    }
+
+    fn skip_newlines(&mut self) {
+        while let Some(Symbol::Newline(_)) = self.peek() {
+            self.advance();
+        }
+    }
+
+    /// Where the next symbol starts, or the end of the input
+    fn start(&self) -> usize {
+        self.peek().map_or(self.end, |symbol| symbol.span().start)
+    }
+
+    fn expected(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some(symbol) => ParseError {
+                message: format!(
+                    "Expected {}, found {} at index {}",
+                    expected,
+                    symbol.describe(),
+                    symbol.span().start
+                ),
+                span: symbol.span(),
+            },
+            None => ParseError {
+                message: format!(
+                    "Expected {}, found the end of input at index {}",
+                    expected, self.end
+                ),
+                span: Span::new(self.end, self.end),
+            },
+        }
+    }
+
+    /// Runs `parse` one nesting level deeper, see [MAX_DEPTH]
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        if self.depth == MAX_DEPTH {
+            let span = self
+                .peek()
+                .map_or(Span::new(self.end, self.end), Symbol::span);
+            return Err(ParseError {
+                message: format!(
+                    "Nesting deeper than {} levels at index {}",
+                    MAX_DEPTH, span.start
+                ),
+                span,
+            });
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn expect_reserved(&mut self, reserved: &str) -> Result<(), ParseError> {
+        if !self.peek_reserved(reserved) {
+            return Err(self.expected(&format!("`{}`", reserved)));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    fn expect_operator(&mut self, kind: TokenKind, text: &str) -> Result<(), ParseError> {
+        if self.peek_operator() != Some(kind) {
+            return Err(self.expected(&format!("`{}`", text)));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    fn expect_word(&mut self, expected: &str) -> Result<Word, ParseError> {
+        match self.peek() {
+            Some(Symbol::Word(word)) => {
+                let word = word.clone();
+                self.advance();
+                Ok(word)
+            }
+            _ => Err(self.expected(expected)),
+        }
+    }
+
+    /// list : and_or ((';' | '&' | newline) and_or)* up to one of `terminators`, `)`, `;;` or the end.
+    fn parse_list(&mut self, terminators: &[&str]) -> Result<List, ParseError> {
+        self.nested(|parser| parser.parse_list_items(terminators))
+    }
+
+    fn parse_list_items(&mut self, terminators: &[&str]) -> Result<List, ParseError> {
+        self.skip_newlines();
+        let start = self.start();
+        let mut items = Vec::new();
+        loop {
+            self.skip_newlines();
+            let at_end = match self.peek() {
+                None => true,
+                Some(Symbol::Operator(kind, _)) => {
+                    matches!(
+                        kind,
+                        TokenKind::RParenthesis
+                            | TokenKind::DoubleSemicolon
+                            | TokenKind::SemicolonAmpersand
+                            | TokenKind::DoubleSemicolonAmpersand
+                    )
+                }
+                Some(Symbol::Word(word)) => terminators
+                    .iter()
+                    .any(|terminator| is_reserved(word, terminator)),
+                Some(Symbol::Newline(_)) => false,
+            };
+            if at_end {
+                break;
+            }
+
+            let and_or = self.parse_and_or()?;
+            let separator = match self.peek_operator() {
+                Some(TokenKind::Semicolon) => Separator::Sequential,
+                Some(TokenKind::Ampersand) => Separator::Asynchronous,
+                _ => {
+                    let is_newline = matches!(self.peek(), Some(Symbol::Newline(_)));
+                    items.push(ListItem {
+                        and_or,
+                        separator: Separator::Sequential,
+                    });
+                    match is_newline {
+                        true => continue,
+                        false => break,
+                    }
+                }
+            };
+            self.advance();
+            items.push(ListItem { and_or, separator });
+        }
+        let end = match items.is_empty() {
+            true => start,
+            false => self.previous_end,
+        };
+        Ok(List {
+            items,
+            span: Span::new(start, end),
+        })
+    }
+
+    /// The body of a compound command, which can't be empty
+    fn parse_compound_list(&mut self, terminators: &[&str]) -> Result<List, ParseError> {
+        let list = self.parse_list(terminators)?;
+        if list.items.is_empty() {
+            return Err(self.expected("a command"));
+        }
+        Ok(list)
+    }
+
+    fn parse_and_or(&mut self) -> Result<AndOr, ParseError> {
+        let first = self.parse_pipeline()?;
+        let mut rest = Vec::new();
+        loop {
+            let operator = match self.peek_operator() {
+                Some(TokenKind::AndIf) => AndOrOperator::And,
+                Some(TokenKind::OrIf) => AndOrOperator::Or,
+                _ => break,
+            };
+            self.advance();
+            self.skip_newlines();
+            rest.push((operator, self.parse_pipeline()?));
+        }
+        Ok(AndOr {
+            span: Span::new(first.span.start, self.previous_end),
+            first,
+            rest,
+        })
+    }
+
+    fn parse_pipeline(&mut self) -> Result<Pipeline, ParseError> {
+        let start = self.start();
+        let mut timed = None;
+        if self.peek_reserved("time") {
+            self.advance();
+            timed = Some(Timed::Default);
+            if self.peek_reserved("-p") {
+                self.advance();
+                timed = Some(Timed::Posix);
+            }
+        }
+        let negated = self.peek_reserved("!");
+        if negated {
+            self.advance();
+        }
+        let mut commands = vec![self.parse_command()?];
+        let mut operators = Vec::new();
+        loop {
+            let operator = match self.peek_operator() {
+                Some(TokenKind::Pipe) => PipeOperator::Pipe,
+                Some(TokenKind::PipeAmpersand) => PipeOperator::PipeOutputAndError,
+                _ => break,
+            };
+            self.advance();
+            self.skip_newlines();
+            operators.push(operator);
+            commands.push(self.parse_command()?);
+        }
+        Ok(Pipeline {
+            timed,
+            negated,
+            commands,
+            operators,
+            span: Span::new(start, self.previous_end),
+        })
+    }
+
+    fn parse_command(&mut self) -> Result<Command, ParseError> {
+        if self.at_compound_command(0) {
+            return Ok(Command::Compound(self.parse_compound_command()?));
+        }
+        let Some(word) = self.peek_word() else {
+            return self.parse_simple_command().map(Command::Simple);
+        };
+        match literal(word) {
+            Some("function") => self
+                .parse_function_definition(true)
+                .map(Command::FunctionDefinition),
+            Some("coproc") => self.parse_coprocess().map(Command::Coprocess),
+            Some("]]") if !self.dialect.conditional_command => {
+                self.parse_simple_command().map(Command::Simple)
+            }
+            Some(text) if TERMINATORS.contains(&text) => {
+                Err(unexpected(&self.symbols[self.position]))
+            }
+            _ => {
+                // `a=()` is an empty array instead :
+                let is_function = !is_assignment(&word.text())
+                    && matches!(
+                        (
+                            self.symbols.get(self.position + 1),
+                            self.symbols.get(self.position + 2)
+                        ),
+                        (
+                            Some(Symbol::Operator(TokenKind::LParenthesis, _)),
+                            Some(Symbol::Operator(TokenKind::RParenthesis, _))
+                        )
+                    );
+                match is_function {
+                    true => self
+                        .parse_function_definition(false)
+                        .map(Command::FunctionDefinition),
+                    false => self.parse_simple_command().map(Command::Simple),
+                }
+            }
+        }
+    }
+
+    /// `name() compound-command`, with `keyword` for `function name [()] compound-command`
+    fn parse_function_definition(
+        &mut self,
+        keyword: bool,
+    ) -> Result<FunctionDefinition, ParseError> {
+        let start = self.start();
+        if keyword {
+            self.advance();
+        }
+        let name = self.expect_word("a function name")?;
+        if !keyword || self.peek_operator() == Some(TokenKind::LParenthesis) {
+            self.expect_operator(TokenKind::LParenthesis, "(")?;
+            self.expect_operator(TokenKind::RParenthesis, ")")?;
+        }
+        self.skip_newlines();
+        if !self.at_compound_command(0) {
+            return Err(self.expected("a compound command as function body"));
+        }
+        let body = self.parse_compound_command()?;
+        Ok(FunctionDefinition {
+            span: Span::new(start, body.span.end),
+            name,
+            body,
+            keyword,
+        })
+    }
+
+    /// `coproc [NAME] command`
+    fn parse_coprocess(&mut self) -> Result<Coprocess, ParseError> {
+        let start = self.start();
+        if let Some(keyword) = self.peek_word() {
+            let span = keyword.span;
+            self.dialect
+                .require_bash(BashVersion::new(4, 0), "coproc", span.start)
+                .map_err(|message| ParseError { message, span })?;
+        }
+        self.advance();
+        let mut name = None;
+        if !self.at_compound_command(0) && self.peek_word().is_some() && self.at_compound_command(1)
+        {
+            name = Some(self.expect_word("a name")?);
+        }
+        let body = match self.at_compound_command(0) {
+            true => Command::Compound(self.parse_compound_command()?),
+            false => Command::Simple(self.parse_simple_command()?),
+        };
+        Ok(Coprocess {
+            name,
+            body: Box::new(body),
+            span: Span::new(start, self.previous_end),
+        })
+    }
+
+    fn parse_simple_command(&mut self) -> Result<SimpleCommand, ParseError> {
+        let start = self.start();
+        let mut command = SimpleCommand {
+            assignments: Vec::new(),
+            words: Vec::new(),
+            redirections: Vec::new(),
+            span: Span::new(start, start),
+        };
+        loop {
+            if self.at_redirection() {
+                command.redirections.push(self.parse_redirection()?);
+                continue;
+            }
+            let Some(word) = self.peek_word() else {
+                break;
+            };
+            let text = word.text();
+            if command.words.is_empty() && is_assignment(&text) {
+                command.assignments.push(self.parse_assignment()?);
+                continue;
+            }
+            let is_declaration = command
+                .words
+                .first()
+                .and_then(literal)
+                .is_some_and(|first| DECLARATION_UTILITIES.contains(&first));
+            if is_declaration && is_assignment(&text) && self.at_adjacent_parenthesis(1) {
+                let assignment = self.parse_assignment()?;
+                command.words.push(self.source_word(assignment.span));
+                continue;
+            }
+            let word = word.clone();
+            self.advance();
+            command.words.push(word);
+        }
+        if command.assignments.is_empty()
+            && command.words.is_empty()
+            && command.redirections.is_empty()
+        {
+            return Err(self.expected("a command"));
+        }
+        command.span.end = self.previous_end;
+        Ok(command)
+    }
+
+    /// `name=value` or `name=(elements)`, the current word has to be an assignment
+    fn parse_assignment(&mut self) -> Result<Assignment, ParseError> {
+        let is_array = self.at_adjacent_parenthesis(1);
+        let word = self.expect_word("an assignment")?;
+        let text = word.text();
+        let (target, value) = text.split_once('=').unwrap_or((&text, ""));
+        let (target, append) = match target.strip_suffix('+') {
+            Some(target) => (target, true),
+            None => (target, false),
+        };
+        let (name, subscript) = match target.split_once('[') {
+            Some((name, subscript)) => (name, subscript.strip_suffix(']')),
+            None => (target, None),
+        };
+
+        let value = match is_array && value.is_empty() {
+            true => {
+                self.advance(); // (
+                let mut elements = Vec::new();
+                loop {
+                    self.skip_newlines();
+                    match self.peek() {
+                        Some(Symbol::Operator(TokenKind::RParenthesis, _)) => break,
+                        Some(Symbol::Word(_)) => {
+                            elements.push(array_element(self.expect_word("an element")?))
+                        }
+                        _ => return Err(self.expected("an array element or `)`")),
+                    }
+                }
+                self.advance(); // )
+                AssignmentValue::Array(elements)
+            }
+            false => AssignmentValue::Scalar(sub_word(&word, text.len() - value.len()..text.len())),
+        };
+        Ok(Assignment {
+            name: name.to_string(),
+            subscript: subscript.map(str::to_string),
+            append,
+            value,
+            span: Span::new(word.span.start, self.previous_end),
+        })
+    }
+
+    /// A redirection operator, or a number directly in front of one : `2>`
+    fn at_redirection(&self) -> bool {
+        if self
+            .peek_operator()
+            .is_some_and(|kind| kind.is_redirection())
+        {
+            return true;
+        }
+        self.io_number().is_some()
+    }
+
+    fn io_number(&self) -> Option<u32> {
+        let word = self.peek_word()?;
+        match self.symbols.get(self.position + 1)? {
+            Symbol::Operator(kind, operator)
+                if kind.is_redirection() && operator.span.start == word.span.end =>
+            {
+                literal(word)?.parse().ok()
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_redirection(&mut self) -> Result<Redirection, ParseError> {
+        let start = self.start();
+        let fd = self.io_number();
+        if fd.is_some() {
+            self.advance();
+        }
+        let Some(Symbol::Operator(kind, operator_word)) = self.advance() else {
+            return Err(self.expected("a redirection operator"));
+        };
+        let operator = match kind {
+            TokenKind::LessAnd => RedirectionOperator::Input,
+            TokenKind::GreatAnd => RedirectionOperator::Output,
+            TokenKind::DoubleGreat => RedirectionOperator::Append,
+            TokenKind::DoubleLess => RedirectionOperator::HereDocument,
+            TokenKind::DoubleLessDash => RedirectionOperator::HereDocumentStripTabs,
+            TokenKind::LessAmpersand => RedirectionOperator::DuplicateInput,
+            TokenKind::GreatAmpersand => RedirectionOperator::DuplicateOutput,
+            TokenKind::LessGreat => RedirectionOperator::ReadWrite,
+            TokenKind::TripleLess => RedirectionOperator::HereString,
+            TokenKind::AmpersandGreat => RedirectionOperator::OutputAndError,
+            TokenKind::AmpersandDoubleGreat => RedirectionOperator::AppendOutputAndError,
+            _ => RedirectionOperator::Clobber,
+        };
+        let target = self.expect_word(&format!("a word after `{}`", operator_word.text))?;
+
+        let here_document = match operator {
+            RedirectionOperator::HereDocument | RedirectionOperator::HereDocumentStripTabs => self
+                .here_documents
+                .pop_front()
+                .map(|token| here_document(token, &target, operator)),
+            _ => None,
+        };
+        Ok(Redirection {
+            fd,
+            operator,
+            target,
+            here_document,
+            span: Span::new(start, self.previous_end),
+        })
+    }
+
+    fn parse_compound_command(&mut self) -> Result<CompoundCommand, ParseError> {
+        let start = self.start();
+        let kind = self.nested(Self::parse_compound_kind)?;
+        let mut redirections = Vec::new();
+        while self.at_redirection() {
+            redirections.push(self.parse_redirection()?);
+        }
+        Ok(CompoundCommand {
+            kind,
+            redirections,
+            span: Span::new(start, self.previous_end),
+        })
+    }
+
+    fn parse_compound_kind(&mut self) -> Result<CompoundKind, ParseError> {
+        let kind = match self.advance() {
+            Some(Symbol::Operator(TokenKind::LParenthesis, _)) => {
+                let list = self.parse_compound_list(&[])?;
+                self.expect_operator(TokenKind::RParenthesis, ")")?;
+                CompoundKind::Subshell(list)
+            }
+            Some(Symbol::Word(word)) if is_reserved(&word, "{") => {
+                let list = self.parse_compound_list(&["}"])?;
+                self.expect_reserved("}")?;
+                CompoundKind::BraceGroup(list)
+            }
+            Some(Symbol::Word(word)) if is_reserved(&word, "if") => self.parse_if()?,
+            Some(Symbol::Word(word))
+                if is_reserved(&word, "while") || is_reserved(&word, "until") =>
+            {
+                let condition = self.parse_compound_list(&["do"])?;
+                let body = self.parse_do_group()?;
+                match literal(&word) {
+                    Some("while") => CompoundKind::While { condition, body },
+                    _ => CompoundKind::Until { condition, body },
+                }
+            }
+            Some(Symbol::Operator(TokenKind::ArithmeticCommand, token)) => {
+                let content = &token.text[2..token.text.len() - 2];
+                CompoundKind::Arithmetic(arithmetic(content, token.span.start + 2, &token)?)
+            }
+            Some(Symbol::Word(word)) if is_reserved(&word, "for") => {
+                if let Some(Symbol::Operator(TokenKind::ArithmeticCommand, token)) = self.peek() {
+                    let token = token.clone();
+                    self.advance();
+                    return self.parse_arithmetic_for(&token);
+                }
+                let (variable, words, body) = self.parse_for_clause()?;
+                CompoundKind::For {
+                    variable,
+                    words,
+                    body,
+                }
+            }
+            Some(Symbol::Word(word)) if is_reserved(&word, "select") => {
+                let (variable, words, body) = self.parse_for_clause()?;
+                CompoundKind::Select {
+                    variable,
+                    words,
+                    body,
+                }
+            }
+            Some(Symbol::Word(word)) if is_reserved(&word, "[[") => {
+                let expression = self.parse_conditional_or()?;
+                self.skip_newlines();
+                self.expect_reserved("]]")?;
+                CompoundKind::Conditional(expression)
+            }
+            Some(Symbol::Word(word)) if is_reserved(&word, "case") => self.parse_case()?,
+            other => {
+                if other.is_some() {
+                    self.position -= 1;
+                }
+                return Err(self.expected("a compound command"));
+            }
+        };
+        Ok(kind)
+    }
+
+    fn parse_if(&mut self) -> Result<CompoundKind, ParseError> {
+        let condition = self.parse_compound_list(&["then"])?;
+        self.expect_reserved("then")?;
+        let then_body = self.parse_compound_list(&["elif", "else", "fi"])?;
+
+        let mut elifs = Vec::new();
+        while self.peek_reserved("elif") {
+            self.advance();
+            let condition = self.parse_compound_list(&["then"])?;
+            self.expect_reserved("then")?;
+            elifs.push((
+                condition,
+                self.parse_compound_list(&["elif", "else", "fi"])?,
+            ));
+        }
+        let mut else_body = None;
+        if self.peek_reserved("else") {
+            self.advance();
+            else_body = Some(self.parse_compound_list(&["fi"])?);
+        }
+        self.expect_reserved("fi")?;
+        Ok(CompoundKind::If {
+            condition,
+            then_body,
+            elifs,
+            else_body,
+        })
+    }
+
+    fn parse_do_group(&mut self) -> Result<List, ParseError> {
+        self.expect_reserved("do")?;
+        let body = self.parse_compound_list(&["done"])?;
+        self.expect_reserved("done")?;
+        Ok(body)
+    }
+
+    /// What follows `for` and `select` : `name [in words]; do list; done`
+    fn parse_for_clause(&mut self) -> Result<(Word, Option<Vec<Word>>, List), ParseError> {
+        let variable = self.expect_word("a variable name")?;
+        if !is_name(&variable.text()) {
+            return Err(ParseError {
+                message: format!(
+                    "Invalid variable name `{}` at index {}",
+                    variable.text(),
+                    variable.span.start
+                ),
+                span: variable.span,
+            });
+        }
+        self.skip_newlines();
+
+        let mut words = None;
+        if self.peek_reserved("in") {
+            self.advance();
+            let mut list = Vec::new();
+            while let Some(word) = self.peek_word() {
+                list.push(word.clone());
+                self.advance();
+            }
+            words = Some(list);
+            // The word list has to be terminated :
+            match self.peek() {
+                Some(Symbol::Operator(TokenKind::Semicolon, _)) | Some(Symbol::Newline(_)) => {
+                    self.advance();
+                }
+                _ => return Err(self.expected("`;` or a newline")),
+            }
+        } else if self.peek_operator() == Some(TokenKind::Semicolon) {
+            self.advance();
+        }
+        self.skip_newlines();
+        let body = self.parse_do_group()?;
+        Ok((variable, words, body))
+    }
+
+    /// What follows `for` in `for ((init; condition; update)); do list; done`, every expression can be left out
+    fn parse_arithmetic_for(&mut self, token: &Token) -> Result<CompoundKind, ParseError> {
+        let content = &token.text[2..token.text.len() - 2];
+        let expressions = split_arithmetic_for(content, &self.dialect)
+            .into_iter()
+            .map(|range| match content[range.clone()].trim().is_empty() {
+                true => Ok(None),
+                false => arithmetic(
+                    &content[range.clone()],
+                    token.span.start + 2 + range.start,
+                    token,
+                )
+                .map(Some),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let [init, condition, update] =
+            <[Option<Expression>; 3]>::try_from(expressions).map_err(|expressions| ParseError {
+                message: format!(
+                    "Expected 3 expressions in `for ((...))`, found {} at index {}",
+                    expressions.len(),
+                    token.span.start
+                ),
+                span: token.span,
+            })?;
+        if self.peek_operator() == Some(TokenKind::Semicolon) {
+            self.advance();
+        }
+        self.skip_newlines();
+        let body = self.parse_do_group()?;
+        Ok(CompoundKind::ArithmeticFor {
+            init,
+            condition,
+            update,
+            body,
+        })
+    }
+
+    fn parse_case(&mut self) -> Result<CompoundKind, ParseError> {
+        let word = self.expect_word("a word")?;
+        self.skip_newlines();
+        self.expect_reserved("in")?;
+
+        let mut items = Vec::new();
+        loop {
+            self.skip_newlines();
+            if self.peek_reserved("esac") {
+                self.advance();
+                break;
+            }
+            let start = self.start();
+            if self.peek_operator() == Some(TokenKind::CasePatternStart) {
+                self.advance();
+            }
+            let mut patterns = vec![self.expect_word("a pattern")?];
+            while self.peek_operator() == Some(TokenKind::CasePatternSeparator) {
+                self.advance();
+                patterns.push(self.expect_word("a pattern")?);
+            }
+            self.expect_operator(TokenKind::CasePatternEnd, ")")?;
+
+            let list = self.parse_list(&["esac"])?;
+            let body = match list.items.is_empty() {
+                true => None,
+                false => Some(list),
+            };
+            let terminator = match self.peek_operator() {
+                Some(TokenKind::DoubleSemicolon) => Some(CaseTerminator::Break),
+                Some(TokenKind::SemicolonAmpersand) => Some(CaseTerminator::FallThrough),
+                Some(TokenKind::DoubleSemicolonAmpersand) => Some(CaseTerminator::Continue),
+                _ => None,
+            };
+            if terminator.is_some() {
+                self.advance();
+            }
+            items.push(CaseItem {
+                patterns,
+                body,
+                terminator,
+                span: Span::new(start, self.previous_end),
+            });
+            if terminator.is_none() {
+                // Only the last item can leave out the `;;`
+                self.skip_newlines();
+                self.expect_reserved("esac")?;
+                break;
+            }
+        }
+        Ok(CompoundKind::Case { word, items })
+    }
+
+    fn parse_conditional_or(&mut self) -> Result<ConditionalExpression, ParseError> {
+        let mut expression = self.parse_conditional_and()?;
+        while self.peek_operator() == Some(TokenKind::OrIf) {
+            self.advance();
+            let right = self.parse_conditional_and()?;
+            expression = ConditionalExpression::Or(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    fn parse_conditional_and(&mut self) -> Result<ConditionalExpression, ParseError> {
+        let mut expression = self.parse_conditional_not()?;
+        while self.peek_operator() == Some(TokenKind::AndIf) {
+            self.advance();
+            let right = self.parse_conditional_not()?;
+            expression = ConditionalExpression::And(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    fn parse_conditional_not(&mut self) -> Result<ConditionalExpression, ParseError> {
+        self.nested(Self::parse_conditional_negation)
+    }
+
+    fn parse_conditional_negation(&mut self) -> Result<ConditionalExpression, ParseError> {
+        self.skip_newlines();
+        if self.peek_reserved("!") {
+            self.advance();
+            let expression = self.parse_conditional_not()?;
+            return Ok(ConditionalExpression::Not(Box::new(expression)));
+        }
+        let expression = self.parse_conditional_primary()?;
+        self.skip_newlines();
+        Ok(expression)
+    }
+
+    fn parse_conditional_primary(&mut self) -> Result<ConditionalExpression, ParseError> {
+        if self.peek_operator() == Some(TokenKind::LParenthesis) {
+            self.advance();
+            let expression = self.parse_conditional_or()?;
+            self.expect_operator(TokenKind::RParenthesis, ")")?;
+            return Ok(ConditionalExpression::Group(Box::new(expression)));
+        }
+        let word = match self.peek_word() {
+            Some(word) if !is_reserved(word, "]]") => word.clone(),
+            _ => return Err(self.expected("a conditional expression")),
+        };
+        self.advance();
+
+        let next_is_operand = self
+            .peek_word()
+            .is_some_and(|next| !is_reserved(next, "]]"));
+        let unary = literal(&word).and_then(UnaryTest::from_operator);
+        if let Some(operator) = unary.filter(|_| next_is_operand) {
+            let operand = self.expect_word("an operand")?;
+            return Ok(ConditionalExpression::Unary { operator, operand });
+        }
+        let operator = match self.peek() {
+            Some(Symbol::Word(operator))
+                if literal(operator).is_some_and(|text| {
+                    text == "=~" || BinaryTest::from_operator(text).is_some()
+                }) =>
+            {
+                operator.text()
+            }
+            Some(Symbol::Operator(TokenKind::LessAnd | TokenKind::GreatAnd, operator)) => {
+                operator.text.clone()
+            }
+            _ => return Ok(ConditionalExpression::Word(word)),
+        };
+        self.advance();
+        let right = self.parse_conditional_operand(operator == "=~")?;
+        match BinaryTest::from_operator(&operator) {
+            Some(operator) => Ok(ConditionalExpression::Binary {
+                left: word,
+                operator,
+                right,
+            }),
+            None => Ok(ConditionalExpression::Match {
+                regex: tokenize_regex(&right.text(), right.span.start),
+                left: word,
+                right,
+            }),
+        }
+    }
+
+    /// The right-hand side of a binary operator. Patterns and regular expressions can contain `(`, `)` and `|`
+    /// that aren't operators : `@(a|b)`, `^(a|b)$`, so adjacent symbols are joined into one word.
+    fn parse_conditional_operand(&mut self, regex: bool) -> Result<Word, ParseError> {
+        let mut operand: Option<Word> = None;
+        let mut depth = 0;
+        while let Some(symbol) = self.peek() {
+            if operand
+                .as_ref()
+                .is_some_and(|operand| operand.span.end != symbol.span().start)
+            {
+                break;
+            }
+            let operator = match symbol {
+                Symbol::Word(word) if !is_reserved(word, "]]") => None,
+                Symbol::Operator(TokenKind::LParenthesis, parenthesis) => {
+                    depth += 1;
+                    Some(parenthesis)
+                }
+                Symbol::Operator(TokenKind::RParenthesis, parenthesis) if depth > 0 => {
+                    depth -= 1;
+                    Some(parenthesis)
+                }
+                Symbol::Operator(TokenKind::Pipe, pipe) if depth > 0 || regex => Some(pipe),
+                _ => break,
+            };
+            let (parts, span) = match (operator, symbol) {
+                (Some(operator), _) => (
+                    vec![WordPart {
+                        kind: WordPartKind::Literal,
+                        text: operator.text.clone(),
+                        span: operator.span,
+                    }],
+                    operator.span,
+                ),
+                (None, Symbol::Word(word)) => (word.parts.clone(), word.span),
+                (None, _) => break,
+            };
+            self.advance();
+            let operand = operand.get_or_insert_with(|| Word {
+                parts: Vec::new(),
+                span: Span::new(span.start, span.start),
+            });
+            operand.parts.extend(parts);
+            operand.span.end = span.end;
+        }
+        operand.ok_or_else(|| self.expected("an operand"))
+    }
+}
+
+/// Builds the here-document of a `<<` redirection from the token holding its lines
+fn here_document(token: &Token, delimiter: &Word, operator: RedirectionOperator) -> HereDocument {
+    let (delimiter, quoted) = here_document_delimiter(&delimiter.text());
+    let strip_tabs = operator == RedirectionOperator::HereDocumentStripTabs;
+    let mut lines: Vec<&str> = token
+        .text
+        .split('\n')
+        .map(|line| match strip_tabs {
+            true => line.trim_start_matches('\t'),
+            false => line,
+        })
+        .collect();
+    // The last line is the delimiter, unless the input ended before it :
+    if lines.last() == Some(&delimiter.as_str()) {
+        lines.pop();
+    }
+    let body = lines
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect::<String>();
+    HereDocument {
+        body,
+        quoted,
+        span: token.span,
+    }
+}
+
+/// The text of `word` when it can be a reserved word : a single unquoted literal, `"if"` and `i\f` aren't
+/// Parses the arithmetic expression `content` of `token`, located at `offset`. Errors cover the whole token.
+fn arithmetic(content: &str, offset: usize, token: &Token) -> Result<Expression, ParseError> {
+    parse_at(content, offset).map_err(|error| ParseError {
+        message: error.to_string(),
+        span: token.span,
+    })
+}
+
+/// The byte ranges of the expressions of `for ((...))`, split at the `;` that aren't quoted or in an expansion
+fn split_arithmetic_for(content: &str, dialect: &Dialect) -> Vec<Range<usize>> {
+    let chars: Vec<char> = content.chars().collect();
+    let offsets: Vec<usize> = content
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(content.len()))
+        .collect();
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut pointer = 0;
+    while pointer < chars.len() {
+        if chars[pointer] == ';' {
+            ranges.push(offsets[start]..offsets[pointer]);
+            start = pointer + 1;
+        }
+        pointer = match tokenize_nested(&chars, pointer, dialect) {
+            Ok(nested_pointer) if nested_pointer > pointer => nested_pointer.min(chars.len()),
+            _ => pointer + 1,
+        };
+    }
+    ranges.push(offsets[start]..content.len());
+    ranges
+}
+
+fn literal(word: &Word) -> Option<&str> {
+    match word.parts.as_slice() {
+        [part] if part.kind == WordPartKind::Literal => Some(&part.text),
+        _ => None,
+    }
+}
+
+fn is_reserved(word: &Word, reserved: &str) -> bool {
+    literal(word) == Some(reserved)
+}
+
+/// > [name] A word consisting only of alphanumeric characters and underscores, and beginning with an
+/// > alphabetic character or an underscore.
+fn is_name(text: &str) -> bool {
+    text.starts_with(|char: char| char.is_ascii_alphabetic() || char == '_')
+        && text
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+/// `NAME=value`, `NAME+=value` or `NAME[subscript]=value`
+pub(crate) fn is_assignment(text: &str) -> bool {
+    let Some((target, _)) = text.split_once('=') else {
+        return false;
+    };
+    let target = target.strip_suffix('+').unwrap_or(target);
+    match target.split_once('[') {
+        Some((name, subscript)) => is_name(name) && subscript.ends_with(']'),
+        None => is_name(target),
+    }
+}
+
+/// The bytes `range` of the text of `word` as a word of its own : the value of `name=value`. A part that's cut
+/// in two stays a pattern or becomes a literal.
+fn sub_word(word: &Word, range: Range<usize>) -> Word {
+    let mut parts = Vec::new();
+    let mut offset = 0;
+    for part in &word.parts {
+        let (start, end) = (offset, offset + part.text.len());
+        offset = end;
+        let (cut_start, cut_end) = (start.max(range.start), end.min(range.end));
+        if cut_start >= cut_end {
+            continue;
+        }
+        if (cut_start, cut_end) == (start, end) {
+            parts.push(part.clone());
+            continue;
+        }
+        let kind = match part.kind {
+            WordPartKind::Pattern => WordPartKind::Pattern,
+            _ => WordPartKind::Literal,
+        };
+        // Tokens from an alias have the span of the alias name, not of their text :
+        let span = match part.span.end - part.span.start == part.text.len() {
+            true => Span::new(
+                part.span.start + cut_start - start,
+                part.span.start + cut_end - start,
+            ),
+            false => part.span,
+        };
+        parts.push(WordPart {
+            kind,
+            text: part.text[cut_start - start..cut_end - start].to_string(),
+            span,
+        });
+    }
+    let span = match (parts.first(), parts.last()) {
+        (Some(first), Some(last)) => Span::new(first.span.start, last.span.end),
+        _ => {
+            let start = (word.span.start + range.start).min(word.span.end);
+            Span::new(start, start)
+        }
+    };
+    Word { parts, span }
+}
+
+/// `[key]=value` or `value`
+fn array_element(word: Word) -> ArrayElement {
+    let text = word.text();
+    if let Some(index) = text.find("]=").filter(|_| text.starts_with('[')) {
+        let key = sub_word(&word, 1..index);
+        let value = sub_word(&word, index + 2..text.len());
+        return ArrayElement {
+            key: Some(key),
+            value,
+        };
+    }
+    ArrayElement {
+        key: None,
+        value: word,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arithmetic::{BinaryOperator, ExpressionKind, IncrementOperator};
+    use crate::dialect::{BashVersion, Dialect};
+    use crate::lexer::regex::RegexTokenKind;
+    use crate::parser::ast::{
+        AndOrOperator, AssignmentValue, BinaryTest, CaseTerminator, Command, CompoundKind,
+        ConditionalExpression, List, PipeOperator, RedirectionOperator, Separator, SimpleCommand,
+        Timed, UnaryTest,
+    };
+    use crate::parser::{parse, parse_with_dialect, ParseError};
+    use crate::tokens::span::Span;
+    use crate::tokens::word::{Word, WordPart, WordPartKind};
+    use crate::{tokenize, tokenize_with_dialect};
+    #[cfg(not(target_arch = "wasm32"))]
+    use proptest::prelude::*;
+
+    fn parse_str(s: &str) -> Result<List, ParseError> {
+        parse(&tokenize(s.to_string()).unwrap())
+    }
+
+    /// The only command of `s`
+    fn command(s: &str) -> Command {
+        let list = parse_str(s).unwrap();
+        assert_eq!(1, list.items.len());
+        let and_or = &list.items[0].and_or;
+        assert!(and_or.rest.is_empty());
+        assert_eq!(1, and_or.first.commands.len());
+        and_or.first.commands[0].clone()
+    }
+
+    fn simple(command: &Command) -> &SimpleCommand {
+        match command {
+            Command::Simple(simple) => simple,
+            _ => panic!("Expected a simple command, got {:?}", command),
+        }
+    }
+
+    fn words(command: &Command) -> Vec<String> {
+        let words = &simple(command).words;
+        words.iter().map(|word| word.text()).collect()
+    }
+
+    fn error(s: &str) -> (String, Span) {
+        let error = parse_str(s).unwrap_err();
+        (error.message, error.span)
+    }
+
+    #[test]
+    fn test_parse_simple_command() {
+        let parsed = command("A=1 B='x y' ls -l \"a b\"$c 2>/dev/null");
+        let simple = simple(&parsed);
+        let names: Vec<&str> = simple.assignments.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(vec!["A", "B"], names);
+        let AssignmentValue::Scalar(value) = &simple.assignments[1].value else {
+            panic!("Expected a scalar value");
+        };
+        assert_eq!("'x y'", value.text());
+        assert_eq!(Span::new(6, 11), value.span);
+        assert_eq!(vec!["ls", "-l", "\"a b\"$c"], words(&parsed));
+        assert_eq!(Span::new(18, 25), simple.words[2].span);
+        assert_eq!(1, simple.redirections.len());
+        assert_eq!(Some(2), simple.redirections[0].fd);
+        assert_eq!(RedirectionOperator::Output, simple.redirections[0].operator);
+        assert_eq!("/dev/null", simple.redirections[0].target.text());
+        assert_eq!(Span::new(0, 37), simple.span);
+
+        // Not an assignment after the command name, and no io number without a redirection right after it :
+        assert_eq!(vec!["make", "CC=gcc"], words(&command("make CC=gcc")));
+        assert_eq!(vec!["echo", "2", "a2"], words(&command("echo 2 >x a2>y")));
+        assert_eq!(
+            vec!["echo", "if", "then", "}"],
+            words(&command("echo if then }"))
+        );
+        assert_eq!(vec!["ls"], words(&command("ls \\\n")));
+    }
+
+    #[test]
+    fn test_parse_word_parts() {
+        let parsed = command("A='x y'$z ls pre\"$c\"*.txt");
+        let AssignmentValue::Scalar(value) = &simple(&parsed).assignments[0].value else {
+            panic!("Expected a scalar value");
+        };
+        let kinds = |word: &Word| -> Vec<WordPartKind> {
+            word.parts.iter().map(|part| part.kind.clone()).collect()
+        };
+        assert_eq!(
+            vec![WordPartKind::SingleQuoted, WordPartKind::ParameterExpansion],
+            kinds(value)
+        );
+        assert_eq!(Span::new(2, 9), value.span);
+        assert_eq!(
+            vec![
+                WordPartKind::Literal,
+                WordPartKind::DoubleQuoted(vec![WordPart {
+                    kind: WordPartKind::ParameterExpansion,
+                    text: String::from("$c"),
+                    span: Span::new(17, 19),
+                }]),
+                WordPartKind::Pattern,
+            ],
+            kinds(&simple(&parsed).words[1])
+        );
+
+        // `A=*` cuts the pattern token in two, the value is still a pattern :
+        let parsed = command("A=b* c[1]=");
+        let AssignmentValue::Scalar(value) = &simple(&parsed).assignments[0].value else {
+            panic!("Expected a scalar value");
+        };
+        assert_eq!(vec![WordPartKind::Pattern], kinds(value));
+        assert_eq!(
+            ("b*".to_string(), Span::new(2, 4)),
+            (value.text(), value.span)
+        );
+        let AssignmentValue::Scalar(value) = &simple(&parsed).assignments[1].value else {
+            panic!("Expected a scalar value");
+        };
+        assert_eq!(Span::new(10, 10), value.span);
+        assert!(value.parts.is_empty());
+
+        // Only unquoted reserved words are reserved :
+        assert_eq!(vec!["\"if\"", "a"], words(&command("\"if\" a")));
+        assert_eq!(vec!["\\{", "a"], words(&command("\\{ a")));
+    }
+
+    #[test]
+    fn test_parse_pipelines_and_lists() {
+        let list = parse_str("! a | b && c ||\n d; e & f\n\ng").unwrap();
+        let separators: Vec<Separator> = list.items.iter().map(|item| item.separator).collect();
+        assert_eq!(
+            vec![
+                Separator::Sequential,
+                Separator::Asynchronous,
+                Separator::Sequential,
+                Separator::Sequential
+            ],
+            separators
+        );
+        let and_or = &list.items[0].and_or;
+        assert!(and_or.first.negated);
+        assert_eq!(2, and_or.first.commands.len());
+        let operators: Vec<AndOrOperator> = and_or.rest.iter().map(|(op, _)| *op).collect();
+        assert_eq!(vec![AndOrOperator::And, AndOrOperator::Or], operators);
+        assert_eq!(Span::new(0, 18), and_or.span);
+        assert_eq!(Span::new(0, 28), list.span);
+
+        assert!(parse_str("").unwrap().items.is_empty());
+        assert!(parse_str("\n# only a comment\n").unwrap().items.is_empty());
+    }
+
+    #[test]
+    fn test_parse_grouping() {
+        let Command::Compound(compound) = command("(a; b) >out") else {
+            panic!("Expected a subshell");
+        };
+        let CompoundKind::Subshell(list) = &compound.kind else {
+            panic!("Expected a subshell");
+        };
+        assert_eq!(2, list.items.len());
+        assert_eq!("out", compound.redirections[0].target.text());
+        assert_eq!(Span::new(0, 11), compound.span);
+
+        let Command::Compound(compound) = command("{ a\nb; }") else {
+            panic!("Expected a brace group");
+        };
+        assert!(matches!(&compound.kind, CompoundKind::BraceGroup(list) if list.items.len() == 2));
+
+        let Command::Compound(compound) = command("{ cp a{,.bak}; }") else {
+            panic!("Expected a brace group");
+        };
+        let CompoundKind::BraceGroup(list) = &compound.kind else {
+            panic!("Expected a brace group");
+        };
+        assert_eq!(
+            vec!["cp", "a{,.bak}"],
+            words(&list.items[0].and_or.first.commands[0])
+        );
+        assert_eq!(vec!["{a,b}", "c"], words(&command("{a,b} c")));
+    }
+
+    #[test]
+    fn test_parse_if() {
+        let Command::Compound(compound) =
+            command("if a; then b; elif c\nthen d; elif e; then f; else g; h; fi")
+        else {
+            panic!("Expected an if");
+        };
+        let CompoundKind::If {
+            condition,
+            then_body,
+            elifs,
+            else_body,
+        } = compound.kind
+        else {
+            panic!("Expected an if");
+        };
+        assert_eq!(1, condition.items.len());
+        assert_eq!(1, then_body.items.len());
+        assert_eq!(2, elifs.len());
+        assert_eq!(2, else_body.unwrap().items.len());
+    }
+
+    #[test]
+    fn test_parse_loops() {
+        let Command::Compound(compound) = command("while a; do b; done") else {
+            panic!("Expected a while loop");
+        };
+        assert!(matches!(compound.kind, CompoundKind::While { .. }));
+        let Command::Compound(compound) = command("until a\ndo\nb\ndone") else {
+            panic!("Expected an until loop");
+        };
+        assert!(matches!(compound.kind, CompoundKind::Until { .. }));
+
+        let Command::Compound(compound) = command("for x in a 'b c'; do echo $x; done") else {
+            panic!("Expected a for loop");
+        };
+        let CompoundKind::For {
+            variable, words, ..
+        } = compound.kind
+        else {
+            panic!("Expected a for loop");
+        };
+        assert_eq!("x", variable.text());
+        let words: Vec<String> = words.unwrap().into_iter().map(|w| w.text()).collect();
+        assert_eq!(vec!["a", "'b c'"], words);
+
+        for s in ["for x do a; done", "for x; do a; done", "for x\ndo a; done"] {
+            let Command::Compound(compound) = command(s) else {
+                panic!("Expected a for loop");
+            };
+            assert!(matches!(
+                compound.kind,
+                CompoundKind::For { words: None, .. }
+            ));
+        }
+        let Command::Compound(compound) = command("for x in; do a; done") else {
+            panic!("Expected a for loop");
+        };
+        assert!(
+            matches!(compound.kind, CompoundKind::For { words: Some(words), .. } if words.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_parse_arithmetic_commands() {
+        let Command::Compound(compound) = command("((x > 1)) >out") else {
+            panic!("Expected an arithmetic command");
+        };
+        let CompoundKind::Arithmetic(expression) = compound.kind else {
+            panic!("Expected an arithmetic command");
+        };
+        assert!(matches!(
+            expression.kind,
+            ExpressionKind::Binary(BinaryOperator::Greater, _, _)
+        ));
+        assert_eq!(Span::new(2, 7), expression.span);
+        assert_eq!(1, compound.redirections.len());
+
+        let Command::Compound(compound) = command("(( x++ ))") else {
+            panic!("Expected an arithmetic command");
+        };
+        assert!(matches!(
+            compound.kind,
+            CompoundKind::Arithmetic(expression)
+                if expression.kind == ExpressionKind::Increment(IncrementOperator::PostIncrement, String::from("x"))
+        ));
+
+        let Command::Compound(compound) = command("for ((i=0;i<3;i++)); do echo $i; done") else {
+            panic!("Expected an arithmetic for loop");
+        };
+        let CompoundKind::ArithmeticFor {
+            init,
+            condition,
+            update,
+            body,
+        } = compound.kind
+        else {
+            panic!("Expected an arithmetic for loop");
+        };
+        assert_eq!(Span::new(6, 9), init.unwrap().span);
+        assert_eq!(Span::new(10, 13), condition.unwrap().span);
+        assert_eq!(Span::new(14, 17), update.unwrap().span);
+        assert_eq!(1, body.items.len());
+
+        let Command::Compound(compound) = command("for ((;;))\ndo :; done") else {
+            panic!("Expected an arithmetic for loop");
+        };
+        assert!(matches!(
+            compound.kind,
+            CompoundKind::ArithmeticFor {
+                init: None,
+                condition: None,
+                update: None,
+                ..
+            }
+        ));
+        assert_eq!(
+            (
+                String::from("Expected 3 expressions in `for ((...))`, found 2 at index 4"),
+                Span::new(4, 11)
+            ),
+            error("for ((1;2)); do :; done")
+        );
+        assert_eq!(
+            (
+                String::from("Unexpected end of arithmetic expression at index 5"),
+                Span::new(0, 7)
+            ),
+            error("((1 +))")
+        );
+        // A subshell in a subshell :
+        let Command::Compound(compound) = command("((ls) )") else {
+            panic!("Expected a subshell");
+        };
+        assert!(matches!(compound.kind, CompoundKind::Subshell(_)));
+    }
+
+    #[test]
+    fn test_parse_case() {
+        let Command::Compound(compound) =
+            command("case $1 in\n a|b) echo ab;;\n (c) ;;\n *) echo other\n esac")
+        else {
+            panic!("Expected a case");
+        };
+        let CompoundKind::Case { word, items } = compound.kind else {
+            panic!("Expected a case");
+        };
+        assert_eq!("$1", word.text());
+        assert_eq!(3, items.len());
+        let patterns: Vec<String> = items[0].patterns.iter().map(|w| w.text()).collect();
+        assert_eq!(vec!["a", "b"], patterns);
+        assert!(items[1].body.is_none());
+        assert_eq!("*", items[2].patterns[0].text());
+        assert!(items[2].body.is_some());
+
+        let terminators: Vec<Option<CaseTerminator>> =
+            items.iter().map(|item| item.terminator).collect();
+        assert_eq!(
+            vec![
+                Some(CaseTerminator::Break),
+                Some(CaseTerminator::Break),
+                None
+            ],
+            terminators
+        );
+
+        let Command::Compound(compound) =
+            command("case $x in a) echo a;& b) echo b;;& *) echo c;; esac")
+        else {
+            panic!("Expected a case");
+        };
+        let CompoundKind::Case { items, .. } = compound.kind else {
+            panic!("Expected a case");
+        };
+        let terminators: Vec<Option<CaseTerminator>> =
+            items.iter().map(|item| item.terminator).collect();
+        assert_eq!(
+            vec![
+                Some(CaseTerminator::FallThrough),
+                Some(CaseTerminator::Continue),
+                Some(CaseTerminator::Break)
+            ],
+            terminators
+        );
+
+        let Command::Compound(compound) =
+            command("case $(case y in y) ;; esac) in a|[bc]*) ;; esac")
+        else {
+            panic!("Expected a case");
+        };
+        let CompoundKind::Case { word, items } = compound.kind else {
+            panic!("Expected a case");
+        };
+        assert_eq!("$(case y in y) ;; esac)", word.text());
+        assert_eq!("[bc]*", items[0].patterns[1].text());
+
+        let Command::Compound(compound) = command("case x in esac") else {
+            panic!("Expected a case");
+        };
+        assert!(matches!(compound.kind, CompoundKind::Case { items, .. } if items.is_empty()));
+    }
+
+    #[test]
+    fn test_parse_function_definition() {
+        let Command::FunctionDefinition(function) = command("greet() { echo hi; } >&2") else {
+            panic!("Expected a function definition");
+        };
+        assert_eq!("greet", function.name.text());
+        assert!(matches!(function.body.kind, CompoundKind::BraceGroup(_)));
+        assert_eq!(
+            RedirectionOperator::DuplicateOutput,
+            function.body.redirections[0].operator
+        );
+        assert_eq!(Span::new(0, 24), function.span);
+
+        let Command::FunctionDefinition(function) = command("f ()\n(ls)") else {
+            panic!("Expected a function definition");
+        };
+        assert!(matches!(function.body.kind, CompoundKind::Subshell(_)));
+    }
+
+    #[test]
+    fn test_parse_here_documents() {
+        let list =
+            parse_str("cat <<EOF >out; cat <<-'E'\nhello $x\nEOF\n\tquoted\n\tE\necho").unwrap();
+        assert_eq!(3, list.items.len());
+        let first = &list.items[0].and_or.first.commands[0];
+        let redirection = &simple(first).redirections[0];
+        assert_eq!(RedirectionOperator::HereDocument, redirection.operator);
+        let here_document = redirection.here_document.as_ref().unwrap();
+        assert_eq!("hello $x\n", here_document.body);
+        assert!(!here_document.quoted);
+        assert_eq!(Span::new(27, 39), here_document.span);
+
+        let second = &list.items[1].and_or.first.commands[0];
+        let redirection = &simple(second).redirections[0];
+        assert_eq!(
+            RedirectionOperator::HereDocumentStripTabs,
+            redirection.operator
+        );
+        let here_document = redirection.here_document.as_ref().unwrap();
+        assert_eq!("quoted\n", here_document.body);
+        assert!(here_document.quoted);
+    }
+
+    #[test]
+    fn test_parse_conditional_expressions() {
+        let Command::Compound(compound) =
+            command("[[ -f x && ! $a =~ ^(a|b)$ || ( y < z ) ]] >out")
+        else {
+            panic!("Expected a conditional");
+        };
+        assert_eq!("out", compound.redirections[0].target.text());
+        let CompoundKind::Conditional(ConditionalExpression::Or(left, right)) = compound.kind
+        else {
+            panic!("Expected `||`");
+        };
+        let ConditionalExpression::And(unary, not) = *left else {
+            panic!("Expected `&&`");
+        };
+        assert!(matches!(
+            *unary,
+            ConditionalExpression::Unary { operator: UnaryTest::RegularFile, operand } if operand.text() == "x"
+        ));
+        let ConditionalExpression::Not(binary) = *not else {
+            panic!("Expected `!`");
+        };
+        let ConditionalExpression::Match {
+            left,
+            right: pattern,
+            regex,
+        } = *binary
+        else {
+            panic!("Expected `=~`");
+        };
+        assert_eq!(
+            ("$a", "^(a|b)$"),
+            (left.text().as_str(), pattern.text().as_str())
+        );
+        assert_eq!(Span::new(19, 26), pattern.span);
+        assert_eq!(7, regex.len());
+        assert_eq!(RegexTokenKind::Start, regex[0].kind);
+        assert_eq!(Span::new(25, 26), regex[6].span);
+        let ConditionalExpression::Group(group) = *right else {
+            panic!("Expected a group");
+        };
+        assert!(matches!(
+            *group,
+            ConditionalExpression::Binary {
+                operator: BinaryTest::Less,
+                ..
+            }
+        ));
+
+        let Command::Compound(compound) = command("[[ $x == @(a|b) ]]") else {
+            panic!("Expected a conditional");
+        };
+        assert!(matches!(
+            compound.kind,
+            CompoundKind::Conditional(ConditionalExpression::Binary { right, .. }) if right.text() == "@(a|b)"
+        ));
+        let Command::Compound(compound) = command("[[ (-n $x) ]]") else {
+            panic!("Expected a conditional");
+        };
+        assert!(matches!(
+            compound.kind,
+            CompoundKind::Conditional(ConditionalExpression::Group(_))
+        ));
+        let Command::Compound(compound) = command("[[ -n ]]") else {
+            panic!("Expected a conditional");
+        };
+        assert!(matches!(
+            compound.kind,
+            CompoundKind::Conditional(ConditionalExpression::Word(word)) if word.text() == "-n"
+        ));
+    }
+
+    #[test]
+    fn test_parse_select_and_coprocesses() {
+        let Command::Compound(compound) = command("select x in a b; do echo $x; break; done")
+        else {
+            panic!("Expected a select");
+        };
+        assert!(
+            matches!(compound.kind, CompoundKind::Select { words: Some(words), .. } if words.len() == 2)
+        );
+
+        let Command::Coprocess(coprocess) = command("coproc NAME { cat; }") else {
+            panic!("Expected a coprocess");
+        };
+        assert_eq!("NAME", coprocess.name.unwrap().text());
+        assert!(matches!(*coprocess.body, Command::Compound(_)));
+        assert_eq!(Span::new(0, 20), coprocess.span);
+
+        let Command::Coprocess(coprocess) = command("coproc cat file") else {
+            panic!("Expected a coprocess");
+        };
+        assert!(coprocess.name.is_none());
+        assert_eq!(vec!["cat", "file"], words(&coprocess.body));
+    }
+
+    #[test]
+    fn test_parse_arrays() {
+        let parsed = command("a=(1 'two' [k]=v\\ w\n x) b+=(y) c[0]=z d=() ls");
+        let simple = simple(&parsed);
+        assert_eq!(vec!["ls"], words(&parsed));
+        assert_eq!(4, simple.assignments.len());
+
+        let AssignmentValue::Array(elements) = &simple.assignments[0].value else {
+            panic!("Expected an array");
+        };
+        let values: Vec<String> = elements.iter().map(|e| e.value.text()).collect();
+        assert_eq!(vec!["1", "'two'", "v\\ w", "x"], values);
+        assert_eq!("k", elements[2].key.as_ref().unwrap().text());
+        assert_eq!(Span::new(15, 19), elements[2].value.span);
+        assert_eq!(Span::new(0, 23), simple.assignments[0].span);
+
+        assert!(simple.assignments[1].append);
+        assert_eq!(Some("0"), simple.assignments[2].subscript.as_deref());
+        assert!(matches!(&simple.assignments[3].value, AssignmentValue::Array(e) if e.is_empty()));
+
+        assert_eq!(
+            vec!["declare", "-A", "m=([k]=v [l]=w)", "x=1"],
+            words(&command("declare -A m=([k]=v [l]=w) x=1"))
+        );
+    }
+
+    #[test]
+    fn test_parse_function_keyword_and_time() {
+        for s in [
+            "function f { echo; }",
+            "function f() { echo; }",
+            "function f\n{ echo; }",
+        ] {
+            let Command::FunctionDefinition(function) = command(s) else {
+                panic!("Expected a function definition");
+            };
+            assert!(function.keyword);
+            assert_eq!("f", function.name.text());
+        }
+
+        let list = parse_str("time -p ! a | b; time c").unwrap();
+        let first = &list.items[0].and_or.first;
+        assert_eq!(Some(Timed::Posix), first.timed);
+        assert!(first.negated);
+        assert_eq!(2, first.commands.len());
+        assert_eq!(Some(Timed::Default), list.items[1].and_or.first.timed);
+        assert_eq!(None, parse_str("a").unwrap().items[0].and_or.first.timed);
+    }
+
+    #[test]
+    fn test_parse_dialects() {
+        let list = parse_str("a |& b | c").unwrap();
+        assert_eq!(
+            vec![PipeOperator::PipeOutputAndError, PipeOperator::Pipe],
+            list.items[0].and_or.first.operators
+        );
+
+        let parsed = command("cat <<< 'a b' &>out &>>log");
+        let operators: Vec<RedirectionOperator> = simple(&parsed)
+            .redirections
+            .iter()
+            .map(|redirection| redirection.operator)
+            .collect();
+        assert_eq!(
+            vec![
+                RedirectionOperator::HereString,
+                RedirectionOperator::OutputAndError,
+                RedirectionOperator::AppendOutputAndError
+            ],
+            operators
+        );
+
+        // `[[` is a command name for dash, like `[`, and `&>` backgrounds the command :
+        let tokens = tokenize_with_dialect(String::from("[[ a ]] &>x"), &Dialect::DASH).unwrap();
+        let list = parse_with_dialect(&tokens, &Dialect::DASH).unwrap();
+        assert_eq!(2, list.items.len());
+        assert_eq!(Separator::Asynchronous, list.items[0].separator);
+        let command = &list.items[0].and_or.first.commands[0];
+        assert_eq!(vec!["[[", "a", "]]"], words(command));
+    }
+
+    #[test]
+    fn test_parse_bash_version() {
+        let bash_3_2 = Dialect {
+            bash_version: Some(BashVersion::new(3, 2)),
+            ..Dialect::BASH
+        };
+        let tokens = tokenize(String::from("a; coproc cat")).unwrap();
+        let error = parse_with_dialect(&tokens, &bash_3_2).unwrap_err();
+        assert_eq!(
+            "`coproc` requires bash 4.0 (targeting 3.2) at index 3",
+            error.message
+        );
+        assert_eq!(Span::new(3, 9), error.span);
+        assert!(parse(&tokens).is_ok());
+
+        let tokens = tokenize_with_dialect(String::from("a | b; echo coproc"), &bash_3_2).unwrap();
+        assert!(parse_with_dialect(&tokens, &bash_3_2).is_ok());
+    }
+
+    #[test]
+    fn test_parse_nesting_limit() {
+        let nested = |depth: usize, open: &str, close: &str| {
+            format!("{}ls{}", open.repeat(depth), close.repeat(depth))
+        };
+        assert!(parse_str(&nested(63, "( ", ")")).is_ok());
+        assert_eq!(
+            (
+                String::from("Nesting deeper than 128 levels at index 128"),
+                Span::new(128, 130)
+            ),
+            error(&nested(64, "( ", ")"))
+        );
+        assert_eq!(
+            String::from("Nesting deeper than 128 levels at index 128"),
+            error(&nested(5000, "( ", " )")).0
+        );
+        assert_eq!(
+            String::from("Nesting deeper than 128 levels at index 128"),
+            error(&nested(5000, "{ ", "; }")).0
+        );
+        assert!(parse_str(&format!("[[ {}a{} ]]", "(".repeat(120), ")".repeat(120))).is_ok());
+        assert_eq!(
+            String::from("Nesting deeper than 128 levels at index 255"),
+            error(&format!("[[ {}a ]]", "! ".repeat(5000))).0
+        );
+        assert_eq!(
+            String::from("Nesting deeper than 128 levels at index 129"),
+            error(&format!("[[ {}a{} ]]", "(".repeat(5000), ")".repeat(5000))).0
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            (
+                String::from("Expected `fi`, found the end of input at index 12"),
+                Span::new(12, 12)
+            ),
+            error("if a; then b")
+        );
+        assert_eq!(
+            (
+                String::from("Expected a command, found the end of input at index 4"),
+                Span::new(4, 4)
+            ),
+            error("ls |")
+        );
+        assert_eq!(
+            (String::from("Unexpected `fi` at index 3"), Span::new(3, 5)),
+            error("ls\nfi")
+        );
+        assert_eq!(
+            (String::from("Unexpected `)` at index 4"), Span::new(4, 5)),
+            error("(ls))")
+        );
+        assert_eq!(
+            (
+                String::from("Expected a command, found `;` at index 5"),
+                Span::new(5, 6)
+            ),
+            error("ls &&; id")
+        );
+        assert_eq!(
+            (
+                String::from("Expected a word after `>`, found a newline at index 6"),
+                Span::new(6, 7)
+            ),
+            error("echo >\nx")
+        );
+        assert_eq!(
+            (
+                String::from("Expected a command, found `}` at index 2"),
+                Span::new(2, 3)
+            ),
+            error("{ }")
+        );
+        assert_eq!(
+            (
+                String::from("Invalid variable name `1` at index 4"),
+                Span::new(4, 5)
+            ),
+            error("for 1 in a; do b; done")
+        );
+        assert_eq!(
+            (
+                String::from("Expected a compound command as function body, found `ls` at index 4"),
+                Span::new(4, 6)
+            ),
+            error("f() ls")
+        );
+        assert_eq!(
+            (
+                String::from("Expected `)`, found `;;` at index 11"),
+                Span::new(11, 13)
+            ),
+            error("case x in a;; esac")
+        );
+        assert_eq!(
+            (
+                String::from("Expected `]]`, found `b` at index 5"),
+                Span::new(5, 6)
+            ),
+            error("[[ a b ]]")
+        );
+        assert_eq!(
+            (
+                String::from("Expected a conditional expression, found `]]` at index 3"),
+                Span::new(3, 5)
+            ),
+            error("[[ ]]")
+        );
+        assert_eq!(
+            (
+                String::from("Expected an array element or `)`, found `;` at index 4"),
+                Span::new(4, 5)
+            ),
+            error("a=(b; c)")
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    proptest! {
+        #[test]
+        fn test_parse_never_panics(s in "([ \n'\"$|&;()<>{}#=!a-z]|if|then|fi|for|in|do|done|case|esac|<<E|\nE)*") {
+            if let Ok(tokens) = tokenize(s) {
+                let _ = parse(&tokens);
+            }
+        }
+    }
+}
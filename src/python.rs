@@ -0,0 +1,214 @@
+use crate::argv::to_argv;
+use crate::dialect::Dialect;
+use crate::injection::detect_shell_injection;
+use crate::quote::{join, quote};
+use crate::tokenize_with_dialect;
+use crate::tokens::tokens::Token;
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use std::error::Error;
+use std::ffi::OsString;
+
+create_exception!(
+    bash_tokenizer,
+    TokenizeError,
+    PyValueError,
+    "Raised for scripts the tokenizer rejects, e.g. an unterminated quote"
+);
+
+/// The location of a token as byte offsets into the UTF-8 encoded script, `end` is exclusive :
+/// `script.encode()[span.start:span.end]`
+#[pyclass(
+    name = "Span",
+    module = "bash_tokenizer",
+    frozen,
+    get_all,
+    eq,
+    hash,
+    skip_from_py_object
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PySpan {
+    start: usize,
+    end: usize,
+}
+
+#[pymethods]
+impl PySpan {
+    fn __repr__(&self) -> String {
+        format!("Span(start={}, end={})", self.start, self.end)
+    }
+}
+
+/// A token : its `kind` (`"Word"`, `"Pipe"`, ...), `text`, `span` and the `alias` it comes from, if any
+#[pyclass(
+    name = "Token",
+    module = "bash_tokenizer",
+    frozen,
+    get_all,
+    eq,
+    skip_from_py_object
+)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PyToken {
+    kind: String,
+    text: String,
+    span: PySpan,
+    alias: Option<String>,
+}
+
+#[pymethods]
+impl PyToken {
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "Token(kind='{}', text={}, span={})",
+            self.kind,
+            PyString::new(py, &self.text).repr()?,
+            self.span.__repr__()
+        ))
+    }
+}
+
+impl From<Token> for PyToken {
+    fn from(token: Token) -> Self {
+        PyToken {
+            kind: format!("{:?}", token.kind),
+            text: token.text,
+            span: PySpan {
+                start: token.span.start,
+                end: token.span.end,
+            },
+            alias: token.alias,
+        }
+    }
+}
+
+/// tokenize(script, dialect="bash")
+/// The tokens of `script`, for the `"bash"`, `"posix"`, `"dash"`, `"ksh93"`, `"zsh"` or `"ash"` dialect. Raises
+/// `TokenizeError` for scripts that can't be tokenized.
+#[pyfunction]
+#[pyo3(name = "tokenize", signature = (script, dialect = "bash"))]
+fn py_tokenize(script: &str, dialect: &str) -> PyResult<Vec<PyToken>> {
+    let dialect: Dialect = dialect
+        .parse()
+        .map_err(|error: Box<dyn Error>| PyValueError::new_err(error.to_string()))?;
+    let tokens = tokenize_with_dialect(script.to_string(), &dialect)
+        .map_err(|error| TokenizeError::new_err(error.to_string()))?;
+    Ok(tokens.into_iter().map(PyToken::from).collect())
+}
+
+/// detect_shell_injection(command, user_input)
+/// Whether `user_input`, spliced into `command`, changes the structure of the command.
+#[pyfunction]
+#[pyo3(name = "detect_shell_injection")]
+fn py_detect_shell_injection(command: &str, user_input: &str) -> bool {
+    detect_shell_injection(command, user_input)
+}
+
+/// quote(arg)
+/// `arg` in a form the shell reads back as exactly one word. Like `shlex.quote` it only uses single quotes,
+/// unless `arg` contains a control character : that takes bash's `$'...'`.
+#[pyfunction]
+#[pyo3(name = "quote")]
+fn py_quote(arg: &str) -> String {
+    quote(arg)
+}
+
+/// join(args)
+/// The quoted `args` separated by spaces, like `shlex.join`.
+#[pyfunction]
+#[pyo3(name = "join")]
+fn py_join(args: Vec<String>) -> String {
+    join(&args.iter().map(String::as_str).collect::<Vec<_>>())
+}
+
+/// to_argv(command)
+/// The arguments bash would pass to the simple command `command`, like `shlex.split`. Raises `ValueError`
+/// for anything that needs a running shell to evaluate, like `$HOME` or `*.txt`.
+#[pyfunction]
+#[pyo3(name = "to_argv")]
+fn py_to_argv(command: &str) -> PyResult<Vec<OsString>> {
+    to_argv(command).map_err(|error| PyValueError::new_err(error.to_string()))
+}
+
+#[pymodule]
+#[pyo3(name = "bash_tokenizer")]
+fn bash_tokenizer(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PySpan>()?;
+    module.add_class::<PyToken>()?;
+    module.add("TokenizeError", module.py().get_type::<TokenizeError>())?;
+    module.add_function(wrap_pyfunction!(py_tokenize, module)?)?;
+    module.add_function(wrap_pyfunction!(py_detect_shell_injection, module)?)?;
+    module.add_function(wrap_pyfunction!(py_quote, module)?)?;
+    module.add_function(wrap_pyfunction!(py_join, module)?)?;
+    module.add_function(wrap_pyfunction!(py_to_argv, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::python::bash_tokenizer;
+    use pyo3::prelude::*;
+    use pyo3::types::{PyDict, PyModule};
+    use std::ffi::CStr;
+
+    /// Runs the assertions of `code` with the module imported as `bash_tokenizer`
+    fn run(code: &CStr) {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "bash_tokenizer")?;
+            bash_tokenizer(&module)?;
+            let globals = PyDict::new(py);
+            globals.set_item("bash_tokenizer", module)?;
+            py.run(code, Some(&globals), None)
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_tokenize() {
+        run(cr#"
+tokens = bash_tokenizer.tokenize("echo $'a\\tb' | wc")
+assert [token.kind for token in tokens] == [
+    "Word", "WhiteSpace", "DollarSingleQuote", "WhiteSpace", "Pipe", "WhiteSpace", "Word"
+], tokens
+assert tokens[2].text == "$'a\\tb'"
+assert (tokens[2].span.start, tokens[2].span.end) == (5, 12)
+assert tokens[0].alias is None
+assert repr(tokens[0]) == "Token(kind='Word', text='echo', span=Span(start=0, end=4))"
+assert bash_tokenizer.tokenize("é x")[2].span.start == 3
+
+assert [token.kind for token in bash_tokenizer.tokenize("a |& b", dialect="dash")][2] == "Pipe"
+try:
+    bash_tokenizer.tokenize("echo 'a")
+    assert False
+except bash_tokenizer.TokenizeError as error:
+    assert str(error) == "Unterminated string literal at index 5"
+    assert isinstance(error, ValueError)
+try:
+    bash_tokenizer.tokenize("ls", "fish")
+    assert False
+except ValueError as error:
+    assert str(error) == "Unknown dialect `fish`"
+"#);
+    }
+
+    #[test]
+    fn test_helpers() {
+        run(cr#"
+assert bash_tokenizer.detect_shell_injection("ls a; rm -rf /", "a; rm -rf /")
+assert not bash_tokenizer.detect_shell_injection("ls 'a b'", "a b")
+assert bash_tokenizer.quote("it's") == "'it'\\''s'"
+assert bash_tokenizer.quote("a\tb") == "$'a\\tb'"
+assert bash_tokenizer.join(["ls", "a b"]) == "ls 'a b'"
+assert bash_tokenizer.to_argv("grep -e $'a\\tb' 'x y'") == ["grep", "-e", "a\tb", "x y"]
+try:
+    bash_tokenizer.to_argv("ls $HOME")
+    assert False
+except ValueError:
+    pass
+"#);
+    }
+}
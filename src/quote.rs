@@ -0,0 +1,171 @@
+use crate::parser::{is_assignment, RESERVED_WORDS};
+
+/// quote
+/// The inverse of tokenizing a word : returns `arg` in a form the shell reads back as exactly one word with
+/// the value `arg`. We pick the shortest form that is safe :
+/// - Bare, when every character is safe to use unquoted and it isn't a variable assignment or reserved word :
+///   `file.txt`
+/// - Single quotes when there's no control character, a single quote ends the quotes and is escaped with a
+///   backslash : `'hello world'`, `'it'\''s'`. Any POSIX shell reads these.
+/// - Dollar-single-quotes otherwise, escaping single quotes, backslashes and control characters : `$'a\tb\n'`.
+///   POSIX only added these in IEEE 1003.1-2024, dash reads them as a `$` and single quotes.
+pub fn quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg.chars().all(is_safe_unquoted)
+        && !is_assignment(arg)
+        && !RESERVED_WORDS.contains(&arg)
+    {
+        return arg.to_string();
+    }
+    if !arg.chars().any(char::is_control) {
+        return match arg.is_empty() {
+            true => String::from("''"),
+            false => arg
+                .split('\'')
+                .map(|part| match part.is_empty() {
+                    true => String::new(),
+                    false => format!("'{}'", part),
+                })
+                .collect::<Vec<String>>()
+                .join("\\'"),
+        };
+    }
+
+    let mut quoted = String::from("$'");
+    for char in arg.chars() {
+        match char {
+            '\'' => quoted.push_str(r"\'"),
+            '\\' => quoted.push_str(r"\\"),
+            '\u{07}' => quoted.push_str(r"\a"),
+            '\u{08}' => quoted.push_str(r"\b"),
+            '\u{1b}' => quoted.push_str(r"\e"),
+            '\u{0c}' => quoted.push_str(r"\f"),
+            '\n' => quoted.push_str(r"\n"),
+            '\r' => quoted.push_str(r"\r"),
+            '\t' => quoted.push_str(r"\t"),
+            '\u{0b}' => quoted.push_str(r"\v"),
+            char if char.is_control() => {
+                // Always two hexadecimal digits, so a following hexadecimal character isn't consumed :
+                let mut bytes = [0; 4];
+                for byte in char.encode_utf8(&mut bytes).bytes() {
+                    quoted.push_str(&format!(r"\x{:02X}", byte));
+                }
+            }
+            char => quoted.push(char),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// join
+/// Quotes every argument with [quote] and separates them with a space.
+pub fn join(args: &[&str]) -> String {
+    args.iter()
+        .map(|arg| quote(arg))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Characters that never have a special meaning in a word, wherever they occur.
+fn is_safe_unquoted(char: char) -> bool {
+    char.is_ascii_alphanumeric()
+        || ['_', '-', '.', '/', ':', ',', '+', '@', '%', '='].contains(&char)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::dialect::Dialect;
+    use crate::quote::{join, quote};
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::tokenize;
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::tokens::tokens::TokenKind;
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::tokens::word::{words, Word};
+    #[cfg(not(target_arch = "wasm32"))]
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_quote_bare() {
+        assert_eq!("ls", quote("ls"));
+        assert_eq!("-l", quote("-l"));
+        assert_eq!("/usr/bin/file.txt", quote("/usr/bin/file.txt"));
+        assert_eq!("--a=b,c:d@e%f+g", quote("--a=b,c:d@e%f+g"));
+        assert_eq!("123", quote("123"));
+    }
+
+    #[test]
+    fn test_quote_single_quotes() {
+        assert_eq!("''", quote(""));
+        assert_eq!("'hello world'", quote("hello world"));
+        assert_eq!("'; rm -rf /'", quote("; rm -rf /"));
+        assert_eq!("'$HOME'", quote("$HOME"));
+        assert_eq!("'~'", quote("~"));
+        assert_eq!("'a=b'", quote("a=b"));
+        assert_eq!("'a+='", quote("a+="));
+        assert_eq!("'FOO+=bar'", quote("FOO+=bar"));
+        assert_eq!("'if'", quote("if"));
+        assert_eq!("'*.txt'", quote("*.txt"));
+        assert_eq!("'a\"b'", quote("a\"b"));
+        assert_eq!("'a\\b'", quote("a\\b"));
+        assert_eq!("'héllo'", quote("héllo"));
+    }
+
+    #[test]
+    fn test_quote_spliced_single_quotes() {
+        assert_eq!(r"'it'\''s'", quote("it's"));
+        assert_eq!(r"\'", quote("'"));
+        assert_eq!(r"\'\''a b'", quote("''a b"));
+        assert_eq!(r"'a'\'", quote("a'"));
+        assert_eq!(r"'a\'\''b'", quote(r"a\'b"));
+    }
+
+    #[test]
+    fn test_quote_dollar_single_quotes() {
+        assert_eq!(r"$'it\'s\n'", quote("it's\n"));
+        assert_eq!(r"$'a\nb'", quote("a\nb"));
+        assert_eq!(r"$'\t\\\''", quote("\t\\'"));
+        assert_eq!(r"$'\x01F'", quote("\u{01}F"));
+        assert_eq!(r"$'\xC2\x85'", quote("\u{85}"));
+        assert_eq!(r"$'\e[0m'", quote("\u{1b}[0m"));
+    }
+
+    #[test]
+    fn test_join() {
+        assert_eq!("", join(&[]));
+        assert_eq!("ls -l", join(&["ls", "-l"]));
+        assert_eq!(
+            r"echo 'hello world' 'it'\''s' ''",
+            join(&["echo", "hello world", "it's", ""])
+        );
+        assert_eq!("'FOO+=bar' ls", join(&["FOO+=bar", "ls"]));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    proptest! {
+        #[test]
+        fn test_quote_is_one_word(arg in "\\PC*|[\\x00-\\x1f'\\\\ a-z0-9]*") {
+            let word = Word::parse(&quote(&arg), &Dialect::BASH).unwrap();
+            prop_assert!(!word.has_expansions());
+        }
+
+        #[test]
+        fn test_quote_without_control_characters_is_dash(arg in "[^\\x00-\\x1f\\x7f-\\x9f]*") {
+            let tokens = tokenize(quote(&arg)).unwrap();
+            prop_assert!(tokens.iter().all(|token| token.kind != TokenKind::DollarSingleQuote));
+            let word = Word::parse(&quote(&arg), &Dialect::DASH).unwrap();
+            prop_assert!(!word.has_expansions());
+        }
+
+        #[test]
+        fn test_join_separates_arguments(args in prop::collection::vec("\\PC*", 1..5)) {
+            let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+            let tokens = tokenize(join(&args)).unwrap();
+            let separators = tokens.iter().filter(|token| token.kind == TokenKind::WhiteSpace).count();
+            prop_assert_eq!(args.len() - 1, separators);
+            prop_assert_eq!(args.len(), words(&tokens, &Dialect::BASH).len());
+        }
+    }
+}
@@ -1 +1,4 @@
+pub mod span;
+#[allow(clippy::module_inception)]
 pub mod tokens;
+pub mod word;
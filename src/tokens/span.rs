@@ -0,0 +1,46 @@
+/// The location of a token in the tokenized string, as byte offsets. `end` is exclusive, so
+/// `&s[span.start..span.end]` is the text of the token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    /// Whether both spans share at least one byte
+    pub fn overlaps(&self, other: &Span) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// Whether `other` lies completely within this span
+    pub fn contains(&self, other: &Span) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokens::span::Span;
+
+    #[test]
+    fn test_span_overlaps() {
+        assert!(Span::new(0, 4).overlaps(&Span::new(3, 5)));
+        assert!(Span::new(3, 5).overlaps(&Span::new(0, 4)));
+        assert!(Span::new(0, 4).overlaps(&Span::new(1, 2)));
+        assert!(!Span::new(0, 4).overlaps(&Span::new(4, 5)));
+        assert!(!Span::new(4, 5).overlaps(&Span::new(0, 4)));
+    }
+
+    #[test]
+    fn test_span_contains() {
+        assert!(Span::new(0, 4).contains(&Span::new(0, 4)));
+        assert!(Span::new(0, 4).contains(&Span::new(1, 3)));
+        assert!(!Span::new(0, 4).contains(&Span::new(3, 5)));
+        assert!(!Span::new(1, 4).contains(&Span::new(0, 2)));
+    }
+}
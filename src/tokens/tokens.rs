@@ -1,11 +1,29 @@
-#[derive(Debug, PartialEq)]
-pub enum Token {
+use crate::tokens::span::Span;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TokenKind {
     WhiteSpace,
+    Newline,
+    Comment,
     SingleQuote,
     DoubleQuote,
     DollarSingleQuote,
+    DollarDoubleQuote,
     Keyword,
     Number,
+    Word,
+    Pattern,           // A word with an unquoted `*`, `?` or `[...]`
+    HereDocument,      // The lines after a `<<` redirection, up to and including the delimiter
+    ArithmeticCommand, // ((...)) where a command starts and after `for`
+    // Expansions :
+    ParameterExpansion,  // $name, ${...}
+    CommandSubstitution, // $(...), `...`
+    ArithmeticExpansion, // $((...))
+    ProcessSubstitution, // <(...), >(...)
+    BraceExpansion,      // {a,b}, {1..3}
+    TildePrefix,         // ~, ~user, ~+, ~-, ~N
+    HistoryExpansion,    // !!, !$, !-2:h, ^old^new, see `Dialect::history_expansion`
     // Metacharacters :
     Pipe,         // |
     Ampersand,    // &
@@ -14,4 +32,94 @@ pub enum Token {
     RParenthesis, // )
     LessAnd,      // <
     GreatAnd,     // >
+    // Case patterns, the metacharacters around the patterns of a `case` item :
+    CasePatternStart,     // ( before the first pattern, it's optional
+    CasePatternSeparator, // | between patterns
+    CasePatternEnd,       // ) after the last pattern
+    // Operators :
+    AndIf,              // &&
+    OrIf,               // ||
+    DoubleSemicolon,    // ;;
+    SemicolonAmpersand, // ;&
+    DoubleLess,         // <<
+    DoubleLessDash,     // <<-
+    DoubleGreat,        // >>
+    LessAmpersand,      // <&
+    GreatAmpersand,     // >&
+    LessGreat,          // <>
+    Clobber,            // >|
+    // Bash operators :
+    TripleLess,               // <<<
+    AmpersandGreat,           // &>
+    AmpersandDoubleGreat,     // &>>
+    PipeAmpersand,            // |&
+    DoubleSemicolonAmpersand, // ;;&
+}
+
+impl TokenKind {
+    /// Tokens that are (part of) a word, adjacent ones form a single word : `a"b"$c`
+    pub fn is_word(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Word
+                | TokenKind::Pattern
+                | TokenKind::Keyword
+                | TokenKind::Number
+                | TokenKind::SingleQuote
+                | TokenKind::DoubleQuote
+                | TokenKind::DollarSingleQuote
+                | TokenKind::DollarDoubleQuote
+                | TokenKind::ParameterExpansion
+                | TokenKind::CommandSubstitution
+                | TokenKind::ArithmeticExpansion
+                | TokenKind::ProcessSubstitution
+                | TokenKind::BraceExpansion
+                | TokenKind::TildePrefix
+                | TokenKind::HistoryExpansion
+        )
+    }
+
+    /// Operators that are followed by the word they redirect to : `<`, `>>`, `2>&1`, ...
+    pub fn is_redirection(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::LessAnd
+                | TokenKind::GreatAnd
+                | TokenKind::DoubleLess
+                | TokenKind::DoubleLessDash
+                | TokenKind::DoubleGreat
+                | TokenKind::LessAmpersand
+                | TokenKind::GreatAmpersand
+                | TokenKind::LessGreat
+                | TokenKind::Clobber
+                | TokenKind::TripleLess
+                | TokenKind::AmpersandGreat
+                | TokenKind::AmpersandDoubleGreat
+        )
+    }
+}
+
+/// A token keeps the exact text it was tokenized from, so concatenating the text of all tokens gives back
+/// the input, whitespace and comments included.
+///
+/// Tokens from the value of an alias, see [tokenize_with_aliases](crate::alias::tokenize_with_aliases),
+/// name the alias in `alias` and share the span of the word the alias replaced, so spans can overlap.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub span: Span,
+    pub alias: Option<String>,
+}
+
+impl Token {
+    pub fn new(kind: TokenKind, text: &str, span: Span) -> Token {
+        Token {
+            kind,
+            text: text.to_string(),
+            span,
+            alias: None,
+        }
+    }
 }
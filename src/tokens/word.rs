@@ -0,0 +1,386 @@
+use crate::dialect::Dialect;
+use crate::lexer::arithmetic_expansion::tokenize_arithmetic_expansion;
+use crate::lexer::command_substitution::tokenize_command_substitution;
+use crate::lexer::history_expansion::tokenize_history_expansion;
+use crate::lexer::parameter_expansion::tokenize_parameter_expansion;
+use crate::tokenize_with_dialect;
+use crate::tokens::span::Span;
+use crate::tokens::tokens::{Token, TokenKind};
+use std::error::Error;
+
+/// A shell word and the parts it's made of, in the order they're written : `pre"$x"'lit'$'\n'${y}post` is
+/// a literal, a double-quoted string holding a parameter expansion, a single-quoted string, a `$'...'`
+/// string, a parameter expansion and another literal.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Word {
+    pub parts: Vec<WordPart>,
+    pub span: Span,
+}
+
+/// A segment of a word, `text` is its source text with quotes and escapes as they're written.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WordPart {
+    pub kind: WordPartKind,
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WordPartKind {
+    Literal,        // Unquoted text, backslash escapes included : `a\ b`
+    Pattern,        // Unquoted text with wildcards : `*.txt`
+    TildePrefix,    // ~, ~user
+    BraceExpansion, // {a,b}, {1..3}
+    SingleQuoted,
+    DollarSingleQuoted,
+    DoubleQuoted(Vec<WordPart>), // The literals and expansions between the quotes
+    DollarDoubleQuoted(Vec<WordPart>),
+    ParameterExpansion,
+    CommandSubstitution,
+    ArithmeticExpansion,
+    ProcessSubstitution,
+    HistoryExpansion, // !!, !$, also between double quotes
+}
+
+impl Word {
+    /// Parses `content` as a single word, like an argument would be tokenized
+    pub fn parse(content: &str, dialect: &Dialect) -> Result<Word, Box<dyn Error>> {
+        let tokens = tokenize_with_dialect(content.to_string(), dialect)?;
+        if let Some(token) = tokens.iter().find(|token| !token.kind.is_word()) {
+            return Err(format!(
+                "Expected a single word, found `{}` at index {}",
+                token.text, token.span.start
+            )
+            .into());
+        }
+        Ok(words(&tokens, dialect).pop().unwrap_or(Word {
+            parts: Vec::new(),
+            span: Span::new(0, 0),
+        }))
+    }
+
+    /// The source text of the word
+    pub fn text(&self) -> String {
+        self.parts.iter().map(|part| part.text.as_str()).collect()
+    }
+
+    /// Whether part of the word is quoted, `""` is an empty argument while nothing is no argument at all
+    pub fn is_quoted(&self) -> bool {
+        self.parts.iter().any(WordPart::is_quoted)
+    }
+
+    /// Whether the value of the word depends on the environment, expansions in quotes included
+    pub fn has_expansions(&self) -> bool {
+        self.parts.iter().any(WordPart::has_expansions)
+    }
+}
+
+impl WordPart {
+    /// The part for a token of a word, `None` for tokens that aren't (part of) a word
+    pub fn from_token(token: &Token, dialect: &Dialect) -> Option<WordPart> {
+        let kind = match token.kind {
+            TokenKind::Word | TokenKind::Keyword | TokenKind::Number => WordPartKind::Literal,
+            TokenKind::Pattern => WordPartKind::Pattern,
+            TokenKind::TildePrefix => WordPartKind::TildePrefix,
+            TokenKind::BraceExpansion => WordPartKind::BraceExpansion,
+            TokenKind::SingleQuote => WordPartKind::SingleQuoted,
+            TokenKind::DollarSingleQuote => WordPartKind::DollarSingleQuoted,
+            TokenKind::DoubleQuote => WordPartKind::DoubleQuoted(quoted_parts(
+                &token.text[1..token.text.len() - 1],
+                token.span.start + 1,
+                dialect,
+            )),
+            TokenKind::DollarDoubleQuote => WordPartKind::DollarDoubleQuoted(quoted_parts(
+                &token.text[2..token.text.len() - 1],
+                token.span.start + 2,
+                dialect,
+            )),
+            TokenKind::ParameterExpansion => WordPartKind::ParameterExpansion,
+            TokenKind::CommandSubstitution => WordPartKind::CommandSubstitution,
+            TokenKind::ArithmeticExpansion => WordPartKind::ArithmeticExpansion,
+            TokenKind::ProcessSubstitution => WordPartKind::ProcessSubstitution,
+            TokenKind::HistoryExpansion => WordPartKind::HistoryExpansion,
+            _ => return None,
+        };
+        Some(WordPart {
+            kind,
+            text: token.text.clone(),
+            span: token.span,
+        })
+    }
+
+    pub fn is_quoted(&self) -> bool {
+        matches!(
+            self.kind,
+            WordPartKind::SingleQuoted
+                | WordPartKind::DollarSingleQuoted
+                | WordPartKind::DoubleQuoted(_)
+                | WordPartKind::DollarDoubleQuoted(_)
+        )
+    }
+
+    pub fn has_expansions(&self) -> bool {
+        match &self.kind {
+            WordPartKind::Literal
+            | WordPartKind::SingleQuoted
+            | WordPartKind::DollarSingleQuoted => false,
+            WordPartKind::DoubleQuoted(parts) | WordPartKind::DollarDoubleQuoted(parts) => {
+                parts.iter().any(WordPart::has_expansions)
+            }
+            _ => true,
+        }
+    }
+}
+
+/// words
+/// Groups adjacent word tokens from [crate::tokenize] into words, `'a'"b"c` is a single word of three parts.
+/// Line continuations between words aren't words themselves.
+pub fn words(tokens: &[Token], dialect: &Dialect) -> Vec<Word> {
+    let mut words: Vec<Word> = Vec::new();
+    let mut word: Option<Word> = None;
+    for token in tokens {
+        let Some(part) = WordPart::from_token(token, dialect) else {
+            words.extend(word.take());
+            continue;
+        };
+        let word = word.get_or_insert_with(|| Word {
+            parts: Vec::new(),
+            span: Span::new(token.span.start, token.span.start),
+        });
+        word.span.end = token.span.end;
+        word.parts.push(part);
+    }
+    words.extend(word);
+    words.retain(|word| !word.text().split("\\\n").all(str::is_empty));
+    words
+}
+
+/// The literals and expansions inside a double-quoted string, `offset` is where `inner` is located.
+/// > The characters ‘$’ and ‘`’ retain their special meaning within double quotes [...] If enabled, history
+/// > expansion will be performed unless an ‘!’ appearing in double quotes is escaped using a backslash.
+fn quoted_parts(inner: &str, offset: usize, dialect: &Dialect) -> Vec<WordPart> {
+    let chars: Vec<char> = inner.chars().collect();
+    let offsets: Vec<usize> = inner
+        .char_indices()
+        .map(|(position, _)| position)
+        .chain(std::iter::once(inner.len()))
+        .collect();
+    let mut parts = Vec::new();
+    let mut push = |kind: WordPartKind, start: usize, end: usize| {
+        let span = Span::new(offset + offsets[start], offset + offsets[end]);
+        parts.push(WordPart {
+            kind,
+            text: inner[offsets[start]..offsets[end]].to_string(),
+            span,
+        });
+    };
+
+    let length = offsets.len() - 1;
+    let (mut pointer, mut literal_start) = (0, 0);
+    while pointer < length {
+        match chars.get(pointer) {
+            Some('\\') => {
+                pointer = (pointer + 2).min(length);
+                continue;
+            }
+            Some('$' | '`') => {}
+            Some('!') if dialect.history_expansion => {}
+            _ => {
+                pointer += 1;
+                continue;
+            }
+        }
+        // A `!` right before the closing quote is a literal, `inner` ends there :
+        let history_pointer = tokenize_history_expansion(&chars, pointer, dialect);
+        let expansion = [
+            (WordPartKind::HistoryExpansion, Ok(history_pointer)),
+            (
+                WordPartKind::ArithmeticExpansion,
+                tokenize_arithmetic_expansion(&chars, pointer, dialect),
+            ),
+            (
+                WordPartKind::CommandSubstitution,
+                tokenize_command_substitution(&chars, pointer, dialect),
+            ),
+            (
+                WordPartKind::ParameterExpansion,
+                tokenize_parameter_expansion(&chars, pointer, dialect),
+            ),
+        ]
+        .into_iter()
+        .find_map(|(kind, result)| match result {
+            Ok(end) if end != pointer => Some((kind, end)),
+            _ => None,
+        });
+        match expansion {
+            Some((kind, end)) => {
+                if literal_start != pointer {
+                    push(WordPartKind::Literal, literal_start, pointer);
+                }
+                push(kind, pointer, end);
+                (pointer, literal_start) = (end, end);
+            }
+            None => pointer += 1, // A lone `$`
+        }
+    }
+    if literal_start != length {
+        push(WordPartKind::Literal, literal_start, length);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::Dialect;
+    use crate::tokenize;
+    use crate::tokens::span::Span;
+    use crate::tokens::word::{words, Word, WordPart, WordPartKind};
+
+    fn part(kind: WordPartKind, text: &str, start: usize) -> WordPart {
+        WordPart {
+            kind,
+            text: text.to_string(),
+            span: Span::new(start, start + text.len()),
+        }
+    }
+
+    #[test]
+    fn test_word_parse() {
+        let word = Word::parse("pre\"$x\"'lit'$'\\n'${y}post", &Dialect::BASH).unwrap();
+        assert_eq!(Span::new(0, 25), word.span);
+        assert_eq!(
+            vec![
+                part(WordPartKind::Literal, "pre", 0),
+                part(
+                    WordPartKind::DoubleQuoted(vec![part(
+                        WordPartKind::ParameterExpansion,
+                        "$x",
+                        4
+                    )]),
+                    "\"$x\"",
+                    3
+                ),
+                part(WordPartKind::SingleQuoted, "'lit'", 7),
+                part(WordPartKind::DollarSingleQuoted, "$'\\n'", 12),
+                part(WordPartKind::ParameterExpansion, "${y}", 17),
+                part(WordPartKind::Literal, "post", 21),
+            ],
+            word.parts
+        );
+        assert_eq!("pre\"$x\"'lit'$'\\n'${y}post", word.text());
+        assert!(word.is_quoted());
+        assert!(word.has_expansions());
+
+        assert_eq!(
+            "Expected a single word, found ` ` at index 1",
+            Word::parse("a b", &Dialect::BASH).unwrap_err().to_string()
+        );
+        assert_eq!(
+            Vec::<WordPart>::new(),
+            Word::parse("", &Dialect::BASH).unwrap().parts
+        );
+    }
+
+    #[test]
+    fn test_word_double_quoted_parts() {
+        let word = Word::parse("\"a $b\\$c $((1+2))`id`$ é$(x)\"", &Dialect::BASH).unwrap();
+        let WordPartKind::DoubleQuoted(parts) = &word.parts[0].kind else {
+            panic!("Expected a double-quoted part");
+        };
+        assert_eq!(
+            &vec![
+                part(WordPartKind::Literal, "a ", 1),
+                part(WordPartKind::ParameterExpansion, "$b", 3),
+                part(WordPartKind::Literal, "\\$c ", 5),
+                part(WordPartKind::ArithmeticExpansion, "$((1+2))", 9),
+                part(WordPartKind::CommandSubstitution, "`id`", 17),
+                part(WordPartKind::Literal, "$ é", 21),
+                part(WordPartKind::CommandSubstitution, "$(x)", 25),
+            ],
+            parts
+        );
+
+        let word = Word::parse("$\"hello $name\"\"\"", &Dialect::BASH).unwrap();
+        assert_eq!(
+            vec![
+                part(
+                    WordPartKind::DollarDoubleQuoted(vec![
+                        part(WordPartKind::Literal, "hello ", 2),
+                        part(WordPartKind::ParameterExpansion, "$name", 8),
+                    ]),
+                    "$\"hello $name\"",
+                    0
+                ),
+                part(WordPartKind::DoubleQuoted(Vec::new()), "\"\"", 14),
+            ],
+            word.parts
+        );
+        assert!(!Word::parse("\"a\"'b'c", &Dialect::BASH)
+            .unwrap()
+            .has_expansions());
+    }
+
+    #[test]
+    fn test_word_double_quoted_history_expansion() {
+        let interactive = Dialect {
+            history_expansion: true,
+            ..Dialect::BASH
+        };
+        let word = Word::parse("\"a !! \\!$ b!\"", &interactive).unwrap();
+        let WordPartKind::DoubleQuoted(parts) = &word.parts[0].kind else {
+            panic!("Expected a double-quoted part");
+        };
+        assert_eq!(
+            &vec![
+                part(WordPartKind::Literal, "a ", 1),
+                part(WordPartKind::HistoryExpansion, "!!", 3),
+                part(WordPartKind::Literal, " \\!$ b!", 5),
+            ],
+            parts
+        );
+        let history = |content: &str| {
+            let word = Word::parse(content, &interactive).unwrap();
+            match &word.parts[0].kind {
+                WordPartKind::DoubleQuoted(parts) => parts
+                    .iter()
+                    .filter(|part| part.kind == WordPartKind::HistoryExpansion)
+                    .map(|part| part.text.clone())
+                    .collect::<Vec<_>>(),
+                _ => panic!("Expected a double-quoted part"),
+            }
+        };
+        assert_eq!(vec!["!!"], history("\"!!\""));
+        assert_eq!(vec!["!$"], history("\"x !$ y\""));
+        assert_eq!(vec!["!:1-3"], history("\"!:1-3\""));
+        assert_eq!(vec!["!!:s/a/\"/"], history("\"!!:s/a/\"/\""));
+        assert!(!Word::parse("\"a !!\"", &Dialect::BASH)
+            .unwrap()
+            .has_expansions());
+    }
+
+    #[test]
+    fn test_words() {
+        let tokens = tokenize(String::from("cp ~/a{,.bak} *.txt \\\n 'x'y; echo $(id)")).unwrap();
+        let words = words(&tokens, &Dialect::BASH);
+        assert_eq!(
+            vec!["cp", "~/a{,.bak}", "*.txt", "'x'y", "echo", "$(id)"],
+            words.iter().map(Word::text).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                part(WordPartKind::TildePrefix, "~", 3),
+                part(WordPartKind::Literal, "/a", 4),
+                part(WordPartKind::BraceExpansion, "{,.bak}", 6),
+            ],
+            words[1].parts
+        );
+        assert_eq!(WordPartKind::Pattern, words[2].parts[0].kind);
+        assert_eq!(Span::new(23, 27), words[3].span);
+        assert!(!words[0].is_quoted());
+        assert!(words[3].is_quoted());
+        assert!(!words[3].has_expansions());
+    }
+}
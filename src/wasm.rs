@@ -0,0 +1,63 @@
+use crate::dialect::Dialect;
+use crate::tokenize_with_dialect;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// tokenize
+/// The tokens of `script` as an array of `{kind, text, span: {start, end}, alias}` objects, the JSON of the
+/// `serde` feature. `dialect` is `"bash"` when it's left out, or `"posix"`, `"dash"`, `"ksh93"`, `"zsh"` or `"ash"`.
+///
+/// Spans are byte offsets into the UTF-8 encoded script, the same a Rust or Python backend gets, not the
+/// UTF-16 indexes of a JavaScript string : the text of every token is there to avoid slicing. Invalid
+/// scripts throw an `Error` with the message of the tokenizer.
+#[wasm_bindgen(js_name = tokenize)]
+pub fn tokenize_js(script: String, dialect: Option<String>) -> Result<JsValue, JsError> {
+    let dialect: Dialect = match dialect {
+        Some(name) => name
+            .parse()
+            .map_err(|error: Box<dyn std::error::Error>| JsError::new(&error.to_string()))?,
+        None => Dialect::BASH,
+    };
+    let tokens = tokenize_with_dialect(script, &dialect)
+        .map_err(|error| JsError::new(&error.to_string()))?;
+    // `null` rather than `undefined` for tokens that don't come from an alias, like `JSON.parse` gives :
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    tokens
+        .serialize(&serializer)
+        .map_err(|error| JsError::new(&error.to_string()))
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use crate::wasm::tokenize_js;
+    use wasm_bindgen::JsValue;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn json(value: &JsValue) -> String {
+        js_sys::JSON::stringify(value).unwrap().into()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_tokenize_js() {
+        let tokens = tokenize_js(String::from("ls é"), None).unwrap();
+        assert_eq!(
+            concat!(
+                r#"[{"kind":"Word","text":"ls","span":{"start":0,"end":2},"alias":null},"#,
+                r#"{"kind":"WhiteSpace","text":" ","span":{"start":2,"end":3},"alias":null},"#,
+                r#"{"kind":"Word","text":"é","span":{"start":3,"end":5},"alias":null}]"#
+            ),
+            json(&tokens)
+        );
+        let tokens = tokenize_js(String::from("a |& b"), Some(String::from("dash"))).unwrap();
+        assert_eq!(
+            r#"{"kind":"Pipe","text":"|","span":{"start":2,"end":3},"alias":null}"#,
+            json(&js_sys::Array::from(&tokens).get(2))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_tokenize_js_errors() {
+        assert!(tokenize_js(String::from("echo 'a"), None).is_err());
+        assert!(tokenize_js(String::from("ls"), Some(String::from("fish"))).is_err());
+    }
+}